@@ -1,12 +1,18 @@
-use reqwest::header::HeaderMap;
+use reqwest::header::{InvalidHeaderValue, ToStrError};
+use reqwest::StatusCode;
+#[cfg(feature = "decimal")]
+use rust_decimal::RoundingStrategy;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::fs;
+use std::hash::{BuildHasher, Hasher};
 use std::str::FromStr;
 use std::string::ToString;
+use std::time::Duration;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 //
@@ -14,6 +20,16 @@ use std::string::ToString;
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Number type used for prices, levels, sizes, stops and limits. It is an exact decimal when the
+/// `decimal` feature is enabled.
+#[cfg(feature = "decimal")]
+pub type Number = rust_decimal::Decimal;
+
+/// Number type used for prices, levels, sizes, stops and limits. Enable the `decimal` feature to
+/// use an exact decimal instead.
+#[cfg(not(feature = "decimal"))]
+pub type Number = f64;
+
 /// Enum to represent the execution environment (DEMO or LIVE).
 #[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum ExecutionEnvironment {
@@ -90,6 +106,9 @@ pub struct ApiConfig {
     /// The base URL for the live environment (loaded from IG_BASE_URL_LIVE env var).
     #[serde(skip_deserializing)]
     pub base_url_live: String,
+    /// Send the password RSA-encrypted with the key returned by GET /session/encryptionKey
+    /// when logging in, instead of in plain text.
+    pub encrypted_password: Option<bool>,
     /// The execution environment (loaded from IG_EXECUTION_ENVIRONMENT env var: DEMO or LIVE).
     #[serde(skip_deserializing)]
     pub execution_environment: ExecutionEnvironment,
@@ -98,6 +117,10 @@ pub struct ApiConfig {
     /// Your user password (loaded from IG_PASSWORD env var).
     #[serde(skip_deserializing)]
     pub password: String,
+    /// Client-side rate limiter settings. Requests are not rate limited if not set.
+    pub rate_limiter: Option<RateLimiterConfig>,
+    /// Retry policy for failed requests. Requests are not retried if not set.
+    pub retry_policy: Option<RetryPolicy>,
     /// The session version to use for login requests.
     pub session_version: Option<usize>,
     /// The maximum number of connection attempts for the streaming API.
@@ -118,9 +141,12 @@ impl ApiConfig {
             auto_login: None,
             base_url_demo: "".to_string(),
             base_url_live: "".to_string(),
+            encrypted_password: None,
             execution_environment: ExecutionEnvironment::Demo,
             logger: LogType::StdLogs,
             password: "".to_string(),
+            rate_limiter: None,
+            retry_policy: None,
             session_version: None,
             streaming_api_max_connection_attempts: None,
             username: "".to_string(),
@@ -213,6 +239,131 @@ impl Default for ApiConfig {
     }
 }
 
+/// Enum to represent the request allowances tracked by the client-side rate limiter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RateLimitBucket {
+    /// Historical price data points per account per week.
+    HistoricalDataPoints,
+    /// Non-trading requests per application per minute.
+    NonTradingRequests,
+    /// Trading requests per account per minute.
+    TradingRequests,
+}
+
+/// Implement the Display trait for RateLimitBucket to provide custom string representation.
+impl std::fmt::Display for RateLimitBucket {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RateLimitBucket::HistoricalDataPoints => write!(f, "historical data points per week"),
+            RateLimitBucket::NonTradingRequests => write!(f, "non-trading requests per minute"),
+            RateLimitBucket::TradingRequests => write!(f, "trading requests per minute"),
+        }
+    }
+}
+
+/// Enum to represent what the rate limiter does with a request exceeding an allowance.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum RateLimitMode {
+    /// Wait until the allowance has enough budget and then send the request.
+    #[default]
+    Queue,
+    /// Fail the request straight away with IgError::RateLimited.
+    Reject,
+}
+
+/// Struct to hold the client-side rate limiter settings. The defaults match the allowances
+/// published by IG.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RateLimiterConfig {
+    /// Maximum number of historical price data points per week.
+    pub historical_data_points_per_week: u32,
+    /// What to do with a request exceeding an allowance.
+    pub mode: RateLimitMode,
+    /// Maximum number of non-trading requests per minute.
+    pub non_trading_requests_per_minute: u32,
+    /// Maximum number of trading requests per minute.
+    pub trading_requests_per_minute: u32,
+}
+
+impl Default for RateLimiterConfig {
+    fn default() -> Self {
+        Self {
+            historical_data_points_per_week: 10_000,
+            mode: RateLimitMode::Queue,
+            non_trading_requests_per_minute: 60,
+            trading_requests_per_minute: 100,
+        }
+    }
+}
+
+/// Struct to hold the retry policy for failed requests.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Delay before the first retry in milliseconds. It is doubled after every attempt.
+    pub initial_backoff_ms: u64,
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Maximum delay between attempts in milliseconds.
+    pub max_backoff_ms: u64,
+    /// Also retry POST, PUT and DELETE requests, e.g. trading requests. Enabling it may result
+    /// in duplicated deals if a request reached IG but its response was lost.
+    pub retry_non_idempotent: bool,
+    /// Retry requests that failed to be sent or whose response was not received, e.g. because
+    /// of a connection reset.
+    pub retry_transport_errors: bool,
+    /// HTTP status codes of the responses that are retried.
+    pub retryable_status_codes: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff_ms: 500,
+            max_attempts: 3,
+            max_backoff_ms: 10_000,
+            retry_non_idempotent: false,
+            retry_transport_errors: true,
+            retryable_status_codes: vec![500, 502, 503, 504],
+        }
+    }
+}
+
+/// Implementation for the RetryPolicy struct.
+impl RetryPolicy {
+    /// Returns the delay before retrying after the given failed attempt, starting at 1. The
+    /// delay grows exponentially and a random jitter of up to half of it is subtracted, so
+    /// that concurrent clients don't retry in lockstep.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff_ms = self
+            .initial_backoff_ms
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff_ms);
+
+        // Random number between 0 and 1 without pulling in a random number generator crate.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(attempt);
+        let jitter = (hasher.finish() % 1_000) as f64 / 1_000.0;
+
+        Duration::from_millis(backoff_ms - (backoff_ms as f64 * jitter / 2.0) as u64)
+    }
+
+    /// Check whether a request that failed with the given error should be retried.
+    pub fn is_retryable_error(&self, error: &IgError) -> bool {
+        match error {
+            IgError::Transport(_) => self.retry_transport_errors,
+            _ => false,
+        }
+    }
+
+    /// Check whether a request whose response has the given status code should be retried.
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retryable_status_codes.contains(&status.as_u16())
+    }
+}
+
 /// Struct to represent API errors.
 #[derive(Debug)]
 pub struct ApiError {
@@ -231,12 +382,293 @@ impl std::fmt::Display for ApiError {
 /// Implement the Error trait for ApiError to handle errors.
 impl std::error::Error for ApiError {}
 
+/// Enum to represent the errors returned by the REST API.
+#[derive(Debug)]
+pub enum IgError {
+    /// The API responded with an IG error code.
+    Api {
+        /// The HTTP status code of the response.
+        status: StatusCode,
+        /// The IG error code found in the response body.
+        error_code: IgErrorCode,
+    },
+    /// A response could not be deserialized.
+    Deserialization {
+        /// Path to the field that failed to deserialize, e.g. `accounts[0].balance`.
+        path: String,
+        /// The deserialization error message.
+        message: String,
+    },
+    /// The API responded with an unexpected HTTP status code and no IG error code.
+    Http {
+        /// The HTTP status code of the response.
+        status: StatusCode,
+        /// The response body.
+        body: String,
+    },
+    /// Any other error, e.g. an invalid header value.
+    Other {
+        /// The error message.
+        message: String,
+    },
+    /// The request was rejected by the client-side rate limiter.
+    RateLimited {
+        /// The allowance that would have been exceeded.
+        bucket: RateLimitBucket,
+        /// Time until the allowance has enough budget for the request.
+        retry_after: Duration,
+    },
+    /// The request could not be sent or the response could not be received.
+    Transport(Box<dyn Error + Send + Sync>),
+    /// A request or response failed validation.
+    Validation {
+        /// The validation error message.
+        message: String,
+    },
+}
+
+/// Implementation for the IgError enum.
+impl IgError {
+    /// Returns the IG error code, if any.
+    pub fn error_code(&self) -> Option<&IgErrorCode> {
+        match self {
+            IgError::Api { error_code, .. } => Some(error_code),
+            _ => None,
+        }
+    }
+
+    /// Build an error from a response with an unexpected status code, parsing the IG error
+    /// code from its body when present.
+    pub fn from_response(status: StatusCode, body: &str) -> Self {
+        let error_code = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|value| value.get("errorCode")?.as_str().map(IgErrorCode::from));
+
+        match error_code {
+            Some(error_code) => IgError::Api { status, error_code },
+            None => IgError::Http {
+                status,
+                body: body.to_string(),
+            },
+        }
+    }
+
+    /// Returns the HTTP status code of the response, if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            IgError::Api { status, .. } | IgError::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+/// Implement the Display trait for IgError to provide custom string representation.
+impl std::fmt::Display for IgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IgError::Api { status, error_code } => {
+                write!(f, "API error with status code {}: {}", status, error_code)
+            }
+            IgError::Deserialization { path, message } => {
+                write!(f, "Failed to deserialize field '{}': {}", path, message)
+            }
+            IgError::Http { status, body } => {
+                write!(f, "HTTP error with status code {}: {}", status, body)
+            }
+            IgError::Other { message } => write!(f, "Error: {}", message),
+            IgError::RateLimited {
+                bucket,
+                retry_after,
+            } => write!(
+                f,
+                "Rate limit of {} exceeded, retry after {:?}",
+                bucket, retry_after
+            ),
+            IgError::Transport(e) => write!(f, "Transport error: {}", e),
+            IgError::Validation { message } => write!(f, "Validation failed: {}", message),
+        }
+    }
+}
+
+/// Implement the Error trait for IgError to handle errors.
+impl std::error::Error for IgError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IgError::Transport(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ApiError> for IgError {
+    fn from(e: ApiError) -> Self {
+        IgError::Other { message: e.message }
+    }
+}
+
+impl From<InvalidHeaderValue> for IgError {
+    fn from(e: InvalidHeaderValue) -> Self {
+        IgError::Other {
+            message: format!("Invalid header value: {}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for IgError {
+    fn from(e: reqwest::Error) -> Self {
+        IgError::Transport(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for IgError {
+    fn from(e: serde_json::Error) -> Self {
+        IgError::Deserialization {
+            path: String::new(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for IgError {
+    fn from(e: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        IgError::Deserialization {
+            path: e.path().to_string(),
+            message: e.into_inner().to_string(),
+        }
+    }
+}
+
+impl From<serde_urlencoded::ser::Error> for IgError {
+    fn from(e: serde_urlencoded::ser::Error) -> Self {
+        IgError::Other {
+            message: format!("Failed to serialize query parameters: {}", e),
+        }
+    }
+}
+
+impl From<ToStrError> for IgError {
+    fn from(e: ToStrError) -> Self {
+        IgError::Other {
+            message: format!("Header value is not valid ASCII: {}", e),
+        }
+    }
+}
+
+/// Enum to represent the error codes returned by the IG REST API in the errorCode field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IgErrorCode {
+    /// error.security.account-suspended
+    AccountSuspended,
+    /// error.security.api-key-invalid
+    ApiKeyInvalid,
+    /// error.security.client-token-invalid
+    ClientTokenInvalid,
+    /// error.public-api.exceeded-account-allowance
+    ExceededAccountAllowance,
+    /// error.public-api.exceeded-account-historical-data-allowance
+    ExceededAccountHistoricalDataAllowance,
+    /// error.public-api.exceeded-account-trading-allowance
+    ExceededAccountTradingAllowance,
+    /// error.public-api.exceeded-api-key-allowance
+    ExceededApiKeyAllowance,
+    /// error.security.invalid-details
+    InvalidDetails,
+    /// error.security.oauth-token-invalid
+    OauthTokenInvalid,
+    /// Any other error code.
+    Other(String),
+}
+
+/// Implementation for the IgErrorCode enum.
+impl IgErrorCode {
+    /// Returns the error code as sent by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            IgErrorCode::AccountSuspended => "error.security.account-suspended",
+            IgErrorCode::ApiKeyInvalid => "error.security.api-key-invalid",
+            IgErrorCode::ClientTokenInvalid => "error.security.client-token-invalid",
+            IgErrorCode::ExceededAccountAllowance => "error.public-api.exceeded-account-allowance",
+            IgErrorCode::ExceededAccountHistoricalDataAllowance => {
+                "error.public-api.exceeded-account-historical-data-allowance"
+            }
+            IgErrorCode::ExceededAccountTradingAllowance => {
+                "error.public-api.exceeded-account-trading-allowance"
+            }
+            IgErrorCode::ExceededApiKeyAllowance => "error.public-api.exceeded-api-key-allowance",
+            IgErrorCode::InvalidDetails => "error.security.invalid-details",
+            IgErrorCode::OauthTokenInvalid => "error.security.oauth-token-invalid",
+            IgErrorCode::Other(error_code) => error_code,
+        }
+    }
+
+    /// Check whether the error code means that an API allowance has been exceeded.
+    pub fn is_allowance_exceeded(&self) -> bool {
+        matches!(
+            self,
+            IgErrorCode::ExceededAccountAllowance
+                | IgErrorCode::ExceededAccountHistoricalDataAllowance
+                | IgErrorCode::ExceededAccountTradingAllowance
+                | IgErrorCode::ExceededApiKeyAllowance
+        )
+    }
+
+    /// Check whether the error code means that the session tokens are no longer valid.
+    pub fn is_session_expired(&self) -> bool {
+        matches!(
+            self,
+            IgErrorCode::ClientTokenInvalid | IgErrorCode::OauthTokenInvalid
+        )
+    }
+}
+
+impl From<&str> for IgErrorCode {
+    fn from(error_code: &str) -> Self {
+        match error_code {
+            "error.security.account-suspended" => IgErrorCode::AccountSuspended,
+            "error.security.api-key-invalid" => IgErrorCode::ApiKeyInvalid,
+            "error.security.client-token-invalid" => IgErrorCode::ClientTokenInvalid,
+            "error.public-api.exceeded-account-allowance" => IgErrorCode::ExceededAccountAllowance,
+            "error.public-api.exceeded-account-historical-data-allowance" => {
+                IgErrorCode::ExceededAccountHistoricalDataAllowance
+            }
+            "error.public-api.exceeded-account-trading-allowance" => {
+                IgErrorCode::ExceededAccountTradingAllowance
+            }
+            "error.public-api.exceeded-api-key-allowance" => IgErrorCode::ExceededApiKeyAllowance,
+            "error.security.invalid-details" => IgErrorCode::InvalidDetails,
+            "error.security.oauth-token-invalid" => IgErrorCode::OauthTokenInvalid,
+            _ => IgErrorCode::Other(error_code.to_string()),
+        }
+    }
+}
+
+/// Implement the Display trait for IgErrorCode to show the error code as sent by the API.
+impl std::fmt::Display for IgErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 //
 // UTILITY FUNCTIONS.
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Get the number of decimal places of a number, ignoring trailing zeros.
+#[cfg(feature = "decimal")]
+pub fn decimal_places(value: Number) -> u32 {
+    value.normalize().scale()
+}
+
+/// Get the number of decimal places of a number, ignoring trailing zeros.
+#[cfg(not(feature = "decimal"))]
+pub fn decimal_places(value: Number) -> u32 {
+    format!("{}", value)
+        .split_once('.')
+        .map_or(0, |(_, decimals)| decimals.len() as u32)
+}
+
 /// Convert a serializable object representing GET parameters to a query string.
 pub fn params_to_query_string<T: Serialize>(
     data: &T,
@@ -244,16 +676,27 @@ pub fn params_to_query_string<T: Serialize>(
     serde_urlencoded::to_string(data)
 }
 
-/// Convert a HeaderMap to a JSON serde_json::Value.
-pub fn headers_to_json(headers: &HeaderMap) -> Result<Value, Box<dyn Error>> {
-    let mut map = serde_json::Map::new();
+/// Round a number to the given decimal places, with midpoints rounded away from zero.
+#[cfg(feature = "decimal")]
+pub fn round_to_decimal_places(value: Number, decimal_places: u32) -> Number {
+    value.round_dp_with_strategy(decimal_places, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Round a number to the given decimal places, with midpoints rounded away from zero.
+#[cfg(not(feature = "decimal"))]
+pub fn round_to_decimal_places(value: Number, decimal_places: u32) -> Number {
+    let factor = 10f64.powi(decimal_places as i32);
+    (value * factor).round() / factor
+}
 
-    for (key, value) in headers {
-        map.insert(
-            key.as_str().to_string(),
-            Value::String(value.to_str()?.to_string()),
-        );
+/// Round a number to the nearest multiple of a step. The number is returned unchanged if the
+/// step is zero.
+pub fn round_to_step(value: Number, step: Number) -> Number {
+    if step == Number::default() {
+        return value;
     }
+    let steps = round_to_decimal_places(value / step, 0);
 
-    Ok(Value::Object(map))
+    // Drop the binary noise of the multiplication when using floats.
+    round_to_decimal_places(steps * step, decimal_places(step))
 }
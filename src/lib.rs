@@ -1,6 +1,13 @@
 pub mod common;
 pub mod rest_api;
+pub mod rest_cassette;
 pub mod rest_client;
+pub mod rest_endpoints;
+#[cfg(feature = "fake-server")]
+pub mod rest_fake_server;
 pub mod rest_models;
+pub mod rest_rate_limiter;
 pub mod rest_regex;
+pub mod rest_response;
+pub mod rest_transport;
 pub mod streaming_api;
\ No newline at end of file
@@ -1,8 +1,17 @@
 use crate::common::*;
 use crate::rest_client::*;
+use crate::rest_endpoints::*;
 use crate::rest_models::*;
+use crate::rest_response::{ApiResponse, BatchedApiResponse, ResponseMeta};
+use crate::rest_transport::Transport;
+use futures::future::try_join_all;
+use reqwest::Method;
 use serde_json::Value;
-use std::error::Error;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Maximum number of epics that can be requested at once through the GET /markets endpoint.
+const MAX_EPICS_PER_MARKETS_REQUEST: usize = 50;
 
 /// Struct to encapsulate the API, including the REST HTTP client, the API configuration
 /// and all the methods to interact with the IG REST API.
@@ -17,13 +26,71 @@ pub struct RestApi {
 /// Provide an implementation for the Api struct with all the methods to interact with the IG REST API.
 impl RestApi {
     /// Create a new instance of the RestApi struct based on the provided configuration.
-    pub async fn new(config: ApiConfig) -> Result<Self, Box<dyn Error>> {
+    pub async fn new(config: ApiConfig) -> Result<Self, IgError> {
         Ok(Self {
             client: RestClient::new(config.clone()).await?,
             config,
         })
     }
 
+    /// Create a new instance of the RestApi struct sending its requests through the given transport.
+    pub async fn with_transport(
+        config: ApiConfig,
+        transport: Arc<dyn Transport>,
+    ) -> Result<Self, IgError> {
+        Ok(Self {
+            client: RestClient::with_transport(config.clone(), transport).await?,
+            config,
+        })
+    }
+
+    /// Log in to the REST API. The new session is shared by all the clones of this instance,
+    /// so it can be called concurrently from behind an Arc<RestApi>.
+    pub async fn login(&self) -> Result<Value, IgError> {
+        let (_, body) = self.client.login().await?;
+
+        Ok(body)
+    }
+
+    /// Send the request of the given endpoint and deserialize its response. Path parameters
+    /// are validated here and the request by the REST client.
+    pub async fn execute<E: Endpoint>(
+        &self,
+        endpoint: &E,
+    ) -> Result<ApiResponse<E::Response>, IgError> {
+        endpoint.validate_path_params()?;
+        let path = endpoint.path()?;
+        let request = endpoint.request();
+        let version = Some(E::VERSION);
+
+        // Send the request to the REST client. Requests without body are sent with an empty
+        // JSON object as body.
+        let (meta, response_value) = if E::METHOD == Method::GET {
+            self.client.get(path, version, &request).await?
+        } else if E::METHOD == Method::DELETE {
+            self.client.delete(path, version, &request).await?
+        } else if E::METHOD == Method::POST {
+            match request {
+                Some(request) => self.client.post(path, version, request).await?,
+                None => self.client.post(path, version, &Empty {}).await?,
+            }
+        } else if E::METHOD == Method::PUT {
+            match request {
+                Some(request) => self.client.put(path, version, request).await?,
+                None => self.client.put(path, version, &Empty {}).await?,
+            }
+        } else {
+            return Err(IgError::Validation {
+                message: format!("Unsupported HTTP method {}.", E::METHOD),
+            });
+        };
+
+        // Convert the serde_json::Value response to the endpoint response model.
+        let response = E::Response::from_value(&response_value)?;
+
+        Ok(ApiResponse::new(meta, response))
+    }
+
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
     //
     // ACCOUNT METHODS.
@@ -31,59 +98,59 @@ impl RestApi {
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
     /// Returns a list of the logged-in client's accounts.
-    pub async fn accounts_get(&self) -> Result<(Value, AccountsGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("accounts".to_string(), Some(1), &None::<Empty>)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let accounts = AccountsGetResponse::from_value(&response_value)?;
-
-        Ok((headers, accounts))
+    pub async fn accounts_get(&self) -> Result<ApiResponse<AccountsGetResponse>, IgError> {
+        self.execute(&AccountsGet).await
     }
 
     /// Returns account preferences.
     pub async fn accounts_preferences_get(
         &self,
-    ) -> Result<(Value, AccountsPreferencesGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("accounts/preferences".to_string(), Some(1), &None::<Empty>)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let accounts_preferences = AccountsPreferencesGetResponse::from_value(&response_value)?;
-
-        Ok((headers, accounts_preferences))
+    ) -> Result<ApiResponse<AccountsPreferencesGetResponse>, IgError> {
+        self.execute(&AccountsPreferencesGet).await
     }
 
     /// Updates account preferences.
     pub async fn accounts_preferences_put(
         &self,
         body: &AccountsPreferencesPutRequest,
-    ) -> Result<(Value, AccountsPreferencesStatusPutResponse), Box<dyn Error>> {
-        // Validate the body.
-        body.validate()?;
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .put("accounts/preferences".to_string(), Some(1), body)
-            .await?;
+    ) -> Result<ApiResponse<AccountsPreferencesStatusPutResponse>, IgError> {
+        self.execute(&AccountsPreferencesPut { body }).await
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////////
+    //
+    // CLIENT SENTIMENT METHODS.
+    //
+    ////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Returns the client sentiment for the given markets.
+    pub async fn clientsentiment_get(
+        &self,
+        params: SentimentQuery,
+    ) -> Result<ApiResponse<Sentiments>, IgError> {
+        self.execute(&ClientSentimentGet { params: &params }).await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let status = AccountsPreferencesStatusPutResponse::from_value(&response_value)?;
+    /// Returns the client sentiment for the given market.
+    pub async fn clientsentiment_market_get(
+        &self,
+        market_id: String,
+    ) -> Result<ApiResponse<Sentiment>, IgError> {
+        self.execute(&ClientSentimentMarketGet {
+            market_id: &market_id,
+        })
+        .await
+    }
 
-        Ok((headers, status))
+    /// Returns the client sentiment for markets related to the given market.
+    pub async fn clientsentiment_related_get(
+        &self,
+        market_id: String,
+    ) -> Result<ApiResponse<Sentiments>, IgError> {
+        self.execute(&ClientSentimentRelatedGet {
+            market_id: &market_id,
+        })
+        .await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -97,18 +164,11 @@ impl RestApi {
     pub async fn confirms_get(
         &self,
         params: ConfirmsGetRequest,
-    ) -> Result<(Value, ConfirmsGetResponse), Box<dyn Error>> {
-        let url = format!("confirms/{}", params.deal_reference);
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.get(url, Some(1), &None::<Empty>).await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let confirmations = ConfirmsGetResponse::from_value(&response_value)?;
-
-        Ok((headers, confirmations))
+    ) -> Result<ApiResponse<ConfirmsGetResponse>, IgError> {
+        self.execute(&ConfirmsGet {
+            deal_reference: &params.deal_reference,
+        })
+        .await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -121,38 +181,51 @@ impl RestApi {
     pub async fn history_activity_get(
         &self,
         params: ActivityHistoryGetRequest,
-    ) -> Result<(Value, ActivityHistoryGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("history/activity".to_string(), Some(3), &Some(params))
-            .await?;
+    ) -> Result<ApiResponse<ActivityHistoryGetResponse>, IgError> {
+        self.execute(&HistoryActivityGet { params: &params }).await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let history_activity = ActivityHistoryGetResponse::from_value(&response_value)?;
+    /// Returns the account activity history through version 1 of the API, which covers either
+    /// the last period up to now or a range of days.
+    pub async fn history_activity_v1_get(
+        &self,
+        period: HistoryPeriod,
+    ) -> Result<ApiResponse<ActivityHistoryV1GetResponse>, IgError> {
+        self.execute(&HistoryActivityV1Get { period: &period })
+            .await
+    }
 
-        Ok((headers, history_activity))
+    /// Returns the account activity history through version 2 of the API, which returns the
+    /// version 1 activity items with paging.
+    pub async fn history_activity_v2_get(
+        &self,
+        params: ActivityHistoryV2GetRequest,
+    ) -> Result<ApiResponse<ActivityHistoryV2GetResponse>, IgError> {
+        self.execute(&HistoryActivityV2Get { params: &params })
+            .await
     }
 
     /// Returns the transaction history. Returns the minute prices within the last 10 minutes by default.
     pub async fn history_transactions_get(
         &self,
         params: TransactionHistoryGetRequest,
-    ) -> Result<(Value, TransactionHistoryGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("history/transactions".to_string(), Some(2), &Some(params))
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let history_activity = TransactionHistoryGetResponse::from_value(&response_value)?;
+    ) -> Result<ApiResponse<TransactionHistoryGetResponse>, IgError> {
+        self.execute(&HistoryTransactionsGet { params: &params })
+            .await
+    }
 
-        Ok((headers, history_activity))
+    /// Returns the transaction history through version 1 of the API, which covers either the
+    /// last period up to now or a range of days.
+    pub async fn history_transactions_v1_get(
+        &self,
+        transaction_type: TransactionType,
+        period: HistoryPeriod,
+    ) -> Result<ApiResponse<TransactionHistoryV1GetResponse>, IgError> {
+        self.execute(&HistoryTransactionsV1Get {
+            period: &period,
+            transaction_type: &transaction_type,
+        })
+        .await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -161,42 +234,167 @@ impl RestApi {
     //
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+    /// Returns the details of the given market.
+    pub async fn market_get(&self, epic: Epic) -> Result<ApiResponse<MarketDetails>, IgError> {
+        self.execute(&MarketGet { epic: &epic }).await
+    }
+
+    /// Returns the details of the given market through version 1 of the API.
+    pub async fn market_v1_get(&self, epic: Epic) -> Result<ApiResponse<MarketDetailsV1>, IgError> {
+        self.execute(&MarketV1Get { epic: &epic }).await
+    }
+
+    /// Returns the details of the given market through version 2 of the API.
+    pub async fn market_v2_get(&self, epic: Epic) -> Result<ApiResponse<MarketDetailsV1>, IgError> {
+        self.execute(&MarketV2Get { epic: &epic }).await
+    }
+
     /// Returns all top-level nodes (market categories) in the market navigation hierarchy if no
     /// node is specified. Returns the specified node's children if a node is specified.
     pub async fn marketnavigation_get(
         &self,
         node_id: Option<String>,
-    ) -> Result<(Value, MarketNavigationGetResponse), Box<dyn Error>> {
-        let url = match node_id {
-            Some(node_id) => format!("marketnavigation/{}", node_id),
-            None => "marketnavigation".to_string(),
-        };
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.get(url, Some(1), &None::<Empty>).await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to MarketsGetResponse model.
-        let markets = MarketNavigationGetResponse::from_value(&response_value)?;
-
-        Ok((headers, markets))
+    ) -> Result<ApiResponse<MarketNavigationGetResponse>, IgError> {
+        self.execute(&MarketNavigationGet {
+            node_id: node_id.as_deref(),
+        })
+        .await
     }
     
     /// Returns the details of the given market.
     pub async fn markets_get(
         &self,
         request: MarketsGetRequest
-    ) -> Result<(Value, MarketsGetResponse), Box<dyn Error>> {
-        let (header_map, response_value) = self.client.get("markets".to_string(), Some(2), &Some(request)).await?;
-        
-        // convert the header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        
-        // Convert the serde_json::Value response to MarketsGetResponse model.
-        let markets_response = MarketsGetResponse::from_value(&response_value)?;
-        
-        Ok((headers, markets_response))
+    ) -> Result<ApiResponse<MarketsGetResponse>, IgError> {
+        self.execute(&MarketsGet { params: &request }).await
+    }
+
+    /// Returns the details of any number of markets. The epics are split into batches of at most
+    /// 50, which are requested concurrently, and the results are merged in the order of the given
+    /// epics. Duplicated epics are only requested and returned once. The metadata of each batch
+    /// response is returned in the batches field of the response.
+    pub async fn markets_get_batched(
+        &self,
+        epics: Vec<Epic>,
+        filter: Option<MarketDetailsFilterType>,
+    ) -> Result<BatchedApiResponse<MarketsGetResponse>, IgError> {
+        // Remove duplicated epics while keeping the caller's order.
+        let mut unique_epics: Vec<Epic> = Vec::with_capacity(epics.len());
+        for epic in epics {
+            if !unique_epics.contains(&epic) {
+                unique_epics.push(epic);
+            }
+        }
+
+        // Send one request per batch of epics concurrently.
+        let requests = unique_epics
+            .chunks(MAX_EPICS_PER_MARKETS_REQUEST)
+            .map(|batch| {
+                self.markets_get(MarketsGetRequest {
+                    epics: batch.to_vec(),
+                    filter,
+                })
+            });
+        let responses = try_join_all(requests).await?;
+
+        // Merge the market details of all the batches in the caller's order.
+        let mut metas: Vec<ResponseMeta> = Vec::with_capacity(responses.len());
+        let mut market_details_by_epic: HashMap<Epic, MarketDetails> = HashMap::new();
+        for batch_response in responses {
+            metas.push(batch_response.meta);
+            for market_details in batch_response.data.market_details {
+                market_details_by_epic
+                    .insert(market_details.instrument.epic.clone(), market_details);
+            }
+        }
+        let market_details = unique_epics
+            .iter()
+            .filter_map(|epic| market_details_by_epic.remove(epic))
+            .collect();
+
+        Ok(BatchedApiResponse::new(
+            metas,
+            MarketsGetResponse { market_details },
+        ))
+    }
+
+    /// Returns all markets matching the given search term.
+    pub async fn markets_search(
+        &self,
+        search_term: &str,
+    ) -> Result<ApiResponse<Vec<MarketData>>, IgError> {
+        let params = MarketSearchRequest {
+            search_term: search_term.to_string(),
+        };
+        let response = self.execute(&MarketsSearch { params: &params }).await?;
+
+        Ok(response.map(|market_search| market_search.markets))
+    }
+
+    /// Searches markets by the given term and returns the details of the best match, which is
+    /// the first market whose epic or instrument name exactly matches the term (ignoring case).
+    /// Epic matches take precedence over name matches. Returns None if there is no exact match.
+    pub async fn markets_search_details(
+        &self,
+        search_term: &str,
+    ) -> Result<ApiResponse<Option<MarketDetails>>, IgError> {
+        let ApiResponse {
+            data: markets,
+            meta,
+        } = self.markets_search(search_term).await?;
+
+        // Pick the best exact match among the search results.
+        let term = search_term.trim();
+        let best_match = markets
+            .iter()
+            .find(|market| market.epic.as_str().eq_ignore_ascii_case(term))
+            .or_else(|| {
+                markets
+                    .iter()
+                    .find(|market| market.instrument_name.trim().eq_ignore_ascii_case(term))
+            });
+
+        let epic = match best_match {
+            Some(market) => market.epic.clone(),
+            None => return Ok(ApiResponse::new(meta, None)),
+        };
+
+        // Fetch the market details for the best match.
+        let markets_response = self
+            .markets_get(MarketsGetRequest {
+                epics: vec![epic],
+                filter: None,
+            })
+            .await?;
+
+        Ok(markets_response.map(|markets| markets.market_details.into_iter().next()))
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////////////
+    //
+    // OPERATIONS METHODS.
+    //
+    ////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Disables the current application key from processing further requests. Disabled keys
+    /// may be re-enabled via the My Account section on the IG web dealing platform.
+    pub async fn operations_application_disable(
+        &self,
+    ) -> Result<ApiResponse<Application>, IgError> {
+        self.execute(&OperationsApplicationDisable).await
+    }
+
+    /// Returns the client applications, including their request allowances.
+    pub async fn operations_application_get(&self) -> Result<ApiResponse<Applications>, IgError> {
+        self.execute(&OperationsApplicationGet).await
+    }
+
+    /// Alters the details of a given client application.
+    pub async fn operations_application_put(
+        &self,
+        body: &UpdateApplication,
+    ) -> Result<ApiResponse<Application>, IgError> {
+        self.execute(&OperationsApplicationPut { body }).await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -209,92 +407,82 @@ impl RestApi {
     pub async fn position_delete(
         &self,
         body: PositionDeleteRequest,
-    ) -> Result<(Value, PositionDeleteResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .delete("positions/otc".to_string(), Some(1), &Some(body))
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let position_delete_response = PositionDeleteResponse::from_value(&response_value)?;
-
-        Ok((headers, position_delete_response))
+    ) -> Result<ApiResponse<PositionDeleteResponse>, IgError> {
+        self.execute(&PositionDelete { body: &body }).await
     }
 
     /// Returns a specific open position for the active account.
     pub async fn position_get(
         &self,
         params: PositionGetRequest,
-    ) -> Result<(Value, PositionGetResponse), Box<dyn Error>> {
-        // Create the url based on the params.
-        let url = format!("positions/{}", params.deal_id);
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.get(url, Some(2), &None::<Empty>).await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let positions = PositionGetResponse::from_value(&response_value)?;
-
-        Ok((headers, positions))
+    ) -> Result<ApiResponse<PositionGetResponse>, IgError> {
+        self.execute(&PositionGet {
+            deal_id: &params.deal_id,
+        })
+        .await
     }
 
     /// Returns a specific open position for the active account.
     pub async fn position_post(
         &self,
         body: PositionPostRequest,
-    ) -> Result<(Value, PositionPostResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .post("positions/otc".to_string(), Some(2), &body)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let position_post_response = PositionPostResponse::from_value(&response_value)?;
-
-        Ok((headers, position_post_response))
+    ) -> Result<ApiResponse<PositionPostResponse>, IgError> {
+        self.execute(&PositionPost { body: &body }).await
     }
 
     /// Updates a specific open position for the active account.
     pub async fn position_put(
         &self,
         body: PositionPutRequest,
-        deal_id: String,
-    ) -> Result<(Value, PositionPutResponse), Box<dyn Error>> {
-        let url = format!("positions/otc/{}", deal_id);
+        deal_id: DealId,
+    ) -> Result<ApiResponse<PositionPutResponse>, IgError> {
+        self.execute(&PositionPut {
+            body: &body,
+            deal_id: &deal_id,
+        })
+        .await
+    }
 
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.put(url, Some(2), &body).await?;
+    /// Returns a specific open position for the active account through version 1 of the API.
+    pub async fn position_v1_get(
+        &self,
+        params: PositionGetRequest,
+    ) -> Result<ApiResponse<PositionV1GetResponse>, IgError> {
+        self.execute(&PositionV1Get {
+            deal_id: &params.deal_id,
+        })
+        .await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let position_post_response = PositionPutResponse::from_value(&response_value)?;
+    /// Opens a position through version 1 of the API.
+    pub async fn position_v1_post(
+        &self,
+        body: PositionV1PostRequest,
+    ) -> Result<ApiResponse<PositionPostResponse>, IgError> {
+        self.execute(&PositionV1Post { body: &body }).await
+    }
 
-        Ok((headers, position_post_response))
+    /// Updates the limit and stop levels of an open position through version 1 of the API.
+    pub async fn position_v1_put(
+        &self,
+        body: PositionV1PutRequest,
+        deal_id: DealId,
+    ) -> Result<ApiResponse<PositionPutResponse>, IgError> {
+        self.execute(&PositionV1Put {
+            body: &body,
+            deal_id: &deal_id,
+        })
+        .await
     }
 
     /// Returns all open positions for the active account.
-    pub async fn positions_get(&self) -> Result<(Value, PositionsGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("positions".to_string(), Some(2), &None::<Empty>)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let positions = PositionsGetResponse::from_value(&response_value)?;
+    pub async fn positions_get(&self) -> Result<ApiResponse<PositionsGetResponse>, IgError> {
+        self.execute(&PositionsGet).await
+    }
 
-        Ok((headers, positions))
+    /// Returns all open positions for the active account through version 1 of the API.
+    pub async fn positions_v1_get(&self) -> Result<ApiResponse<PositionsV1GetResponse>, IgError> {
+        self.execute(&PositionsV1Get).await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -306,43 +494,17 @@ impl RestApi {
     /// Returns all open sprint market positions for the active account.
     pub async fn positions_sprintmarkets_get(
         &self,
-    ) -> Result<(Value, SprintMarketPositionsGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get(
-                "positions/sprintmarkets".to_string(),
-                Some(2),
-                &None::<Empty>,
-            )
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let positions = SprintMarketPositionsGetResponse::from_value(&response_value)?;
-
-        Ok((headers, positions))
+    ) -> Result<ApiResponse<SprintMarketPositionsGetResponse>, IgError> {
+        self.execute(&PositionsSprintMarketsGet).await
     }
 
     /// Creates a sprint market position.
     pub async fn positions_sprintmarkets_post(
         &self,
         body: SprintMarketPositionsPostRequest,
-    ) -> Result<(Value, SprintMarketPositionsPostResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .delete("positions/sprintmarkets".to_string(), Some(1), &Some(body))
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let position_post_response =
-            SprintMarketPositionsPostResponse::from_value(&response_value)?;
-
-        Ok((headers, position_post_response))
+    ) -> Result<ApiResponse<SprintMarketPositionsPostResponse>, IgError> {
+        self.execute(&PositionsSprintMarketsPost { body: &body })
+            .await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -352,198 +514,221 @@ impl RestApi {
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
     /// Log out of the IG API by deleting the current session.
-    pub async fn session_delete(&self) -> Result<(Value, ()), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, _) = self
-            .client
-            .delete("session".to_string(), Some(1), &None::<Empty>)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
+    pub async fn session_delete(&self) -> Result<ApiResponse<()>, IgError> {
+        let response = self.execute(&SessionDelete).await?;
 
-        Ok((headers, ()))
+        Ok(response.map(|_| ()))
     }
 
     /// Get session details for the current session.
     pub async fn session_get(
         &self,
         params: Option<SessionDetailsGetRequest>,
-    ) -> Result<(Value, SessionDetailsGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("session".to_string(), Some(1), &params)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Convert the serde_json::Value response to Session model.
-        let session = SessionDetailsGetResponse::from_value(&response_value)?;
-
-        Ok((headers, session))
+    ) -> Result<ApiResponse<SessionDetailsGetResponse>, IgError> {
+        self.execute(&SessionGet {
+            params: params.as_ref(),
+        })
+        .await
     }
 
-    /// This method is not implemented as the login process is handled by the rest_client module.
-    pub async fn session_post() {
-        unimplemented!("This method will not be implemented as the login process is handled by the rest_client module.");
+    /// Log in with the configured session version, creating a new session shared by all the
+    /// clones of this instance, and return its details in the shape of that version.
+    pub async fn session_post(&self) -> Result<ApiResponse<SessionPostResponse>, IgError> {
+        let (meta, body) = self.client.login().await?;
+        let session = SessionPostResponse::from_version(meta.api_version, &body)?;
+
+        Ok(ApiResponse::new(meta, session))
     }
 
     /// Switch to a different account by updating the current session.
     pub async fn session_put(
         &self,
         body: &AccountSwitchPutRequest,
-    ) -> Result<(Value, AccountSwitchPutResponse), Box<dyn Error>> {
-        // Validate the body.
-        body.validate()?;
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .put("session".to_string(), Some(1), body)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to AccountSwitchResponse.
-        let account_switch_response = AccountSwitchPutResponse::from_value(&response_value)?;
-
-        Ok((headers, account_switch_response))
+    ) -> Result<ApiResponse<AccountSwitchPutResponse>, IgError> {
+        self.execute(&SessionPut { body }).await
     }
 
     /// Creates a trading session, obtaining session tokens for subsequent API access.
     /// Please note, region-specific login restrictions may apply.
     pub async fn session_encryption_key_get(
         &self,
-    ) -> Result<(Value, SessionEncryptionKeyGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("session/encryptionKey".to_string(), Some(1), &None::<Empty>)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to EncryptionKeyResponse.
-        let encryption_key_response = SessionEncryptionKeyGetResponse::from_value(&response_value)?;
-
-        Ok((headers, encryption_key_response))
+    ) -> Result<ApiResponse<SessionEncryptionKeyGetResponse>, IgError> {
+        self.execute(&SessionEncryptionKeyGet).await
     }
 
     /// Refresh the current session, obtaining new session tokens for subsequent API access.
     pub async fn session_refresh_token_post(
         &self,
         body: &SessionRefreshTokenPostRequest,
-    ) -> Result<(Value, SessionRefreshTokenPostResponse), Box<dyn Error>> {
-        // Validate the body.
-        body.validate()?;
-
-        // Send the request to the REST client.
-        let (headers, response_value) = self
-            .client
-            .post("session/refresh-token".to_string(), Some(1), body)
-            .await?;
+    ) -> Result<ApiResponse<SessionRefreshTokenPostResponse>, IgError> {
+        self.execute(&SessionRefreshTokenPost { body }).await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&headers)?;
-        // Deserialize the response_value to SessionRefreshTokenResponse.
-        let session_refresh_token_response =
-            SessionRefreshTokenPostResponse::from_value(&response_value)?;
+    /// Switch to a different account of the same client, updating the session state derived
+    /// from the active account: the IG-ACCOUNT-ID header when using session version 3, the
+    /// session tokens if the switch returns new ones, and the account restored when logging in
+    /// again after a session expiry.
+    pub async fn switch_account(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<ApiResponse<AccountSwitchPutResponse>, IgError> {
+        let response = self
+            .session_put(&AccountSwitchPutRequest {
+                account_id: account_id.clone(),
+                default_account: None,
+            })
+            .await?;
+        self.client
+            .set_active_account(account_id.as_str(), &response.meta.headers)?;
 
-        Ok((headers, session_refresh_token_response))
+        Ok(response)
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
     //
-    // WORKINGORDERS ENDPOINTS.
+    // WATCHLISTS ENDPOINTS.
     //
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-    /// Deletes a working order for the active account.
-    pub async fn workingorders_delete(
+    /// Deletes a watchlist.
+    pub async fn watchlist_delete(
         &self,
-        deal_id: String,
-    ) -> Result<(Value, WorkingOrderDeleteResponse), Box<dyn Error>> {
-        let params = WorkingOrderDeleteRequest {
-            deal_id: deal_id.clone(),
-        };
+        watchlist_id: String,
+    ) -> Result<ApiResponse<WatchlistStatusResult>, IgError> {
+        self.execute(&WatchlistDelete {
+            watchlist_id: &watchlist_id,
+        })
+        .await
+    }
 
-        // Validate the params.
-        params.validate()?;
+    /// Removes a market from a watchlist.
+    pub async fn watchlist_epic_delete(
+        &self,
+        watchlist_id: String,
+        epic: Epic,
+    ) -> Result<ApiResponse<WatchlistStatusResult>, IgError> {
+        self.execute(&WatchlistEpicDelete {
+            epic: &epic,
+            watchlist_id: &watchlist_id,
+        })
+        .await
+    }
+
+    /// Returns the markets of the given watchlist.
+    pub async fn watchlist_get(
+        &self,
+        watchlist_id: String,
+    ) -> Result<ApiResponse<WatchlistMarkets>, IgError> {
+        self.execute(&WatchlistGet {
+            watchlist_id: &watchlist_id,
+        })
+        .await
+    }
+
+    /// Adds a market to a watchlist.
+    pub async fn watchlist_put(
+        &self,
+        body: &AddToWatchlist,
+        watchlist_id: String,
+    ) -> Result<ApiResponse<WatchlistStatusResult>, IgError> {
+        self.execute(&WatchlistPut {
+            body,
+            watchlist_id: &watchlist_id,
+        })
+        .await
+    }
 
-        let url = format!("workingorders/otc/{}", params.deal_id);
+    /// Returns all watchlists belonging to the active account.
+    pub async fn watchlists_get(&self) -> Result<ApiResponse<Watchlists>, IgError> {
+        self.execute(&WatchlistsGet).await
+    }
 
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.delete(url, Some(2), &None::<Empty>).await?;
+    /// Creates a watchlist.
+    pub async fn watchlists_post(
+        &self,
+        body: &CreateWatchlist,
+    ) -> Result<ApiResponse<CreateWatchlistResult>, IgError> {
+        self.execute(&WatchlistsPost { body }).await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to WorkingOrdersDeleteResponse.
-        let working_orders = WorkingOrderDeleteResponse::from_value(&response_value)?;
+    ////////////////////////////////////////////////////////////////////////////////////////////////////////
+    //
+    // WORKINGORDERS ENDPOINTS.
+    //
+    ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-        Ok((headers, working_orders))
+    /// Deletes a working order for the active account.
+    pub async fn workingorders_delete(
+        &self,
+        deal_id: DealId,
+    ) -> Result<ApiResponse<WorkingOrderDeleteResponse>, IgError> {
+        self.execute(&WorkingOrdersDelete { deal_id: &deal_id })
+            .await
     }
 
     /// Get list of working orders.
     pub async fn workingorders_get(
         &self,
-    ) -> Result<(Value, WorkingOrdersGetResponse), Box<dyn Error>> {
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .get("workingorders".to_string(), Some(2), &None::<Empty>)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to WorkingOrdersGetResponse.
-        let working_orders = WorkingOrdersGetResponse::from_value(&response_value)?;
-
-        Ok((headers, working_orders))
+    ) -> Result<ApiResponse<WorkingOrdersGetResponse>, IgError> {
+        self.execute(&WorkingOrdersGet).await
     }
 
     /// Create a new working order.
     pub async fn workingorders_post(
         &self,
         body: &WorkingOrderPostRequest,
-    ) -> Result<(Value, WorkingOrderPostResponse), Box<dyn Error>> {
-        // Validate the body.
-        body.validate()?;
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self
-            .client
-            .post("workingorders/otc".to_string(), Some(2), body)
-            .await?;
-
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to WorkingOrdersPostResponse.
-        let working_orders = WorkingOrderPostResponse::from_value(&response_value)?;
-
-        Ok((headers, working_orders))
+    ) -> Result<ApiResponse<WorkingOrderPostResponse>, IgError> {
+        self.execute(&WorkingOrdersPost { body }).await
     }
 
     /// Update a working order for the active account.
     pub async fn workingorders_put(
         &self,
         body: &WorkingOrderPutRequest,
-        deal_id: String,
-    ) -> Result<(Value, WorkingOrderPutResponse), Box<dyn Error>> {
-        // Validate the body.
-        body.validate()?;
+        deal_id: DealId,
+    ) -> Result<ApiResponse<WorkingOrderPutResponse>, IgError> {
+        self.execute(&WorkingOrdersPut {
+            body,
+            deal_id: &deal_id,
+        })
+        .await
+    }
 
-        let url = format!("workingorders/otc/{}", deal_id);
+    /// Deletes a working order for the active account through version 1 of the API.
+    pub async fn workingorders_v1_delete(
+        &self,
+        deal_id: DealId,
+    ) -> Result<ApiResponse<WorkingOrderDeleteResponse>, IgError> {
+        self.execute(&WorkingOrdersV1Delete { deal_id: &deal_id })
+            .await
+    }
 
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.put(url, Some(2), body).await?;
+    /// Get list of working orders through version 1 of the API.
+    pub async fn workingorders_v1_get(
+        &self,
+    ) -> Result<ApiResponse<WorkingOrdersV1GetResponse>, IgError> {
+        self.execute(&WorkingOrdersV1Get).await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to WorkingOrdersPutResponse.
-        let working_orders = WorkingOrderPutResponse::from_value(&response_value)?;
+    /// Create a new working order through version 1 of the API.
+    pub async fn workingorders_v1_post(
+        &self,
+        body: &WorkingOrderV1PostRequest,
+    ) -> Result<ApiResponse<WorkingOrderPostResponse>, IgError> {
+        self.execute(&WorkingOrdersV1Post { body }).await
+    }
 
-        Ok((headers, working_orders))
+    /// Update a working order for the active account through version 1 of the API.
+    pub async fn workingorders_v1_put(
+        &self,
+        body: &WorkingOrderV1PutRequest,
+        deal_id: DealId,
+    ) -> Result<ApiResponse<WorkingOrderPutResponse>, IgError> {
+        self.execute(&WorkingOrdersV1Put {
+            body,
+            deal_id: &deal_id,
+        })
+        .await
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -554,22 +739,44 @@ impl RestApi {
 
     pub async fn prices_get(
         &self,
-        epic: &str,
+        epic: &Epic,
         body: PricesGetRequest,
-    ) -> Result<(Value, PricesGetResponse), Box<dyn Error>> {
-        // Validate the body.
-        body.validate()?;
-
-        let url = format!("prices/{}", epic);
-
-        // Send the request to the REST client.
-        let (header_map, response_value) = self.client.get(url, Some(3), &Some(body)).await?;
+    ) -> Result<ApiResponse<PricesGetResponse>, IgError> {
+        self.execute(&PricesGet {
+            epic,
+            params: &body,
+        })
+        .await
+    }
 
-        // Convert header_map to json.
-        let headers: Value = headers_to_json(&header_map)?;
-        // Deserialize the response_value to WorkingOrdersPutResponse.
-        let prices = PricesGetResponse::from_value(&response_value)?;
+    /// Returns the historical prices of a range of dates and times through version 1 of the API.
+    pub async fn prices_v1_get(
+        &self,
+        epic: &Epic,
+        resolution: Resolution,
+        params: PricesV1GetRequest,
+    ) -> Result<ApiResponse<PricesV1GetResponse>, IgError> {
+        self.execute(&PricesV1Get {
+            epic,
+            params: &params,
+            resolution: &resolution,
+        })
+        .await
+    }
 
-        Ok((headers, prices))
+    /// Returns the historical prices of a range of dates and times, or the last number of data
+    /// points, through version 2 of the API.
+    pub async fn prices_v2_get(
+        &self,
+        epic: &Epic,
+        resolution: Resolution,
+        range: PriceRange,
+    ) -> Result<ApiResponse<PricesV1GetResponse>, IgError> {
+        self.execute(&PricesV2Get {
+            epic,
+            range: &range,
+            resolution: &resolution,
+        })
+        .await
     }
 }
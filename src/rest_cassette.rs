@@ -0,0 +1,557 @@
+use crate::common::*;
+use crate::rest_transport::{Transport, TransportRequest, TransportResponse};
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Value replacing secrets and tokens in cassettes.
+const REDACTED: &str = "REDACTED";
+/// JSON fields redacted from request and response bodies.
+const REDACTED_FIELDS: [&str; 5] = [
+    "access_token",
+    "encryptedPassword",
+    "identifier",
+    "password",
+    "refresh_token",
+];
+/// Headers redacted from responses.
+const REDACTED_HEADERS: [&str; 4] = ["authorization", "cst", "set-cookie", "x-security-token"];
+/// Start of the cassette files written by RecordingTransport.
+const RECORDING_HEADER: &str = "{\"interactions\":[";
+/// End of the cassette files written by RecordingTransport, overwritten by each interaction
+/// appended to them.
+const RECORDING_FOOTER: &str = "\n]}\n";
+
+/// Recorded request/response pairs, stored as a JSON file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Cassette {
+    /// Recorded interactions, in the order they were made.
+    pub interactions: Vec<Interaction>,
+}
+
+/// Implementation for the Cassette struct.
+impl Cassette {
+    /// Load a cassette from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, IgError> {
+        let contents = std::fs::read_to_string(path.as_ref()).map_err(|e| IgError::Other {
+            message: format!("Failed to read cassette {}: {}", path.as_ref().display(), e),
+        })?;
+
+        Ok(serde_path_to_error::deserialize(
+            &mut serde_json::Deserializer::from_str(&contents),
+        )?)
+    }
+
+    /// Save the cassette to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), IgError> {
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(path.as_ref(), contents).map_err(|e| write_error(path.as_ref(), e))
+    }
+}
+
+/// A recorded request and its response.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Interaction {
+    /// The recorded request.
+    pub request: RecordedRequest,
+    /// The recorded response.
+    pub response: RecordedResponse,
+}
+
+/// A recorded request. Only the fields used to match requests on replay and the redacted
+/// body are kept; request headers are not recorded, as they carry the credentials.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecordedRequest {
+    /// The redacted JSON body, or null if the request has no body.
+    pub body: Value,
+    /// The HTTP method, with DELETE requests sent as POST requests recorded as DELETE.
+    pub method: String,
+    /// The URL path.
+    pub path: String,
+    /// The URL query string, if any.
+    pub query: Option<String>,
+    /// The value of the Version header, if any.
+    pub version: Option<String>,
+}
+
+/// Implementation for the RecordedRequest struct.
+impl RecordedRequest {
+    /// Record a request, redacting its body.
+    pub fn from_request(request: &TransportRequest) -> Result<Self, IgError> {
+        let url = Url::parse(&request.url).map_err(|e| IgError::Validation {
+            message: format!("Invalid request URL {}: {}", request.url, e),
+        })?;
+
+        let method = match request.headers.get("_method") {
+            Some(method) => method.to_str()?.to_uppercase(),
+            None => request.method.to_string(),
+        };
+        let version = match request.headers.get("Version") {
+            Some(version) => Some(version.to_str()?.to_string()),
+            None => None,
+        };
+
+        Ok(Self {
+            body: redacted_body(request.body.as_deref().unwrap_or_default()),
+            method,
+            path: url.path().to_string(),
+            query: url.query().map(str::to_string),
+            version,
+        })
+    }
+
+    /// Check whether two requests match on method, path, query and Version header.
+    pub fn matches(&self, other: &RecordedRequest) -> bool {
+        self.method == other.method
+            && self.path == other.path
+            && self.query == other.query
+            && self.version == other.version
+    }
+}
+
+/// A recorded response.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecordedResponse {
+    /// The redacted JSON body, or null if the response has no body. Bodies that are not JSON
+    /// are recorded as strings.
+    pub body: Value,
+    /// The response headers, with the session tokens redacted.
+    pub headers: BTreeMap<String, String>,
+    /// The HTTP status code.
+    pub status: u16,
+}
+
+/// Implementation for the RecordedResponse struct.
+impl RecordedResponse {
+    /// Record a response, redacting its headers and body.
+    pub fn from_response(response: &TransportResponse) -> Self {
+        let headers = response
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                    REDACTED
+                } else {
+                    value.to_str().ok()?
+                };
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Self {
+            body: redacted_body(&response.body),
+            headers,
+            status: response.status.as_u16(),
+        }
+    }
+
+    /// Build the transport response to replay.
+    pub fn to_response(&self) -> Result<TransportResponse, IgError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name =
+                HeaderName::from_bytes(name.as_bytes()).map_err(|e| IgError::Validation {
+                    message: format!("Invalid recorded header {}: {}", name, e),
+                })?;
+            headers.insert(name, HeaderValue::from_str(value)?);
+        }
+
+        let body = match &self.body {
+            Value::Null => Vec::new(),
+            Value::String(text) => text.as_bytes().to_vec(),
+            body => serde_json::to_vec(body)?,
+        };
+
+        let status = StatusCode::from_u16(self.status).map_err(|e| IgError::Validation {
+            message: format!("Invalid recorded status code {}: {}", self.status, e),
+        })?;
+
+        Ok(TransportResponse {
+            body,
+            headers,
+            status,
+        })
+    }
+}
+
+/// Transport recording every request and response sent through another transport into a
+/// cassette file. Each interaction is appended to the file as a line of its own, so the file is
+/// a valid cassette at any time without being rewritten. Recording to an existing cassette
+/// appends to it.
+#[derive(Debug)]
+pub struct RecordingTransport {
+    /// Interactions recorded so far.
+    cassette: Arc<Mutex<Cassette>>,
+    /// The cassette file, open for appending interactions.
+    file: Arc<Mutex<File>>,
+    /// Transport actually sending the requests.
+    inner: Arc<dyn Transport>,
+    /// Path of the cassette file.
+    path: PathBuf,
+}
+
+/// Implementation for the RecordingTransport struct.
+impl RecordingTransport {
+    /// Returns a copy of the interactions recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.lock().clone()
+    }
+
+    /// Acquire the lock on the cassette. A poisoned lock is recovered, as the cassette is
+    /// always left consistent.
+    fn lock(&self) -> MutexGuard<'_, Cassette> {
+        self.cassette.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Create a new RecordingTransport instance sending requests through the given transport
+    /// and recording them to the given cassette file. An existing cassette is loaded and
+    /// written again in the format interactions are appended to.
+    pub fn new(inner: Arc<dyn Transport>, path: impl Into<PathBuf>) -> Result<Self, IgError> {
+        let path = path.into();
+        let cassette = if path.exists() {
+            Cassette::load(&path)?
+        } else {
+            Cassette::default()
+        };
+
+        let mut contents = RECORDING_HEADER.to_string();
+        for (index, interaction) in cassette.interactions.iter().enumerate() {
+            if index > 0 {
+                contents.push(',');
+            }
+            contents.push('\n');
+            contents.push_str(&serde_json::to_string(interaction)?);
+        }
+        contents.push_str(RECORDING_FOOTER);
+
+        let mut file = File::create(&path).map_err(|e| write_error(&path, e))?;
+        file.write_all(contents.as_bytes())
+            .map_err(|e| write_error(&path, e))?;
+
+        Ok(Self {
+            cassette: Arc::new(Mutex::new(cassette)),
+            file: Arc::new(Mutex::new(file)),
+            inner,
+            path,
+        })
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, IgError>> {
+        Box::pin(async move {
+            let recorded_request = RecordedRequest::from_request(&request)?;
+            let response = self.inner.send(request).await?;
+            let interaction = Interaction {
+                request: recorded_request,
+                response: RecordedResponse::from_response(&response),
+            };
+
+            // Append the interaction without blocking the runtime.
+            let cassette = Arc::clone(&self.cassette);
+            let file = Arc::clone(&self.file);
+            let path = self.path.clone();
+            tokio::task::spawn_blocking(move || {
+                append_interaction(&cassette, &file, &path, interaction)
+            })
+            .await
+            .map_err(|e| IgError::Other {
+                message: format!("Failed to record interaction: {}", e),
+            })??;
+
+            Ok(response)
+        })
+    }
+}
+
+/// Transport replaying the responses of a cassette instead of sending the requests. Each
+/// request is answered with the first interaction not replayed yet that matches it on method,
+/// path, query and Version header, so repeated requests replay their responses in order.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    /// Interactions to replay.
+    cassette: Cassette,
+    /// Whether each interaction has been replayed already.
+    replayed: Mutex<Vec<bool>>,
+}
+
+/// Implementation for the ReplayTransport struct.
+impl ReplayTransport {
+    /// Create a new ReplayTransport instance loading the given cassette file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, IgError> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    /// Create a new ReplayTransport instance replaying the given cassette.
+    pub fn new(cassette: Cassette) -> Self {
+        let replayed = vec![false; cassette.interactions.len()];
+        Self {
+            cassette,
+            replayed: Mutex::new(replayed),
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, IgError>> {
+        let result = RecordedRequest::from_request(&request).and_then(|recorded_request| {
+            let mut replayed = self.replayed.lock().unwrap_or_else(PoisonError::into_inner);
+            let index = self
+                .cassette
+                .interactions
+                .iter()
+                .zip(replayed.iter())
+                .position(|(interaction, replayed)| {
+                    !replayed && interaction.request.matches(&recorded_request)
+                })
+                .ok_or_else(|| IgError::Other {
+                    message: format!(
+                        "No recorded interaction left for {} {} (version {:?}).",
+                        recorded_request.method, recorded_request.path, recorded_request.version
+                    ),
+                })?;
+
+            replayed[index] = true;
+            self.cassette.interactions[index].response.to_response()
+        });
+
+        Box::pin(async move { result })
+    }
+}
+
+/// Append an interaction to a cassette file written by RecordingTransport, overwriting its
+/// footer, and to the cassette in memory.
+fn append_interaction(
+    cassette: &Mutex<Cassette>,
+    file: &Mutex<File>,
+    path: &Path,
+    interaction: Interaction,
+) -> Result<(), IgError> {
+    let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut cassette = cassette.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let separator = if cassette.interactions.is_empty() {
+        ""
+    } else {
+        ","
+    };
+    let contents = format!(
+        "{}\n{}{}",
+        separator,
+        serde_json::to_string(&interaction)?,
+        RECORDING_FOOTER
+    );
+    file.seek(SeekFrom::End(-(RECORDING_FOOTER.len() as i64)))
+        .and_then(|_| file.write_all(contents.as_bytes()))
+        .map_err(|e| write_error(path, e))?;
+    cassette.interactions.push(interaction);
+
+    Ok(())
+}
+
+/// Build the error returned when a cassette file can't be written.
+fn write_error(path: &Path, error: std::io::Error) -> IgError {
+    IgError::Other {
+        message: format!("Failed to write cassette {}: {}", path.display(), error),
+    }
+}
+
+/// Parse a JSON body and redact its secrets. Empty bodies are recorded as null and bodies that
+/// are not JSON as strings.
+fn redacted_body(body: &[u8]) -> Value {
+    if body.is_empty() {
+        return Value::Null;
+    }
+
+    match serde_json::from_slice(body) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value
+        }
+        Err(_) => Value::String(String::from_utf8_lossy(body).into_owned()),
+    }
+}
+
+/// Replace the values of the redacted fields of a JSON value, at any depth.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if REDACTED_FIELDS.contains(&name.as_str()) && !field.is_null() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Method;
+    use serde_json::json;
+
+    /// Transport always returning the same response.
+    #[derive(Debug)]
+    struct StubTransport(TransportResponse);
+
+    impl Transport for StubTransport {
+        fn send(
+            &self,
+            _request: TransportRequest,
+        ) -> BoxFuture<'_, Result<TransportResponse, IgError>> {
+            let response = self.0.clone();
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    fn request(method: Method, url: &str, version: &'static str, body: Value) -> TransportRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("Version", HeaderValue::from_static(version));
+        headers.insert("cst", HeaderValue::from_static("secret_cst"));
+        TransportRequest {
+            body: Some(serde_json::to_vec(&body).unwrap()),
+            headers,
+            method,
+            url: url.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn recording_transport_redacts_secrets() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cst", HeaderValue::from_static("secret_cst"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        let stub = StubTransport(TransportResponse {
+            body: serde_json::to_vec(&json!({
+                "accountId": "ABC123",
+                "oauthToken": { "access_token": "secret_access_token", "expires_in": "60" },
+            }))
+            .unwrap(),
+            headers,
+            status: StatusCode::OK,
+        });
+
+        let path = std::env::temp_dir().join(format!(
+            "ig_trading_api_cassette_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let recorder = RecordingTransport::new(Arc::new(stub), &path).unwrap();
+        recorder
+            .send(request(
+                Method::POST,
+                "https://demo-api.ig.com/gateway/deal/session",
+                "3",
+                json!({ "identifier": "user", "password": "secret_password" }),
+            ))
+            .await
+            .unwrap();
+
+        let cassette = Cassette::load(&path).unwrap();
+        assert_eq!(cassette, recorder.cassette());
+
+        // Recording to an existing cassette appends to it.
+        let recorder = RecordingTransport::new(recorder.inner.clone(), &path).unwrap();
+        recorder
+            .send(request(
+                Method::GET,
+                "https://demo-api.ig.com/gateway/deal/accounts",
+                "1",
+                Value::Null,
+            ))
+            .await
+            .unwrap();
+        let appended = Cassette::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(appended.interactions.len(), 2);
+        assert_eq!(appended.interactions[0], cassette.interactions[0]);
+        assert_eq!(
+            appended.interactions[1].request.path,
+            "/gateway/deal/accounts"
+        );
+
+        let interaction = &cassette.interactions[0];
+        assert_eq!(interaction.request.path, "/gateway/deal/session");
+        assert_eq!(interaction.request.version.as_deref(), Some("3"));
+        assert_eq!(
+            interaction.request.body,
+            json!({ "identifier": REDACTED, "password": REDACTED })
+        );
+        assert_eq!(interaction.response.headers["cst"], REDACTED);
+        assert_eq!(
+            interaction.response.headers["content-type"],
+            "application/json"
+        );
+        assert_eq!(
+            interaction.response.body,
+            json!({
+                "accountId": "ABC123",
+                "oauthToken": { "access_token": REDACTED, "expires_in": "60" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_transport_matches_requests_in_order() {
+        let recorded = |version: &str, body: Value| Interaction {
+            request: RecordedRequest {
+                body: Value::Null,
+                method: "GET".to_string(),
+                path: "/gateway/deal/positions".to_string(),
+                query: None,
+                version: Some(version.to_string()),
+            },
+            response: RecordedResponse {
+                body,
+                headers: BTreeMap::new(),
+                status: 200,
+            },
+        };
+        let replayer = ReplayTransport::new(Cassette {
+            interactions: vec![
+                recorded("2", json!({ "positions": [1] })),
+                recorded("1", json!({ "positions": [] })),
+                recorded("2", json!({ "positions": [2] })),
+            ],
+        });
+
+        let url = "https://example.com/gateway/deal/positions";
+        for expected in [json!({ "positions": [1] }), json!({ "positions": [2] })] {
+            let response = replayer
+                .send(request(Method::GET, url, "2", Value::Null))
+                .await
+                .unwrap();
+            assert_eq!(
+                serde_json::from_slice::<Value>(&response.body).unwrap(),
+                expected
+            );
+        }
+
+        // All the matching interactions have been replayed, and the query must match too.
+        assert!(replayer
+            .send(request(Method::GET, url, "2", Value::Null))
+            .await
+            .is_err());
+        assert!(replayer
+            .send(request(
+                Method::GET,
+                &format!("{}?a=b", url),
+                "1",
+                Value::Null
+            ))
+            .await
+            .is_err());
+    }
+}
@@ -1,52 +1,98 @@
 use crate::common::*;
 use crate::rest_models::{
-    AuthenticationPostRequest, AuthenticationPostResponseV3, ValidateRequest, ValidateResponse,
+    AccountSwitchPutRequest, AuthenticationPostRequest, AuthenticationPostResponseV3, Empty,
+    SessionEncryptionKeyGetResponse, SessionRefreshTokenPostRequest,
+    SessionRefreshTokenPostResponse, ValidateRequest, ValidateResponse,
 };
+use crate::rest_rate_limiter::{RateLimitBudget, RateLimiter};
+use crate::rest_response::ResponseMeta;
+use crate::rest_transport::{ReqwestTransport, Transport, TransportRequest, TransportResponse};
 use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::StatusCode;
+use reqwest::{Method, StatusCode};
 use serde::Serialize;
 use serde_json::{json, Value};
-use std::error::Error;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
 
 /// Default session version if not explicitly set.
 const DEFAULT_SESSION_VERSION: usize = 2;
 /// Default auto-login behavior if not explicitly set.
 const DEFAULT_AUTO_LOGIN: bool = true;
+/// Time before the access token expiry at which the token refresher refreshes it.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(15);
+/// Time to wait before retrying after the token refresher fails to refresh the access token.
+const TOKEN_REFRESH_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Struct to represent the authentication state of the REST API client.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    /// The active account identifier, once logged in. It is kept when logging in again, so an
+    /// account switch outlives session expiries.
+    pub account_id: Option<String>,
+    /// Instant at which the current access token expires when session_version is 3.
+    pub access_token_expiry: Option<Instant>,
+    /// The API authentication headers.
+    pub auth_headers: Option<HeaderMap>,
+    /// Number of times the session has been replaced. It is used to detect whether another
+    /// task has already logged in again after a session expiry.
+    pub generation: u64,
+    /// The Lightstreamer endpoint to use for streaming data from the selected execution environment.
+    pub lightstreamer_endpoint: String,
+    /// The refresh token to use for refreshing the session when session_version is 3.
+    pub refresh_token: Option<String>,
+}
 
 /// Struct to represent the REST API client.
+///
+/// The session is shared by all the clones of a client, so logging in again through any of
+/// them updates the tokens used by the rest.
 #[derive(Clone, Debug)]
 pub struct RestClient {
-    /// The API authentication headers.
-    pub auth_headers: Option<HeaderMap>,
     /// Automatically log in to the API on instantiation and when the session expires.
     pub auto_login: bool,
     /// The API base URL based on the account type.
     pub base_url: String,
-    /// The reqwest client instance.
-    pub client: reqwest::Client,
     /// Common headers used for all requests.
     pub common_headers: HeaderMap,
     /// The API configuration.
     pub config: ApiConfig,
-    /// The Lightstreamer endpoint to use for streaming data from the selected execution environment.
-    pub lightstreamer_endpoint: String,
-    /// The refresh token to use for refreshing the session when session_version is 3.
-    pub refresh_token: Option<String>,
+    /// Lock held while logging in or refreshing the session, so that concurrent tasks
+    /// don't each trigger their own login.
+    login_lock: Arc<Mutex<()>>,
+    /// Client-side rate limiter, shared by all the clones of this client.
+    pub rate_limiter: Option<RateLimiter>,
+    /// The authentication state shared by all the clones of this client.
+    session: Arc<RwLock<Session>>,
     /// Session version.
     pub session_version: usize,
+    /// The transport used to send the HTTP requests.
+    pub transport: Arc<dyn Transport>,
 }
 
 /// Implementation for the RestClient struct.
 impl RestClient {
+    /// Returns the active account identifier, if logged in.
+    pub fn account_id(&self) -> Option<String> {
+        self.session_read().account_id.clone()
+    }
+
+    /// Returns the current authentication headers, if logged in.
+    pub fn auth_headers(&self) -> Option<HeaderMap> {
+        self.session_read().auth_headers.clone()
+    }
+
     /// Send a DELETE request to the API.
     pub async fn delete(
         &self,
         method: String,
         api_version: Option<usize>,
         body: &Option<impl Serialize + ValidateRequest>,
-    ) -> Result<(HeaderMap, Value), Box<dyn Error>> {
+    ) -> Result<(ResponseMeta, Value), IgError> {
         // Default API version is 1.
-        let version = api_version.unwrap_or(1).to_string();
+        let api_version = api_version.unwrap_or(1);
         // Validate the body.
         if let Some(body) = body {
             body.validate()?;
@@ -54,37 +100,64 @@ impl RestClient {
         // Convert the body to a serde_json::Value.
         let body = serde_json::to_value(body)?;
 
-        let response = self
-            .client
-            .post(&format!("{}/{}", &self.base_url, method))
-            .json(&body)
-            .headers(self.auth_headers.clone().unwrap_or(HeaderMap::new()))
-            .headers(self.common_headers.clone())
-            .header("Version", version)
-            .header("_method", "DELETE".to_string())
-            .send()
-            .await?;
+        let mut request = self.build_request(
+            Method::POST,
+            format!("{}/{}", &self.base_url, method),
+            &api_version.to_string(),
+            Some(&body),
+        )?;
+        request
+            .headers
+            .insert("_method", HeaderValue::from_static("DELETE"));
+
+        let bucket = RateLimiter::bucket_for("DELETE", &method);
+        let (meta, body) = self.send(request, api_version, bucket, false).await?;
 
         // Check the response status code.
-        match response.status() {
+        match meta.status {
             // If the status code is 204 No Content, return success.
-            StatusCode::NO_CONTENT => Ok((response.headers().clone(), json!({}))),
+            StatusCode::NO_CONTENT => Ok((meta, json!({}))),
             // If the status code is 200 OK, return success and response body.
-            StatusCode::OK => Ok((response.headers().clone(), response.json().await?)),
+            StatusCode::OK => Ok((meta, serde_json::from_str(&body)?)),
             // If the status code is other, return an error.
-            _ => Err(Box::new(ApiError {
-                message: format!(
-                    "DELETE operation using method '{}' failed with status code: {:?} - {:?}",
-                    method,
-                    response.status(),
-                    response.text().await?
-                ),
-            })),
+            _ => Err(IgError::from_response(meta.status, &body)),
         }
     }
 
-    /// Create a new RestClient instance.
-    pub async fn new(config: ApiConfig) -> Result<Self, Box<dyn Error>> {
+    /// Build a request to the API with the common headers and the given API version.
+    fn build_request(
+        &self,
+        http_method: Method,
+        url: String,
+        version: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<TransportRequest, IgError> {
+        let mut headers = self.common_headers.clone();
+        headers.insert("Version", HeaderValue::from_str(version)?);
+
+        let body = match body {
+            Some(body) => Some(serde_json::to_vec(body)?),
+            None => None,
+        };
+
+        Ok(TransportRequest {
+            body,
+            headers,
+            method: http_method,
+            url,
+        })
+    }
+
+    /// Create a new RestClient instance sending its requests through reqwest.
+    pub async fn new(config: ApiConfig) -> Result<Self, IgError> {
+        Self::with_transport(config, Arc::new(ReqwestTransport::default())).await
+    }
+
+    /// Create a new RestClient instance sending its requests through the given transport.
+    pub async fn with_transport(
+        config: ApiConfig,
+        transport: Arc<dyn Transport>,
+    ) -> Result<Self, IgError> {
         // Determine the API base URL based on the account type.
         let base_url = match config.execution_environment {
             ExecutionEnvironment::Demo => config.base_url_demo.clone(),
@@ -96,6 +169,9 @@ impl RestClient {
         // Default auto_login is DEFAULT_AUTO_LOGIN.
         let auto_login = config.auto_login.unwrap_or(DEFAULT_AUTO_LOGIN);
 
+        // Create the rate limiter if it is configured.
+        let rate_limiter = config.rate_limiter.as_ref().map(RateLimiter::new);
+
         // Set the common headers.
         let mut common_headers = HeaderMap::new();
         common_headers.insert("Accept", "application/json; charset=UTF-8".parse()?);
@@ -103,16 +179,16 @@ impl RestClient {
         common_headers.insert("X-IG-API-KEY", config.api_key.as_str().parse()?);
 
         // Create a new RestClient instance.
-        let mut rest_client = Self {
-            auth_headers: None,
+        let rest_client = Self {
             auto_login,
             base_url,
-            client: reqwest::Client::new(),
             common_headers,
             config,
-            lightstreamer_endpoint: "".to_string(),
-            refresh_token: None,
+            login_lock: Arc::new(Mutex::new(())),
+            rate_limiter,
+            session: Arc::new(RwLock::new(Session::default())),
             session_version,
+            transport,
         };
 
         // If auto_login is true, then login to the API.
@@ -129,9 +205,9 @@ impl RestClient {
         method: String,
         api_version: Option<usize>,
         params: &Option<impl Serialize + ValidateRequest>,
-    ) -> Result<(HeaderMap, Value), Box<dyn Error>> {
+    ) -> Result<(ResponseMeta, Value), IgError> {
         // Default API version is 1.
-        let api_version = api_version.unwrap_or(1).to_string();
+        let api_version = api_version.unwrap_or(1);
         // Validate the params.
         if let Some(params) = params {
             params.validate()?;
@@ -145,74 +221,91 @@ impl RestClient {
             format!("{}/{}?{}", &self.base_url, method, query_string)
         };
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.auth_headers.clone().unwrap_or(HeaderMap::new()))
-            .headers(self.common_headers.clone())
-            .header("Version", api_version)
-            .send()
-            .await?;
+        let request =
+            self.build_request(Method::GET, url, &api_version.to_string(), None::<&Value>)?;
+
+        let bucket = RateLimiter::bucket_for("GET", &method);
+        let (meta, body) = self.send(request, api_version, bucket, true).await?;
 
         // Check the response status code.
-        match response.status() {
+        match meta.status {
             // If the status code is 200 OK, return the JSON body.
-            StatusCode::OK => Ok((response.headers().clone(), response.json().await?)),
+            StatusCode::OK => Ok((meta, serde_json::from_str(&body)?)),
             // If the status code is not 200 OK, return an error.
-            _ => Err(Box::new(ApiError {
-                message: format!(
-                    "GET operation to url '{}' and query_string '{}' failed with status code: {:?} - {:?}",
-                    url,
-                    query_string,
-                    response.status(),
-                    response.text().await?
-                ),
-            })),
+            _ => Err(IgError::from_response(meta.status, &body)),
         }
     }
 
-    /// Log in to the REST API.
-    pub async fn login(&mut self) -> Result<Value, Box<dyn Error>> {
+    /// Returns the Lightstreamer endpoint obtained when logging in.
+    pub fn lightstreamer_endpoint(&self) -> String {
+        self.session_read().lightstreamer_endpoint.clone()
+    }
+
+    /// Log in to the REST API. The new session is shared by all the clones of this client.
+    pub async fn login(&self) -> Result<(ResponseMeta, Value), IgError> {
+        let _guard = self.login_lock.lock().await;
+        self.authenticate().await
+    }
+
+    /// Log in to the REST API using session version 1.
+    pub async fn login_v1(&self) -> Result<(ResponseMeta, Value), IgError> {
+        let _guard = self.login_lock.lock().await;
+        self.authenticate_v1_or_v2(1).await
+    }
+
+    /// Log in to the REST API using session version 2.
+    pub async fn login_v2(&self) -> Result<(ResponseMeta, Value), IgError> {
+        let _guard = self.login_lock.lock().await;
+        self.authenticate_v1_or_v2(2).await
+    }
+
+    /// Log in to the REST API using session version 3.
+    pub async fn login_v3(&self) -> Result<(ResponseMeta, Value), IgError> {
+        let _guard = self.login_lock.lock().await;
+        self.authenticate_v3().await
+    }
+
+    /// Log in to the REST API using the configured session version. The login lock must be held.
+    async fn authenticate(&self) -> Result<(ResponseMeta, Value), IgError> {
         match self.session_version {
-            1 | 2 => Ok(self.login_v2().await?),
-            3 => Ok(self.login_v3().await?),
-            _ => Err(Box::new(ApiError {
+            1 | 2 => self.authenticate_v1_or_v2(self.session_version).await,
+            3 => self.authenticate_v3().await,
+            _ => Err(IgError::Other {
                 message: format!("Invalid session version: {}", self.session_version),
-            })),
+            }),
         }
     }
 
-    /// Log in to the REST API using session version 2.
-    pub async fn login_v2(&mut self) -> Result<Value, Box<dyn Error>> {
-        // Create the login request body.
-        let login_request_body = AuthenticationPostRequest {
-            identifier: self.config.username.clone(),
-            password: self.config.password.clone(),
-        };
+    /// Log in to the REST API using session version 1 or 2, which both return CST and
+    /// X-SECURITY-TOKEN headers. The login lock must be held.
+    async fn authenticate_v1_or_v2(
+        &self,
+        version: usize,
+    ) -> Result<(ResponseMeta, Value), IgError> {
+        let start = Instant::now();
 
-        // Validate the login request body.
-        login_request_body.validate()?;
+        // Create and validate the login request body.
+        let login_request_body = self.login_request_body().await?;
 
         // Send the login request.
-        let response = self
-            .client
-            .post(&format!("{}/session", &self.base_url))
-            .json(&login_request_body)
-            .headers(self.common_headers.clone())
-            .header("Version", "2")
-            .send()
-            .await?;
+        let request = self.build_request(
+            Method::POST,
+            format!("{}/session", &self.base_url),
+            &version.to_string(),
+            Some(&login_request_body),
+        )?;
+        let response = self.transport.send(request).await?;
 
         // Check the response status code.
-        match response.status() {
+        match response.status {
             // If the status code is 200 OK, return the JSON body plus headers.
             StatusCode::OK => {
                 // Get cst and x-security-token headers from the login response.
                 let mut auth_headers = HeaderMap::new();
-                if let Some(cst_header) = response.headers().get("cst") {
+                if let Some(cst_header) = response.headers.get("cst") {
                     auth_headers.insert("cst", HeaderValue::from_str(cst_header.to_str()?)?);
                 }
-                if let Some(security_token_header) = response.headers().get("x-security-token") {
+                if let Some(security_token_header) = response.headers.get("x-security-token") {
                     auth_headers.insert(
                         "x-security-token",
                         HeaderValue::from_str(security_token_header.to_str()?).unwrap(),
@@ -223,75 +316,85 @@ impl RestClient {
                 if auth_headers.get("cst").is_none()
                     || auth_headers.get("x-security-token").is_none()
                 {
-                    return Err(Box::new(ApiError {
+                    return Err(IgError::Other {
                         message:
                             "Any of the cst / x-security-token headers not found in login response."
                                 .to_string(),
-                    }));
+                    });
                 }
 
-                self.auth_headers = Some(auth_headers);
-
                 // Deserialize the response body to a serde_json::Value.
-                let response_json: Value = response.json().await?;
+                let response_json: Value = serde_json::from_slice(&response.body)?;
 
                 // Get the lightstreamer endpoint from the login response.
-                self.lightstreamer_endpoint = match response_json.get("lightstreamerEndpoint") {
+                let lightstreamer_endpoint = match response_json.get("lightstreamerEndpoint") {
                     Some(endpoint) => match endpoint.as_str() {
                         Some(s) => s.to_string(),
                         None => {
-                            return Err(Box::new(ApiError {
+                            return Err(IgError::Validation {
                                 message: "Lightstreamer endpoint is not a string.".to_string(),
-                            }))
+                            })
                         }
                     },
                     None => {
-                        return Err(Box::new(ApiError {
+                        return Err(IgError::Validation {
                             message: "Lightstreamer endpoint not found in login response.".to_string(),
-                        }))
+                        })
+                    }
+                };
+
+                // Logging in selects the default account, so switch back to the active account
+                // if another one was switched to before logging in again.
+                let current_account_id = response_json
+                    .get("currentAccountId")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                let account_id = match self.account_id() {
+                    Some(account_id) if Some(&account_id) != current_account_id.as_ref() => {
+                        self.restore_account(&account_id, &mut auth_headers).await?;
+                        Some(account_id)
                     }
+                    _ => current_account_id,
                 };
 
-                Ok(response_json)
+                let mut session = self.session_write();
+                session.account_id = account_id;
+                session.auth_headers = Some(auth_headers);
+                session.lightstreamer_endpoint = lightstreamer_endpoint;
+                session.generation += 1;
+
+                let meta =
+                    ResponseMeta::new(version, start.elapsed(), response.headers, response.status);
+
+                Ok((meta, response_json))
             }
             // If the status code is not 200 OK, return an error.
-            _ => Err(Box::new(ApiError {
-                message: format!(
-                    "Login failed with status code: {:?} - {:?}",
-                    response.status(),
-                    response.text().await?
-                ),
-            })),
+            status => Err(IgError::from_response(status, &response_text(&response))),
         }
     }
 
-    /// Log in to the REST API using session version 2.
-    pub async fn login_v3(&mut self) -> Result<Value, Box<dyn Error>> {
-        // Create the login request body.
-        let login_request_body = AuthenticationPostRequest {
-            identifier: self.config.username.clone(),
-            password: self.config.password.clone(),
-        };
+    /// Log in to the REST API using session version 3. The login lock must be held.
+    async fn authenticate_v3(&self) -> Result<(ResponseMeta, Value), IgError> {
+        let start = Instant::now();
 
-        // Validate the login request body.
-        login_request_body.validate()?;
+        // Create and validate the login request body.
+        let login_request_body = self.login_request_body().await?;
 
         // Send the login request.
-        let response = self
-            .client
-            .post(&format!("{}/session", &self.base_url))
-            .json(&login_request_body)
-            .headers(self.common_headers.clone())
-            .header("Version", "3")
-            .send()
-            .await?;
+        let request = self.build_request(
+            Method::POST,
+            format!("{}/session", &self.base_url),
+            "3",
+            Some(&login_request_body),
+        )?;
+        let response = self.transport.send(request).await?;
 
         // Check the response status code.
-        match response.status() {
+        match response.status {
             // If the status code is 200 OK, return the JSON body plus headers.
             StatusCode::OK => {
                 // Deserialize the response body to a LoginResponseV3.
-                let response_body = response.json().await?;
+                let response_body = serde_json::from_slice(&response.body)?;
                 let login_response = AuthenticationPostResponseV3::from_value(&response_body)?;
 
                 // Get access_token from the login response and set it as the Bearer token in Authorization header.
@@ -304,30 +407,68 @@ impl RestClient {
                     ))?,
                 );
 
-                let account_number = match self.config.execution_environment {
-                    ExecutionEnvironment::Demo => self.config.account_number_demo.clone(),
-                    ExecutionEnvironment::Live => self.config.account_number_live.clone(),
+                // Keep the active account if another one was switched to before logging in again.
+                let account_number = match self.account_id() {
+                    Some(account_id) => account_id,
+                    None => match self.config.execution_environment {
+                        ExecutionEnvironment::Demo => self.config.account_number_demo.clone(),
+                        ExecutionEnvironment::Live => self.config.account_number_live.clone(),
+                    },
                 };
 
                 auth_headers.insert("IG-ACCOUNT-ID", HeaderValue::from_str(&account_number)?);
 
-                self.auth_headers = Some(auth_headers);
+                let access_token_expiry = expiry_instant(&login_response.oauth_token.expires_in)?;
 
-                self.refresh_token = Some(login_response.oauth_token.refresh_token);
+                let mut session = self.session_write();
+                session.account_id = Some(account_number);
+                session.access_token_expiry = Some(access_token_expiry);
+                session.auth_headers = Some(auth_headers);
+                session.lightstreamer_endpoint = login_response.lightstreamer_endpoint;
+                session.refresh_token = Some(login_response.oauth_token.refresh_token);
+                session.generation += 1;
 
-                self.lightstreamer_endpoint = login_response.lightstreamer_endpoint;
+                let meta = ResponseMeta::new(3, start.elapsed(), response.headers, response.status);
 
-                Ok(response_body)
+                Ok((meta, response_body))
             }
             // If the status code is not 200 OK, return an error.
-            _ => Err(Box::new(ApiError {
-                message: format!(
-                    "Login failed with status code: {:?} - {:?}",
-                    response.status(),
-                    response.text().await?,
-                ),
-            })),
+            status => Err(IgError::from_response(status, &response_text(&response))),
+        }
+    }
+
+    /// Create and validate the login request body. If encrypted_password is enabled in the
+    /// configuration, the password is encrypted with the key returned by
+    /// GET /session/encryptionKey, which is throttled and retried like any other request.
+    async fn login_request_body(&self) -> Result<AuthenticationPostRequest, IgError> {
+        let mut login_request_body = AuthenticationPostRequest {
+            encrypted_password: None,
+            identifier: self.config.username.clone(),
+            password: self.config.password.clone(),
+        };
+        login_request_body.validate()?;
+
+        if self.config.encrypted_password.unwrap_or(false) {
+            let request = self.build_request(
+                Method::GET,
+                format!("{}/session/encryptionKey", &self.base_url),
+                "1",
+                None::<&Empty>,
+            )?;
+            let bucket = RateLimiter::bucket_for("GET", "session/encryptionKey");
+            let (_, status, _, body) = self.send_with_retries(request, bucket, true, false).await?;
+            if status != StatusCode::OK {
+                return Err(IgError::from_response(status, &body));
+            }
+
+            let response_body = serde_json::from_str(&body)?;
+            let encryption_key = SessionEncryptionKeyGetResponse::from_value(&response_body)?;
+            login_request_body.password =
+                encryption_key.encrypt_password(&login_request_body.password)?;
+            login_request_body.encrypted_password = Some(true);
         }
+
+        Ok(login_request_body)
     }
 
     /// Send a POST request to the REST API.
@@ -336,39 +477,30 @@ impl RestClient {
         method: String,
         api_version: Option<usize>,
         body: &(impl Serialize + ValidateRequest),
-    ) -> Result<(HeaderMap, Value), Box<dyn Error>> {
+    ) -> Result<(ResponseMeta, Value), IgError> {
         // Default API version is 1.
-        let version = api_version.unwrap_or(1).to_string();
+        let api_version = api_version.unwrap_or(1);
         // Validate the body.
         body.validate()?;
         // Convert the body to a serde_json::Value.
         let body = serde_json::to_value(body)?;
 
-        let response = self
-            .client
-            .post(&format!("{}/{}", &self.base_url, method))
-            .json(&body)
-            .headers(self.auth_headers.clone().unwrap_or(HeaderMap::new()))
-            .headers(self.common_headers.clone())
-            .header("Version", version.clone())
-            .send()
-            .await?;
+        let request = self.build_request(
+            Method::POST,
+            format!("{}/{}", &self.base_url, method),
+            &api_version.to_string(),
+            Some(&body),
+        )?;
+
+        let bucket = RateLimiter::bucket_for("POST", &method);
+        let (meta, response_body) = self.send(request, api_version, bucket, false).await?;
 
         // Check the response status code.
-        match response.status() {
+        match meta.status {
             // If the status code is 200 OK, return the JSON body.
-            StatusCode::OK => Ok((response.headers().clone(), response.json().await?)),
+            StatusCode::OK => Ok((meta, serde_json::from_str(&response_body)?)),
             // If the status code is not 200 OK, return an error.
-            _ => Err(Box::new(ApiError {
-                message: format!(
-                    "POST operation using method '{}', version '{}' and body '{:?}' failed with status code: {:?} - {:?}",
-                    method,
-                    version,
-                    body,
-                    response.status(),
-                    response.text().await?
-                ),
-            })),
+            _ => Err(IgError::from_response(meta.status, &response_body)),
         }
     }
 
@@ -376,40 +508,462 @@ impl RestClient {
     pub async fn put(
         &self,
         method: String,
-        version: Option<usize>,
+        api_version: Option<usize>,
         body: &(impl Serialize + ValidateRequest),
-    ) -> Result<(HeaderMap, Value), Box<dyn Error>> {
+    ) -> Result<(ResponseMeta, Value), IgError> {
         // Default API version is 1.
-        let version = version.unwrap_or(1).to_string();
+        let api_version = api_version.unwrap_or(1);
         // Validate the body.
         body.validate()?;
 
         // Send the PUT request.
-        let response = self
-            .client
-            .put(&format!("{}/{}", &self.base_url, method))
-            .json(&body)
-            .headers(self.auth_headers.clone().unwrap_or(HeaderMap::new()))
-            .headers(self.common_headers.clone())
-            .header("Version", version.clone())
-            .send()
-            .await?;
+        let request = self.build_request(
+            Method::PUT,
+            format!("{}/{}", &self.base_url, method),
+            &api_version.to_string(),
+            Some(body),
+        )?;
+
+        let bucket = RateLimiter::bucket_for("PUT", &method);
+        let (meta, response_body) = self.send(request, api_version, bucket, false).await?;
 
         // Check the response status code.
-        match response.status() {
+        match meta.status {
             // If the status code is 200 OK, return the JSON body.
-            StatusCode::OK => Ok((response.headers().clone(), response.json().await?)),
+            StatusCode::OK => Ok((meta, serde_json::from_str(&response_body)?)),
             // If the status code is not 200 OK, return an error.
-            _ => Err(Box::new(ApiError {
+            _ => Err(IgError::from_response(meta.status, &response_body)),
+        }
+    }
+
+    /// Returns the remaining budget of the rate limiter allowances, if the rate limiter is enabled.
+    pub fn rate_limit_budget(&self) -> Option<RateLimitBudget> {
+        self.rate_limiter.as_ref().map(RateLimiter::budget)
+    }
+
+    /// Log in again after a session expiry. When session_version is 3 the session is refreshed
+    /// using the refresh token first, falling back to a full login if the refresh fails.
+    ///
+    /// `generation` is the authentication generation used by the failed request. If the
+    /// authentication headers have been replaced since then, another task has already logged
+    /// in again and nothing is done.
+    async fn reauthenticate(&self, generation: u64) -> Result<(), IgError> {
+        let _guard = self.login_lock.lock().await;
+
+        if self.session_read().generation != generation {
+            return Ok(());
+        }
+
+        if self.session_version == 3 && self.refresh_session().await.is_ok() {
+            return Ok(());
+        }
+
+        self.authenticate().await?;
+
+        Ok(())
+    }
+
+    /// Record in the rate limiter the historical price data points returned by a prices request.
+    fn record_data_points(&self, bucket: RateLimitBucket, status: StatusCode, body: &str) {
+        let rate_limiter = match &self.rate_limiter {
+            Some(rate_limiter) if bucket == RateLimitBucket::HistoricalDataPoints => rate_limiter,
+            _ => return,
+        };
+        if status != StatusCode::OK {
+            return;
+        }
+
+        let data_points = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|value| value.get("prices")?.as_array().map(Vec::len))
+            .unwrap_or(0);
+        rate_limiter.record_data_points(data_points as u32);
+    }
+
+    /// Refresh the session using the refresh token obtained when logging in with session version 3.
+    /// The login lock must be held.
+    async fn refresh_session(&self) -> Result<(), IgError> {
+        let refresh_token = match self.refresh_token() {
+            Some(refresh_token) => refresh_token,
+            None => {
+                return Err(IgError::Other {
+                    message: "Refresh token not found.".to_string(),
+                })
+            }
+        };
+
+        // Create and validate the refresh token request body.
+        let refresh_request_body = SessionRefreshTokenPostRequest { refresh_token };
+        refresh_request_body.validate()?;
+
+        // Send the refresh token request.
+        let request = self.build_request(
+            Method::POST,
+            format!("{}/session/refresh-token", &self.base_url),
+            "1",
+            Some(&refresh_request_body),
+        )?;
+        let response = self.transport.send(request).await?;
+
+        if response.status != StatusCode::OK {
+            return Err(IgError::from_response(
+                response.status,
+                &response_text(&response),
+            ));
+        }
+
+        let response_body = serde_json::from_slice(&response.body)?;
+        let refresh_response = SessionRefreshTokenPostResponse::from_value(&response_body)?;
+
+        let authorization =
+            HeaderValue::from_str(&format!("Bearer {}", refresh_response.access_token))?;
+        let access_token_expiry = expiry_instant(&refresh_response.expires_in)?;
+
+        // Replace the Authorization header, keeping the rest of the auth headers.
+        let mut session = self.session_write();
+        session
+            .auth_headers
+            .get_or_insert_with(HeaderMap::new)
+            .insert("Authorization", authorization);
+        session.access_token_expiry = Some(access_token_expiry);
+        session.refresh_token = Some(refresh_response.refresh_token);
+        session.generation += 1;
+
+        Ok(())
+    }
+
+    /// Returns the refresh token obtained when logging in with session version 3.
+    pub fn refresh_token(&self) -> Option<String> {
+        self.session_read().refresh_token.clone()
+    }
+
+    /// Switch to the given account with the authentication headers of a new session, updating
+    /// them with the ones returned by the switch. The switch is throttled and retried like any
+    /// other request, but without the headers of the current session. The login lock must be
+    /// held.
+    async fn restore_account(
+        &self,
+        account_id: &str,
+        auth_headers: &mut HeaderMap,
+    ) -> Result<(), IgError> {
+        let body = AccountSwitchPutRequest {
+            account_id: account_id.parse()?,
+            default_account: None,
+        };
+        let mut request = self.build_request(
+            Method::PUT,
+            format!("{}/session", &self.base_url),
+            "1",
+            Some(&body),
+        )?;
+        request.headers.extend(auth_headers.clone());
+
+        let bucket = RateLimiter::bucket_for("PUT", "session");
+        let (_, status, headers, body) = self
+            .send_with_retries(request, bucket, false, false)
+            .await?;
+        if status != StatusCode::OK {
+            return Err(IgError::from_response(status, &body));
+        }
+        update_auth_headers(auth_headers, &headers);
+
+        Ok(())
+    }
+
+    /// Send a request adding the current authentication headers and return the metadata and
+    /// body of the response. If the session has expired and auto_login is enabled, log in again
+    /// and replay the request once.
+    ///
+    /// Failed attempts are retried according to the configured retry policy. Requests that
+    /// are not idempotent are only retried if the policy explicitly allows it.
+    async fn send(
+        &self,
+        request: TransportRequest,
+        api_version: usize,
+        bucket: RateLimitBucket,
+        idempotent: bool,
+    ) -> Result<(ResponseMeta, String), IgError> {
+        let started = Instant::now();
+        let (generation, mut status, mut headers, mut body) = self
+            .send_with_retries(request.clone(), bucket, idempotent, true)
+            .await?;
+
+        if self.auto_login && is_session_expired(status, &body) {
+            // Log in again and replay the request with the new authentication headers.
+            self.reauthenticate(generation).await?;
+            (_, status, headers, body) = self
+                .send_with_retries(request, bucket, idempotent, true)
+                .await?;
+        }
+
+        let meta = ResponseMeta::new(api_version, started.elapsed(), headers, status);
+
+        Ok((meta, body))
+    }
+
+    /// Send a request once through the transport, without authentication headers handling nor
+    /// retries, and record its rate limiter usage.
+    async fn send_once(
+        &self,
+        request: TransportRequest,
+        bucket: RateLimitBucket,
+    ) -> Result<(StatusCode, HeaderMap, String), IgError> {
+        let response = self.transport.send(request).await?;
+        let body = response_text(&response);
+        self.record_data_points(bucket, response.status, &body);
+
+        Ok((response.status, response.headers, body))
+    }
+
+    /// Send a request through the rate limiter, retrying it according to the configured retry
+    /// policy. The current authentication headers are added to every attempt if
+    /// with_auth_headers is set. Returns the session generation used by the last attempt along
+    /// with the status code, headers and body of the response.
+    async fn send_with_retries(
+        &self,
+        request: TransportRequest,
+        bucket: RateLimitBucket,
+        idempotent: bool,
+        with_auth_headers: bool,
+    ) -> Result<(u64, StatusCode, HeaderMap, String), IgError> {
+        let retry_policy = self
+            .config
+            .retry_policy
+            .as_ref()
+            .filter(|retry_policy| idempotent || retry_policy.retry_non_idempotent);
+
+        let mut attempt = 1;
+        loop {
+            self.throttle(bucket).await?;
+            let mut attempt_request = request.clone();
+            let generation = {
+                let session = self.session_read();
+                match &session.auth_headers {
+                    Some(auth_headers) if with_auth_headers => {
+                        attempt_request.headers.extend(auth_headers.clone());
+                    }
+                    _ => {}
+                }
+                session.generation
+            };
+            let result = self.send_once(attempt_request, bucket).await;
+
+            let retryable = match (retry_policy, &result) {
+                (Some(retry_policy), Ok((status, _, _))) => {
+                    retry_policy.is_retryable_status(*status)
+                }
+                (Some(retry_policy), Err(e)) => retry_policy.is_retryable_error(e),
+                (None, _) => false,
+            };
+
+            match retry_policy {
+                Some(retry_policy) if retryable && attempt < retry_policy.max_attempts => {
+                    tokio::time::sleep(retry_policy.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                _ => {
+                    return result
+                        .map(|(status, headers, body)| (generation, status, headers, body))
+                }
+            }
+        }
+    }
+
+    /// Returns a snapshot of the current session.
+    pub fn session(&self) -> Session {
+        self.session_read().clone()
+    }
+
+    /// Acquire a read lock on the session. A poisoned lock is recovered, as the session is
+    /// always left consistent by its writers.
+    fn session_read(&self) -> RwLockReadGuard<'_, Session> {
+        self.session.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Acquire a write lock on the session. A poisoned lock is recovered, as the session is
+    /// always left consistent by its writers.
+    fn session_write(&self) -> RwLockWriteGuard<'_, Session> {
+        self.session.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Make the given account the active one after switching to it through PUT /session. It is
+    /// sent in the IG-ACCOUNT-ID header when using session version 3 and kept when logging in
+    /// again. The CST and X-SECURITY-TOKEN headers of the switch response, if any, replace the
+    /// current ones.
+    pub fn set_active_account(
+        &self,
+        account_id: &str,
+        response_headers: &HeaderMap,
+    ) -> Result<(), IgError> {
+        let account_id_header = HeaderValue::from_str(account_id)?;
+
+        let mut session = self.session_write();
+        if let Some(auth_headers) = session.auth_headers.as_mut() {
+            if auth_headers.contains_key("IG-ACCOUNT-ID") {
+                auth_headers.insert("IG-ACCOUNT-ID", account_id_header);
+            }
+            update_auth_headers(auth_headers, response_headers);
+        }
+        session.account_id = Some(account_id.to_string());
+
+        Ok(())
+    }
+
+    /// Start a background task that refreshes the OAuth access token shortly before it expires,
+    /// replacing the Authorization header of this client and all its clones.
+    ///
+    /// Only available when session_version is 3 and the client is logged in. If refreshing the
+    /// token fails, a full login is attempted instead. Every failure is reported through the
+    /// returned receiver, which can be dropped if failures don't need to be handled. The task
+    /// runs until the returned handle is stopped or dropped.
+    pub fn start_token_refresher(
+        &self,
+    ) -> Result<(TokenRefresher, UnboundedReceiver<IgError>), IgError> {
+        // Constraint: the access token can only be refreshed in session version 3.
+        if self.session_version != 3 {
+            return Err(IgError::Other {
                 message: format!(
-                    "PUT operation using method '{}', version '{}' and body '{:?}' failed with status code: {:?} - {:?}",
-                    method,
-                    version,
-                    serde_json::to_string(&body)?,
-                    response.status(),
-                    response.text().await?
+                    "Token refresher requires session version 3, but session version is {}.",
+                    self.session_version
                 ),
-            })),
+            });
+        }
+
+        // Constraint: the client must be logged in.
+        if self.refresh_token().is_none() {
+            return Err(IgError::Other {
+                message: "Token refresher requires the client to be logged in.".to_string(),
+            });
+        }
+
+        let (failure_sender, failure_receiver) = unbounded_channel();
+        let stop_signal = Arc::new(Notify::new());
+        let stop_notified = Arc::clone(&stop_signal);
+        let client = self.clone();
+
+        let task = tokio::spawn(async move {
+            let mut delay = client.token_refresh_delay();
+            loop {
+                tokio::select! {
+                    _ = stop_notified.notified() => break,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+
+                // Hold the login lock so that a re-login after a session expiry doesn't race
+                // with the refresh.
+                let _guard = client.login_lock.lock().await;
+
+                if let Err(e) = client.refresh_session().await {
+                    let _ = failure_sender.send(e);
+
+                    // The refresh token may have expired as well, so log in again.
+                    if let Err(e) = client.authenticate().await {
+                        let _ = failure_sender.send(e);
+                        delay = TOKEN_REFRESH_RETRY_INTERVAL;
+                        continue;
+                    }
+                }
+
+                delay = client.token_refresh_delay();
+            }
+        });
+
+        Ok((TokenRefresher { stop_signal, task }, failure_receiver))
+    }
+
+    /// Wait for the rate limiter budget needed by a request, or fail if the rate limiter is in
+    /// Reject mode and the budget is exhausted.
+    async fn throttle(&self, bucket: RateLimitBucket) -> Result<(), IgError> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(bucket).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Time to wait before refreshing the current access token.
+    fn token_refresh_delay(&self) -> Duration {
+        match self.session_read().access_token_expiry {
+            Some(access_token_expiry) => access_token_expiry
+                .saturating_duration_since(Instant::now())
+                .saturating_sub(TOKEN_REFRESH_MARGIN),
+            None => TOKEN_REFRESH_RETRY_INTERVAL,
+        }
+    }
+}
+
+/// Handle to the background task started by RestClient::start_token_refresher. Dropping it
+/// aborts the task.
+#[derive(Debug)]
+#[must_use = "dropping the token refresher stops it"]
+pub struct TokenRefresher {
+    /// Signal used to stop the background task.
+    stop_signal: Arc<Notify>,
+    /// The background task.
+    task: JoinHandle<()>,
+}
+
+/// Implementation for the TokenRefresher struct.
+impl TokenRefresher {
+    /// Check whether the background task is still running.
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Stop the background task and wait for it to finish. A refresh in progress is completed
+    /// before stopping.
+    pub async fn stop(mut self) -> Result<(), IgError> {
+        self.stop_signal.notify_one();
+        (&mut self.task).await.map_err(|e| IgError::Other {
+            message: format!("Token refresher task failed: {}", e),
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Abort the background task when the handle is dropped without being stopped.
+impl Drop for TokenRefresher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Compute the instant at which an access token expires from the expires_in field, in seconds,
+/// of an OAuth token.
+fn expiry_instant(expires_in: &str) -> Result<Instant, IgError> {
+    let seconds = expires_in.parse::<u64>().map_err(|_| IgError::Validation {
+        message: format!("Invalid access token expiry: {}", expires_in),
+    })?;
+
+    Ok(Instant::now() + Duration::from_secs(seconds))
+}
+
+/// Check whether a response means that the session tokens are no longer valid.
+fn is_session_expired(status: StatusCode, body: &str) -> bool {
+    if status != StatusCode::UNAUTHORIZED {
+        return false;
+    }
+
+    match IgError::from_response(status, body) {
+        IgError::Api { error_code, .. } => error_code.is_session_expired(),
+        _ => false,
+    }
+}
+
+/// Returns the body of a response as text, replacing invalid UTF-8 sequences.
+fn response_text(response: &TransportResponse) -> String {
+    String::from_utf8_lossy(&response.body).into_owned()
+}
+
+/// Replace the CST and X-SECURITY-TOKEN authentication headers with the ones of a response, if
+/// the response has them.
+fn update_auth_headers(auth_headers: &mut HeaderMap, response_headers: &HeaderMap) {
+    for name in ["cst", "x-security-token"] {
+        if let Some(value) = response_headers.get(name) {
+            if auth_headers.contains_key(name) {
+                auth_headers.insert(name, value.clone());
+            }
         }
     }
 }
@@ -418,31 +972,43 @@ impl RestClient {
 mod tests {
     use super::*;
     use crate::common::{ApiConfig, ExecutionEnvironment, LogType};
+    use crate::rest_models::Empty;
 
-    #[tokio::test]
-    async fn new_rest_client_works() {
-        // Create a mock API configuration
-        let config = ApiConfig {
+    /// Returns the configuration shared by the tests, logging in with the given session version.
+    fn test_config(session_version: usize) -> ApiConfig {
+        ApiConfig {
             account_number_demo: "test_account_number_demo".to_string(),
             account_number_live: "test_account_number_live".to_string(),
             account_number_test: None,
             api_key: "test_api_key".to_string(),
-            auto_login: Some(false),
+            auto_login: Some(true),
             base_url_demo: "https://demo.example.com".to_string(),
             base_url_live: "https://live.example.com".to_string(),
+            encrypted_password: None,
             execution_environment: ExecutionEnvironment::Demo,
             logger: LogType::StdLogs,
             password: "test_password".to_string(),
-            session_version: Some(2),
+            rate_limiter: None,
+            retry_policy: None,
+            session_version: Some(session_version),
             streaming_api_max_connection_attempts: None,
             username: "test_username".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn new_rest_client_works() {
+        // Create a mock API configuration
+        let config = ApiConfig {
+            auto_login: Some(false),
+            ..test_config(2)
         };
 
         // Call the `new` function with the mock configuration
         let rest_client = RestClient::new(config).await.unwrap();
 
         // Make assertions about the returned `RestClient` object
-        assert_eq!(rest_client.auth_headers, None);
+        assert_eq!(rest_client.auth_headers(), None);
         assert_eq!(rest_client.auto_login, false);
         assert_eq!(rest_client.base_url, "https://demo.example.com");
         assert_eq!(
@@ -471,4 +1037,343 @@ mod tests {
         assert_eq!(rest_client.config.username, "test_username");
         assert_eq!(rest_client.session_version, 2);
     }
+
+    #[test]
+    fn retry_policy_works() {
+        let retry_policy = RetryPolicy::default();
+
+        for attempt in 1..=3 {
+            let backoff = retry_policy.backoff(attempt);
+            let max_backoff = Duration::from_millis(500 * 2u64.pow(attempt - 1));
+            assert!(backoff <= max_backoff && backoff >= max_backoff / 2);
+        }
+        assert!(retry_policy.backoff(20) <= Duration::from_millis(retry_policy.max_backoff_ms));
+
+        assert!(retry_policy.is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!retry_policy.is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!retry_policy.is_retryable_error(&IgError::Validation {
+            message: "Invalid epic.".to_string(),
+        }));
+    }
+
+    #[tokio::test]
+    async fn rest_client_clones_share_session() {
+        let config = ApiConfig {
+            auto_login: Some(false),
+            ..test_config(2)
+        };
+        let rest_client = RestClient::new(config).await.unwrap();
+        let rest_client_clone = rest_client.clone();
+
+        let mut auth_headers = HeaderMap::new();
+        auth_headers.insert("cst", HeaderValue::from_static("test_cst"));
+        {
+            let mut session = rest_client.session_write();
+            session.auth_headers = Some(auth_headers.clone());
+            session.refresh_token = Some("test_refresh_token".to_string());
+            session.generation += 1;
+        }
+
+        assert_eq!(rest_client_clone.auth_headers(), Some(auth_headers));
+        assert_eq!(
+            rest_client_clone.refresh_token(),
+            Some("test_refresh_token".to_string())
+        );
+        assert_eq!(rest_client_clone.session().generation, 1);
+    }
+
+    /// Transport returning scripted responses and recording the requests it receives.
+    #[derive(Debug, Default)]
+    struct ScriptedTransport {
+        requests: std::sync::Mutex<Vec<TransportRequest>>,
+        responses: std::sync::Mutex<std::collections::VecDeque<TransportResponse>>,
+    }
+
+    impl ScriptedTransport {
+        fn push_response(
+            &self,
+            status: StatusCode,
+            headers: &[(&'static str, &'static str)],
+            body: &str,
+        ) {
+            let mut header_map = HeaderMap::new();
+            for (name, value) in headers {
+                header_map.insert(*name, HeaderValue::from_static(value));
+            }
+            self.responses.lock().unwrap().push_back(TransportResponse {
+                body: body.as_bytes().to_vec(),
+                headers: header_map,
+                status,
+            });
+        }
+    }
+
+    impl Transport for ScriptedTransport {
+        fn send(
+            &self,
+            request: TransportRequest,
+        ) -> futures::future::BoxFuture<'_, Result<TransportResponse, IgError>> {
+            self.requests.lock().unwrap().push(request);
+            let response = self.responses.lock().unwrap().pop_front();
+            Box::pin(async move {
+                response.ok_or_else(|| IgError::Other {
+                    message: "No scripted response left.".to_string(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn rest_client_logs_in_again_and_replays_expired_requests() {
+        let config = test_config(2);
+
+        let login_body = r#"{"lightstreamerEndpoint":"https://apd.example.com"}"#;
+        let transport = Arc::new(ScriptedTransport::default());
+        transport.push_response(
+            StatusCode::OK,
+            &[("cst", "cst_1"), ("x-security-token", "token_1")],
+            login_body,
+        );
+        transport.push_response(
+            StatusCode::UNAUTHORIZED,
+            &[],
+            r#"{"errorCode":"error.security.client-token-invalid"}"#,
+        );
+        transport.push_response(
+            StatusCode::OK,
+            &[("cst", "cst_2"), ("x-security-token", "token_2")],
+            login_body,
+        );
+        transport.push_response(StatusCode::OK, &[], r#"{"accounts":[]}"#);
+
+        let rest_client = RestClient::with_transport(config, transport.clone())
+            .await
+            .unwrap();
+        let (_, body) = rest_client
+            .get("accounts".to_string(), Some(1), &None::<Empty>)
+            .await
+            .unwrap();
+        assert_eq!(body, json!({"accounts": []}));
+        assert_eq!(
+            rest_client.lightstreamer_endpoint(),
+            "https://apd.example.com"
+        );
+        assert_eq!(rest_client.session().generation, 2);
+
+        let requests = transport.requests.lock().unwrap();
+        let paths_and_tokens: Vec<(&str, Option<&HeaderValue>)> = requests
+            .iter()
+            .map(|request| (request.url.as_str(), request.headers.get("cst")))
+            .collect();
+        assert_eq!(
+            paths_and_tokens,
+            vec![
+                ("https://demo.example.com/session", None),
+                (
+                    "https://demo.example.com/accounts",
+                    Some(&HeaderValue::from_static("cst_1"))
+                ),
+                ("https://demo.example.com/session", None),
+                (
+                    "https://demo.example.com/accounts",
+                    Some(&HeaderValue::from_static("cst_2"))
+                ),
+            ]
+        );
+        assert_eq!(requests[1].headers.get("Version").unwrap(), "1");
+    }
+
+    #[tokio::test]
+    async fn encryption_key_requests_are_retried() {
+        use base64::prelude::{Engine, BASE64_STANDARD};
+        use rsa::pkcs8::EncodePublicKey;
+
+        let config = ApiConfig {
+            encrypted_password: Some(true),
+            retry_policy: Some(RetryPolicy {
+                initial_backoff_ms: 1,
+                ..Default::default()
+            }),
+            ..test_config(2)
+        };
+
+        let private_key = rsa::RsaPrivateKey::new(&mut rsa::rand_core::OsRng, 1024).unwrap();
+        let key_der = rsa::RsaPublicKey::from(&private_key)
+            .to_public_key_der()
+            .unwrap();
+        let encryption_key_body = format!(
+            r#"{{"encryptionKey":"{}","timeStamp":1700000000000}}"#,
+            BASE64_STANDARD.encode(key_der.as_bytes())
+        );
+
+        let transport = Arc::new(ScriptedTransport::default());
+        transport.push_response(StatusCode::SERVICE_UNAVAILABLE, &[], "");
+        transport.push_response(StatusCode::OK, &[], &encryption_key_body);
+        transport.push_response(
+            StatusCode::OK,
+            &[("cst", "cst_1"), ("x-security-token", "token_1")],
+            r#"{"lightstreamerEndpoint":"https://apd.example.com"}"#,
+        );
+
+        RestClient::with_transport(config, transport.clone())
+            .await
+            .unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        let urls: Vec<&str> = requests
+            .iter()
+            .map(|request| request.url.as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://demo.example.com/session/encryptionKey",
+                "https://demo.example.com/session/encryptionKey",
+                "https://demo.example.com/session",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn token_refresher_refreshes_tokens_and_reports_failures() {
+        let config = test_config(3);
+
+        // Tokens expiring within the refresh margin are refreshed straight away.
+        let transport = Arc::new(ScriptedTransport::default());
+        transport.push_response(
+            StatusCode::OK,
+            &[],
+            r#"{"accountId":"ABC123","clientId":"101","lightstreamerEndpoint":"https://apd.example.com","oauthToken":{"access_token":"token_1","expires_in":"15","refresh_token":"refresh_1","scope":"profile","token_type":"Bearer"},"timezoneOffset":1}"#,
+        );
+        transport.push_response(
+            StatusCode::OK,
+            &[],
+            r#"{"access_token":"token_2","expires_in":"15","refresh_token":"refresh_2","scope":"profile","token_type":"Bearer"}"#,
+        );
+        transport.push_response(
+            StatusCode::UNAUTHORIZED,
+            &[],
+            r#"{"errorCode":"error.security.oauth-token-invalid"}"#,
+        );
+
+        let rest_client = RestClient::with_transport(config, transport.clone())
+            .await
+            .unwrap();
+        let (token_refresher, mut failures) = rest_client.start_token_refresher().unwrap();
+
+        // The failed refresh and the failed login attempted after it are both reported.
+        for _ in 0..2 {
+            let failure = tokio::time::timeout(Duration::from_secs(5), failures.recv())
+                .await
+                .unwrap();
+            assert!(failure.is_some());
+        }
+        assert_eq!(
+            rest_client
+                .auth_headers()
+                .unwrap()
+                .get("Authorization")
+                .unwrap(),
+            "Bearer token_2"
+        );
+        assert_eq!(rest_client.refresh_token(), Some("refresh_2".to_string()));
+        assert!(token_refresher.is_running());
+
+        // Dropping the handle aborts the task, releasing its clone of the client.
+        drop(token_refresher);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(Arc::strong_count(&transport), 2);
+    }
+
+    #[tokio::test]
+    async fn only_delete_requests_are_sent_with_the_method_override() {
+        let transport = Arc::new(ScriptedTransport::default());
+        transport.push_response(
+            StatusCode::OK,
+            &[("cst", "cst_1"), ("x-security-token", "token_1")],
+            r#"{"lightstreamerEndpoint":"https://apd.example.com"}"#,
+        );
+        transport.push_response(StatusCode::OK, &[], r#"{"dealReference":"REF_1"}"#);
+        transport.push_response(StatusCode::OK, &[], r#"{"dealReference":"REF_2"}"#);
+
+        let rest_client = RestClient::with_transport(test_config(2), transport.clone())
+            .await
+            .unwrap();
+        rest_client
+            .post("positions/sprintmarkets".to_string(), Some(1), &Empty {})
+            .await
+            .unwrap();
+        rest_client
+            .delete("positions/otc".to_string(), Some(1), &None::<Empty>)
+            .await
+            .unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[1].method, Method::POST);
+        assert_eq!(
+            requests[1].url,
+            "https://demo.example.com/positions/sprintmarkets"
+        );
+        assert!(requests[1].headers.get("_method").is_none());
+        assert_eq!(requests[2].method, Method::POST);
+        assert_eq!(requests[2].headers.get("_method").unwrap(), "DELETE");
+    }
+
+    #[test]
+    fn expiry_instant_works() {
+        let expiry = expiry_instant("60").unwrap();
+        let remaining = expiry.saturating_duration_since(Instant::now());
+        assert!(remaining > Duration::from_secs(55) && remaining <= Duration::from_secs(60));
+
+        assert!(expiry_instant("").is_err());
+        assert!(expiry_instant("sixty").is_err());
+    }
+
+    #[test]
+    fn ig_error_from_response_works() {
+        let error = IgError::from_response(
+            StatusCode::FORBIDDEN,
+            r#"{"errorCode":"error.public-api.exceeded-api-key-allowance"}"#,
+        );
+        assert_eq!(error.status(), Some(StatusCode::FORBIDDEN));
+        assert_eq!(
+            error.error_code(),
+            Some(&IgErrorCode::ExceededApiKeyAllowance)
+        );
+        assert!(error.error_code().unwrap().is_allowance_exceeded());
+
+        let error = IgError::from_response(
+            StatusCode::BAD_REQUEST,
+            r#"{"errorCode":"error.unknown.code"}"#,
+        );
+        assert_eq!(
+            error.error_code(),
+            Some(&IgErrorCode::Other("error.unknown.code".to_string()))
+        );
+
+        let error = IgError::from_response(StatusCode::BAD_GATEWAY, "Bad Gateway");
+        assert!(matches!(error, IgError::Http { .. }));
+        assert_eq!(error.error_code(), None);
+    }
+
+    #[test]
+    fn is_session_expired_works() {
+        let expired_bodies = [
+            r#"{"errorCode":"error.security.client-token-invalid"}"#,
+            r#"{"errorCode":"error.security.oauth-token-invalid"}"#,
+        ];
+        for body in expired_bodies {
+            assert!(is_session_expired(StatusCode::UNAUTHORIZED, body));
+            assert!(!is_session_expired(StatusCode::FORBIDDEN, body));
+        }
+
+        assert!(!is_session_expired(
+            StatusCode::UNAUTHORIZED,
+            r#"{"errorCode":"error.security.invalid-details"}"#
+        ));
+        assert!(!is_session_expired(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized"
+        ));
+    }
 }
@@ -0,0 +1,1438 @@
+use crate::common::*;
+use crate::rest_models::*;
+use crate::rest_regex::*;
+use reqwest::Method;
+use serde::Serialize;
+
+/// Trait to define a REST API endpoint declaratively. Endpoints are sent through
+/// RestApi::execute, so endpoints not covered by this crate can be added downstream by
+/// implementing this trait.
+pub trait Endpoint: Sync {
+    /// The query parameters of GET requests, or the body of other requests.
+    type Request: Serialize + ValidateRequest + Sync;
+    /// The response model.
+    type Response: ValidateResponse;
+
+    /// The HTTP method, one of GET, POST, PUT or DELETE. DELETE requests are sent as POST
+    /// requests with the `_method` header, so they can have a body.
+    const METHOD: Method;
+    /// The path relative to the API base URL, with `{name}` placeholders for the path parameters.
+    const PATH: &'static str;
+    /// The API version sent in the Version header.
+    const VERSION: usize;
+
+    /// Returns the path with its placeholders replaced by the path parameters. The placeholders
+    /// are read from the template, so parameter values are inserted verbatim.
+    fn path(&self) -> Result<String, IgError> {
+        let params = self.path_params();
+        let mut path = String::with_capacity(Self::PATH.len());
+        let mut template = Self::PATH;
+
+        while let Some(start) = template.find('{') {
+            let missing = || IgError::Validation {
+                message: format!("Missing path parameter in {}.", Self::PATH),
+            };
+            let end = start + template[start..].find('}').ok_or_else(missing)?;
+            let name = &template[start + 1..end];
+            let (_, value) = params
+                .iter()
+                .find(|(param, _)| *param == name)
+                .ok_or_else(missing)?;
+
+            path.push_str(&template[..start]);
+            path.push_str(value);
+            template = &template[end + 1..];
+        }
+        path.push_str(template);
+
+        Ok(path)
+    }
+
+    /// Returns the values of the path parameters by placeholder name.
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        Vec::new()
+    }
+
+    /// Returns the query parameters or body to send, if any.
+    fn request(&self) -> Option<&Self::Request> {
+        None
+    }
+
+    /// Validate the path parameters. The request is validated by the REST client.
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// ACCOUNT ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /accounts endpoint.
+#[derive(Debug)]
+pub struct AccountsGet;
+
+impl Endpoint for AccountsGet {
+    type Request = Empty;
+    type Response = AccountsGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "accounts";
+    const VERSION: usize = 1;
+}
+
+/// GET /accounts/preferences endpoint.
+#[derive(Debug)]
+pub struct AccountsPreferencesGet;
+
+impl Endpoint for AccountsPreferencesGet {
+    type Request = Empty;
+    type Response = AccountsPreferencesGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "accounts/preferences";
+    const VERSION: usize = 1;
+}
+
+/// PUT /accounts/preferences endpoint.
+#[derive(Debug)]
+pub struct AccountsPreferencesPut<'a> {
+    /// The new account preferences.
+    pub body: &'a AccountsPreferencesPutRequest,
+}
+
+impl Endpoint for AccountsPreferencesPut<'_> {
+    type Request = AccountsPreferencesPutRequest;
+    type Response = AccountsPreferencesStatusPutResponse;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "accounts/preferences";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// CLIENT SENTIMENT ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /clientsentiment endpoint.
+#[derive(Debug)]
+pub struct ClientSentimentGet<'a> {
+    /// The markets to get the client sentiment for.
+    pub params: &'a SentimentQuery,
+}
+
+impl Endpoint for ClientSentimentGet<'_> {
+    type Request = SentimentQuery;
+    type Response = Sentiments;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "clientsentiment";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /clientsentiment/{marketId} endpoint.
+#[derive(Debug)]
+pub struct ClientSentimentMarketGet<'a> {
+    /// Market identifier.
+    pub market_id: &'a str,
+}
+
+impl Endpoint for ClientSentimentMarketGet<'_> {
+    type Request = Empty;
+    type Response = Sentiment;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "clientsentiment/{marketId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("marketId", self.market_id)]
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        SentimentMarketRequest {
+            market_id: self.market_id.to_string(),
+        }
+        .validate()
+    }
+}
+
+/// GET /clientsentiment/related/{marketId} endpoint.
+#[derive(Debug)]
+pub struct ClientSentimentRelatedGet<'a> {
+    /// Market identifier.
+    pub market_id: &'a str,
+}
+
+impl Endpoint for ClientSentimentRelatedGet<'_> {
+    type Request = Empty;
+    type Response = Sentiments;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "clientsentiment/related/{marketId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("marketId", self.market_id)]
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        SentimentMarketRequest {
+            market_id: self.market_id.to_string(),
+        }
+        .validate()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// CONFIRMS ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /confirms/{dealReference} endpoint.
+#[derive(Debug)]
+pub struct ConfirmsGet<'a> {
+    /// Deal reference.
+    pub deal_reference: &'a DealReference,
+}
+
+impl Endpoint for ConfirmsGet<'_> {
+    type Request = Empty;
+    type Response = ConfirmsGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "confirms/{dealReference}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealReference", self.deal_reference.as_str())]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// HISTORY ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /history/activity endpoint.
+#[derive(Debug)]
+pub struct HistoryActivityGet<'a> {
+    /// The activity history query parameters.
+    pub params: &'a ActivityHistoryGetRequest,
+}
+
+impl Endpoint for HistoryActivityGet<'_> {
+    type Request = ActivityHistoryGetRequest;
+    type Response = ActivityHistoryGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "history/activity";
+    const VERSION: usize = 3;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /history/activity/{lastPeriod} and GET /history/activity/{fromDate}/{toDate} endpoints
+/// (version 1).
+#[derive(Debug)]
+pub struct HistoryActivityV1Get<'a> {
+    /// The period to get the activity of.
+    pub period: &'a HistoryPeriod,
+}
+
+impl Endpoint for HistoryActivityV1Get<'_> {
+    type Request = Empty;
+    type Response = ActivityHistoryV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "history/activity";
+    const VERSION: usize = 1;
+
+    fn path(&self) -> Result<String, IgError> {
+        Ok(format!("{}/{}", Self::PATH, self.period.path()))
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        self.period.validate()
+    }
+}
+
+/// GET /history/activity endpoint (version 2).
+#[derive(Debug)]
+pub struct HistoryActivityV2Get<'a> {
+    /// The activity history query parameters.
+    pub params: &'a ActivityHistoryV2GetRequest,
+}
+
+impl Endpoint for HistoryActivityV2Get<'_> {
+    type Request = ActivityHistoryV2GetRequest;
+    type Response = ActivityHistoryV2GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "history/activity";
+    const VERSION: usize = 2;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /history/transactions endpoint.
+#[derive(Debug)]
+pub struct HistoryTransactionsGet<'a> {
+    /// The transaction history query parameters.
+    pub params: &'a TransactionHistoryGetRequest,
+}
+
+impl Endpoint for HistoryTransactionsGet<'_> {
+    type Request = TransactionHistoryGetRequest;
+    type Response = TransactionHistoryGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "history/transactions";
+    const VERSION: usize = 2;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /history/transactions/{transactionType}/{lastPeriod} and
+/// GET /history/transactions/{transactionType}/{fromDate}/{toDate} endpoints (version 1).
+#[derive(Debug)]
+pub struct HistoryTransactionsV1Get<'a> {
+    /// The period to get the transactions of.
+    pub period: &'a HistoryPeriod,
+    /// Transaction type.
+    pub transaction_type: &'a TransactionType,
+}
+
+impl Endpoint for HistoryTransactionsV1Get<'_> {
+    type Request = Empty;
+    type Response = TransactionHistoryV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "history/transactions";
+    const VERSION: usize = 1;
+
+    fn path(&self) -> Result<String, IgError> {
+        Ok(format!(
+            "{}/{}/{}",
+            Self::PATH,
+            self.transaction_type.as_str(),
+            self.period.path()
+        ))
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        self.period.validate()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// MARKETS ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /markets/{epic} endpoint.
+#[derive(Debug)]
+pub struct MarketGet<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+}
+
+impl Endpoint for MarketGet<'_> {
+    type Request = Empty;
+    type Response = MarketDetails;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "markets/{epic}";
+    const VERSION: usize = 3;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("epic", self.epic.as_str())]
+    }
+}
+
+/// GET /markets/{epic} endpoint (version 1).
+#[derive(Debug)]
+pub struct MarketV1Get<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+}
+
+impl Endpoint for MarketV1Get<'_> {
+    type Request = Empty;
+    type Response = MarketDetailsV1;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "markets/{epic}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("epic", self.epic.as_str())]
+    }
+}
+
+/// GET /markets/{epic} endpoint (version 2).
+#[derive(Debug)]
+pub struct MarketV2Get<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+}
+
+impl Endpoint for MarketV2Get<'_> {
+    type Request = Empty;
+    type Response = MarketDetailsV1;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "markets/{epic}";
+    const VERSION: usize = 2;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("epic", self.epic.as_str())]
+    }
+}
+
+/// GET /marketnavigation and GET /marketnavigation/{nodeId} endpoints.
+#[derive(Debug)]
+pub struct MarketNavigationGet<'a> {
+    /// Node identifier, or None for the top-level nodes.
+    pub node_id: Option<&'a str>,
+}
+
+impl Endpoint for MarketNavigationGet<'_> {
+    type Request = Empty;
+    type Response = MarketNavigationGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "marketnavigation";
+    const VERSION: usize = 1;
+
+    fn path(&self) -> Result<String, IgError> {
+        Ok(match self.node_id {
+            Some(node_id) => format!("{}/{}", Self::PATH, node_id),
+            None => Self::PATH.to_string(),
+        })
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        match self.node_id {
+            Some(node_id) if !NODE_ID_REGEX.is_match(node_id) => Err(IgError::Validation {
+                message: "Node ID field is invalid.".to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// GET /markets?epics={epics} endpoint.
+#[derive(Debug)]
+pub struct MarketsGet<'a> {
+    /// The markets to get the details of.
+    pub params: &'a MarketsGetRequest,
+}
+
+impl Endpoint for MarketsGet<'_> {
+    type Request = MarketsGetRequest;
+    type Response = MarketsGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "markets";
+    const VERSION: usize = 2;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /markets?searchTerm={searchTerm} endpoint.
+#[derive(Debug)]
+pub struct MarketsSearch<'a> {
+    /// The search term.
+    pub params: &'a MarketSearchRequest,
+}
+
+impl Endpoint for MarketsSearch<'_> {
+    type Request = MarketSearchRequest;
+    type Response = MarketSearch;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "markets";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// OPERATIONS ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// PUT /operations/application/disable endpoint.
+#[derive(Debug)]
+pub struct OperationsApplicationDisable;
+
+impl Endpoint for OperationsApplicationDisable {
+    type Request = Empty;
+    type Response = Application;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "operations/application/disable";
+    const VERSION: usize = 1;
+}
+
+/// GET /operations/application endpoint.
+#[derive(Debug)]
+pub struct OperationsApplicationGet;
+
+impl Endpoint for OperationsApplicationGet {
+    type Request = Empty;
+    type Response = Applications;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "operations/application";
+    const VERSION: usize = 1;
+}
+
+/// PUT /operations/application endpoint.
+#[derive(Debug)]
+pub struct OperationsApplicationPut<'a> {
+    /// The application update.
+    pub body: &'a UpdateApplication,
+}
+
+impl Endpoint for OperationsApplicationPut<'_> {
+    type Request = UpdateApplication;
+    type Response = Application;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "operations/application";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// POSITIONS ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// DELETE /positions/otc endpoint.
+#[derive(Debug)]
+pub struct PositionDelete<'a> {
+    /// The position to close.
+    pub body: &'a PositionDeleteRequest,
+}
+
+impl Endpoint for PositionDelete<'_> {
+    type Request = PositionDeleteRequest;
+    type Response = PositionDeleteResponse;
+
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "positions/otc";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// GET /positions/{dealId} endpoint.
+#[derive(Debug)]
+pub struct PositionGet<'a> {
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for PositionGet<'_> {
+    type Request = Empty;
+    type Response = PositionGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "positions/{dealId}";
+    const VERSION: usize = 2;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+}
+
+/// POST /positions/otc endpoint.
+#[derive(Debug)]
+pub struct PositionPost<'a> {
+    /// The position to open.
+    pub body: &'a PositionPostRequest,
+}
+
+impl Endpoint for PositionPost<'_> {
+    type Request = PositionPostRequest;
+    type Response = PositionPostResponse;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "positions/otc";
+    const VERSION: usize = 2;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// PUT /positions/otc/{dealId} endpoint.
+#[derive(Debug)]
+pub struct PositionPut<'a> {
+    /// The position update.
+    pub body: &'a PositionPutRequest,
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for PositionPut<'_> {
+    type Request = PositionPutRequest;
+    type Response = PositionPutResponse;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "positions/otc/{dealId}";
+    const VERSION: usize = 2;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// GET /positions/{dealId} endpoint (version 1).
+#[derive(Debug)]
+pub struct PositionV1Get<'a> {
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for PositionV1Get<'_> {
+    type Request = Empty;
+    type Response = PositionV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "positions/{dealId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+}
+
+/// POST /positions/otc endpoint (version 1).
+#[derive(Debug)]
+pub struct PositionV1Post<'a> {
+    /// The position to open.
+    pub body: &'a PositionV1PostRequest,
+}
+
+impl Endpoint for PositionV1Post<'_> {
+    type Request = PositionV1PostRequest;
+    type Response = PositionPostResponse;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "positions/otc";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// PUT /positions/otc/{dealId} endpoint (version 1).
+#[derive(Debug)]
+pub struct PositionV1Put<'a> {
+    /// The position update.
+    pub body: &'a PositionV1PutRequest,
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for PositionV1Put<'_> {
+    type Request = PositionV1PutRequest;
+    type Response = PositionPutResponse;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "positions/otc/{dealId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// GET /positions endpoint.
+#[derive(Debug)]
+pub struct PositionsGet;
+
+impl Endpoint for PositionsGet {
+    type Request = Empty;
+    type Response = PositionsGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "positions";
+    const VERSION: usize = 2;
+}
+
+/// GET /positions endpoint (version 1).
+#[derive(Debug)]
+pub struct PositionsV1Get;
+
+impl Endpoint for PositionsV1Get {
+    type Request = Empty;
+    type Response = PositionsV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "positions";
+    const VERSION: usize = 1;
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// SPRINT MARKET ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /positions/sprintmarkets endpoint.
+#[derive(Debug)]
+pub struct PositionsSprintMarketsGet;
+
+impl Endpoint for PositionsSprintMarketsGet {
+    type Request = Empty;
+    type Response = SprintMarketPositionsGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "positions/sprintmarkets";
+    const VERSION: usize = 2;
+}
+
+/// POST /positions/sprintmarkets endpoint.
+#[derive(Debug)]
+pub struct PositionsSprintMarketsPost<'a> {
+    /// The sprint market position to create.
+    pub body: &'a SprintMarketPositionsPostRequest,
+}
+
+impl Endpoint for PositionsSprintMarketsPost<'_> {
+    type Request = SprintMarketPositionsPostRequest;
+    type Response = SprintMarketPositionsPostResponse;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "positions/sprintmarkets";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// PRICES ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// GET /prices/{epic} endpoint.
+#[derive(Debug)]
+pub struct PricesGet<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+    /// The prices query parameters.
+    pub params: &'a PricesGetRequest,
+}
+
+impl Endpoint for PricesGet<'_> {
+    type Request = PricesGetRequest;
+    type Response = PricesGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "prices/{epic}";
+    const VERSION: usize = 3;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("epic", self.epic.as_str())]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /prices/{epic}/{resolution} endpoint (version 1).
+#[derive(Debug)]
+pub struct PricesV1Get<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+    /// The prices query parameters.
+    pub params: &'a PricesV1GetRequest,
+    /// Price resolution.
+    pub resolution: &'a Resolution,
+}
+
+impl Endpoint for PricesV1Get<'_> {
+    type Request = PricesV1GetRequest;
+    type Response = PricesV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "prices/{epic}/{resolution}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("epic", self.epic.as_str()),
+            ("resolution", self.resolution.as_str()),
+        ]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.params)
+    }
+}
+
+/// GET /prices/{epic}/{resolution}/{numPoints} and
+/// GET /prices/{epic}/{resolution}/{startDate}/{endDate} endpoints (version 2).
+#[derive(Debug)]
+pub struct PricesV2Get<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+    /// The prices to get.
+    pub range: &'a PriceRange,
+    /// Price resolution.
+    pub resolution: &'a Resolution,
+}
+
+impl Endpoint for PricesV2Get<'_> {
+    type Request = Empty;
+    type Response = PricesV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "prices";
+    const VERSION: usize = 2;
+
+    fn path(&self) -> Result<String, IgError> {
+        Ok(format!(
+            "{}/{}/{}/{}",
+            Self::PATH,
+            self.epic,
+            self.resolution.as_str(),
+            self.range.path()
+        ))
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        self.range.validate()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// SESSION ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// DELETE /session endpoint.
+#[derive(Debug)]
+pub struct SessionDelete;
+
+impl Endpoint for SessionDelete {
+    type Request = Empty;
+    type Response = Empty;
+
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "session";
+    const VERSION: usize = 1;
+}
+
+/// GET /session/encryptionKey endpoint.
+#[derive(Debug)]
+pub struct SessionEncryptionKeyGet;
+
+impl Endpoint for SessionEncryptionKeyGet {
+    type Request = Empty;
+    type Response = SessionEncryptionKeyGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "session/encryptionKey";
+    const VERSION: usize = 1;
+}
+
+/// GET /session endpoint.
+#[derive(Debug)]
+pub struct SessionGet<'a> {
+    /// The session details query parameters, if any.
+    pub params: Option<&'a SessionDetailsGetRequest>,
+}
+
+impl Endpoint for SessionGet<'_> {
+    type Request = SessionDetailsGetRequest;
+    type Response = SessionDetailsGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "session";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        self.params
+    }
+}
+
+/// PUT /session endpoint.
+#[derive(Debug)]
+pub struct SessionPut<'a> {
+    /// The account to switch to.
+    pub body: &'a AccountSwitchPutRequest,
+}
+
+impl Endpoint for SessionPut<'_> {
+    type Request = AccountSwitchPutRequest;
+    type Response = AccountSwitchPutResponse;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "session";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// POST /session/refresh-token endpoint.
+#[derive(Debug)]
+pub struct SessionRefreshTokenPost<'a> {
+    /// The refresh token.
+    pub body: &'a SessionRefreshTokenPostRequest,
+}
+
+impl Endpoint for SessionRefreshTokenPost<'_> {
+    type Request = SessionRefreshTokenPostRequest;
+    type Response = SessionRefreshTokenPostResponse;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "session/refresh-token";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// WATCHLISTS ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// DELETE /watchlists/{watchlistId} endpoint.
+#[derive(Debug)]
+pub struct WatchlistDelete<'a> {
+    /// Watchlist identifier.
+    pub watchlist_id: &'a str,
+}
+
+impl Endpoint for WatchlistDelete<'_> {
+    type Request = Empty;
+    type Response = WatchlistStatusResult;
+
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "watchlists/{watchlistId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("watchlistId", self.watchlist_id)]
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        WatchlistRequest {
+            watchlist_id: self.watchlist_id.to_string(),
+        }
+        .validate()
+    }
+}
+
+/// DELETE /watchlists/{watchlistId}/{epic} endpoint.
+#[derive(Debug)]
+pub struct WatchlistEpicDelete<'a> {
+    /// Instrument epic identifier.
+    pub epic: &'a Epic,
+    /// Watchlist identifier.
+    pub watchlist_id: &'a str,
+}
+
+impl Endpoint for WatchlistEpicDelete<'_> {
+    type Request = Empty;
+    type Response = WatchlistStatusResult;
+
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "watchlists/{watchlistId}/{epic}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("epic", self.epic.as_str()),
+            ("watchlistId", self.watchlist_id),
+        ]
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        WatchlistEpicDeleteRequest {
+            epic: self.epic.clone(),
+            watchlist_id: self.watchlist_id.to_string(),
+        }
+        .validate()
+    }
+}
+
+/// GET /watchlists/{watchlistId} endpoint.
+#[derive(Debug)]
+pub struct WatchlistGet<'a> {
+    /// Watchlist identifier.
+    pub watchlist_id: &'a str,
+}
+
+impl Endpoint for WatchlistGet<'_> {
+    type Request = Empty;
+    type Response = WatchlistMarkets;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "watchlists/{watchlistId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("watchlistId", self.watchlist_id)]
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        WatchlistRequest {
+            watchlist_id: self.watchlist_id.to_string(),
+        }
+        .validate()
+    }
+}
+
+/// PUT /watchlists/{watchlistId} endpoint.
+#[derive(Debug)]
+pub struct WatchlistPut<'a> {
+    /// The market to add.
+    pub body: &'a AddToWatchlist,
+    /// Watchlist identifier.
+    pub watchlist_id: &'a str,
+}
+
+impl Endpoint for WatchlistPut<'_> {
+    type Request = AddToWatchlist;
+    type Response = WatchlistStatusResult;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "watchlists/{watchlistId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("watchlistId", self.watchlist_id)]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+
+    fn validate_path_params(&self) -> Result<(), IgError> {
+        WatchlistRequest {
+            watchlist_id: self.watchlist_id.to_string(),
+        }
+        .validate()
+    }
+}
+
+/// GET /watchlists endpoint.
+#[derive(Debug)]
+pub struct WatchlistsGet;
+
+impl Endpoint for WatchlistsGet {
+    type Request = Empty;
+    type Response = Watchlists;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "watchlists";
+    const VERSION: usize = 1;
+}
+
+/// POST /watchlists endpoint.
+#[derive(Debug)]
+pub struct WatchlistsPost<'a> {
+    /// The watchlist to create.
+    pub body: &'a CreateWatchlist,
+}
+
+impl Endpoint for WatchlistsPost<'_> {
+    type Request = CreateWatchlist;
+    type Response = CreateWatchlistResult;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "watchlists";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// WORKINGORDERS ENDPOINTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// DELETE /workingorders/otc/{dealId} endpoint.
+#[derive(Debug)]
+pub struct WorkingOrdersDelete<'a> {
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for WorkingOrdersDelete<'_> {
+    type Request = Empty;
+    type Response = WorkingOrderDeleteResponse;
+
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "workingorders/otc/{dealId}";
+    const VERSION: usize = 2;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+}
+
+/// GET /workingorders endpoint.
+#[derive(Debug)]
+pub struct WorkingOrdersGet;
+
+impl Endpoint for WorkingOrdersGet {
+    type Request = Empty;
+    type Response = WorkingOrdersGetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "workingorders";
+    const VERSION: usize = 2;
+}
+
+/// POST /workingorders/otc endpoint.
+#[derive(Debug)]
+pub struct WorkingOrdersPost<'a> {
+    /// The working order to create.
+    pub body: &'a WorkingOrderPostRequest,
+}
+
+impl Endpoint for WorkingOrdersPost<'_> {
+    type Request = WorkingOrderPostRequest;
+    type Response = WorkingOrderPostResponse;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "workingorders/otc";
+    const VERSION: usize = 2;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// PUT /workingorders/otc/{dealId} endpoint.
+#[derive(Debug)]
+pub struct WorkingOrdersPut<'a> {
+    /// The working order update.
+    pub body: &'a WorkingOrderPutRequest,
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for WorkingOrdersPut<'_> {
+    type Request = WorkingOrderPutRequest;
+    type Response = WorkingOrderPutResponse;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "workingorders/otc/{dealId}";
+    const VERSION: usize = 2;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// DELETE /workingorders/otc/{dealId} endpoint (version 1).
+#[derive(Debug)]
+pub struct WorkingOrdersV1Delete<'a> {
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for WorkingOrdersV1Delete<'_> {
+    type Request = Empty;
+    type Response = WorkingOrderDeleteResponse;
+
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "workingorders/otc/{dealId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+}
+
+/// GET /workingorders endpoint (version 1).
+#[derive(Debug)]
+pub struct WorkingOrdersV1Get;
+
+impl Endpoint for WorkingOrdersV1Get {
+    type Request = Empty;
+    type Response = WorkingOrdersV1GetResponse;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "workingorders";
+    const VERSION: usize = 1;
+}
+
+/// POST /workingorders/otc endpoint (version 1).
+#[derive(Debug)]
+pub struct WorkingOrdersV1Post<'a> {
+    /// The working order to create.
+    pub body: &'a WorkingOrderV1PostRequest,
+}
+
+impl Endpoint for WorkingOrdersV1Post<'_> {
+    type Request = WorkingOrderV1PostRequest;
+    type Response = WorkingOrderPostResponse;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "workingorders/otc";
+    const VERSION: usize = 1;
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+/// PUT /workingorders/otc/{dealId} endpoint (version 1).
+#[derive(Debug)]
+pub struct WorkingOrdersV1Put<'a> {
+    /// The working order update.
+    pub body: &'a WorkingOrderV1PutRequest,
+    /// Deal identifier.
+    pub deal_id: &'a DealId,
+}
+
+impl Endpoint for WorkingOrdersV1Put<'_> {
+    type Request = WorkingOrderV1PutRequest;
+    type Response = WorkingOrderPutResponse;
+
+    const METHOD: Method = Method::PUT;
+    const PATH: &'static str = "workingorders/otc/{dealId}";
+    const VERSION: usize = 1;
+
+    fn path_params(&self) -> Vec<(&'static str, &str)> {
+        vec![("dealId", self.deal_id.as_str())]
+    }
+
+    fn request(&self) -> Option<&Self::Request> {
+        Some(self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn endpoint_path_replaces_placeholders() {
+        let epic = "IX.D.DAX.IFMM.IP".parse().unwrap();
+        let endpoint = WatchlistEpicDelete {
+            epic: &epic,
+            watchlist_id: "12345",
+        };
+        assert_eq!(
+            endpoint.path().unwrap(),
+            "watchlists/12345/IX.D.DAX.IFMM.IP"
+        );
+
+        let endpoint = MarketNavigationGet { node_id: None };
+        assert_eq!(endpoint.path().unwrap(), "marketnavigation");
+
+        let period = HistoryPeriod::DateRange {
+            from: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 2, 3).unwrap(),
+        };
+        let endpoint = HistoryTransactionsV1Get {
+            period: &period,
+            transaction_type: &TransactionType::AllDeal,
+        };
+        assert_eq!(
+            endpoint.path().unwrap(),
+            "history/transactions/ALL_DEAL/02-01-2024/03-02-2024"
+        );
+
+        let period = HistoryPeriod::LastPeriod(std::time::Duration::from_secs(600));
+        let endpoint = HistoryActivityV1Get { period: &period };
+        assert_eq!(endpoint.path().unwrap(), "history/activity/600000");
+
+        let epic = "IX.D.DAX.IFMM.IP".parse().unwrap();
+        let params = PricesV1GetRequest {
+            end_date: NaiveDate::from_ymd_opt(2024, 6, 3)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+            start_date: NaiveDate::from_ymd_opt(2024, 6, 3)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+        };
+        let endpoint = PricesV1Get {
+            epic: &epic,
+            params: &params,
+            resolution: &Resolution::Minute15,
+        };
+        assert_eq!(
+            endpoint.path().unwrap(),
+            "prices/IX.D.DAX.IFMM.IP/MINUTE_15"
+        );
+        assert_eq!(
+            params_to_query_string(&params).unwrap(),
+            "startdate=2024%3A06%3A03-09%3A00%3A00&enddate=2024%3A06%3A03-10%3A00%3A00"
+        );
+
+        let range = PriceRange::DateRange {
+            from: params.start_date,
+            to: params.end_date,
+        };
+        let endpoint = PricesV2Get {
+            epic: &epic,
+            range: &range,
+            resolution: &Resolution::Hour,
+        };
+        assert_eq!(
+            endpoint.path().unwrap(),
+            "prices/IX.D.DAX.IFMM.IP/HOUR/2024-06-03 09:00:00/2024-06-03 10:00:00"
+        );
+
+        let endpoint = PricesV2Get {
+            epic: &epic,
+            range: &PriceRange::NumPoints(10),
+            resolution: &Resolution::Day,
+        };
+        assert_eq!(endpoint.path().unwrap(), "prices/IX.D.DAX.IFMM.IP/DAY/10");
+    }
+
+    #[test]
+    fn endpoint_path_fails_on_missing_placeholders() {
+        struct MissingParam;
+
+        impl Endpoint for MissingParam {
+            type Request = Empty;
+            type Response = Empty;
+
+            const METHOD: Method = Method::GET;
+            const PATH: &'static str = "positions/{dealId}";
+            const VERSION: usize = 1;
+        }
+
+        assert!(matches!(
+            MissingParam.path(),
+            Err(IgError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn endpoint_path_keeps_braces_in_param_values() {
+        struct BraceParam;
+
+        impl Endpoint for BraceParam {
+            type Request = Empty;
+            type Response = Empty;
+
+            const METHOD: Method = Method::GET;
+            const PATH: &'static str = "watchlists/{watchlistId}/{epic}";
+            const VERSION: usize = 1;
+
+            fn path_params(&self) -> Vec<(&'static str, &str)> {
+                vec![("watchlistId", "{epic}"), ("epic", "IX.D.DAX.IFMM.IP")]
+            }
+        }
+
+        assert_eq!(
+            BraceParam.path().unwrap(),
+            "watchlists/{epic}/IX.D.DAX.IFMM.IP"
+        );
+    }
+
+    #[test]
+    fn sprint_market_positions_are_created_with_post() {
+        let body = SprintMarketPositionsPostRequest {
+            deal_reference: None,
+            direction: None,
+            epic: "FM.D.FTSE.FTSE.IP".parse().unwrap(),
+            expiry_period: None,
+            size: Number::from(1u8),
+        };
+        let endpoint = PositionsSprintMarketsPost { body: &body };
+
+        // Only DELETE endpoints are sent as POST requests with the _method header.
+        assert_eq!(PositionsSprintMarketsPost::METHOD, Method::POST);
+        assert_eq!(endpoint.path().unwrap(), "positions/sprintmarkets");
+        assert!(endpoint.request().is_some());
+    }
+
+    #[test]
+    fn market_navigation_node_ids_are_validated() {
+        let endpoint = MarketNavigationGet {
+            node_id: Some("97601"),
+        };
+        assert!(endpoint.validate_path_params().is_ok());
+        assert_eq!(endpoint.path().unwrap(), "marketnavigation/97601");
+
+        assert!(MarketNavigationGet { node_id: None }
+            .validate_path_params()
+            .is_ok());
+        for node_id in ["", "97601/195235", "../accounts", "97601?x=1"] {
+            assert!(matches!(
+                MarketNavigationGet {
+                    node_id: Some(node_id)
+                }
+                .validate_path_params(),
+                Err(IgError::Validation { .. })
+            ));
+        }
+    }
+}
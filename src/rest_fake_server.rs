@@ -0,0 +1,1828 @@
+use crate::common::*;
+use crate::rest_models::{AccountType, Direction};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rsa::pkcs8::EncodePublicKey;
+use rsa::rand_core::OsRng;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::task::JoinHandle;
+
+/// Account identifier of the fake account.
+const FAKE_ACCOUNT_ID: &str = "ABC123";
+/// API key accepted by the fake server.
+const FAKE_API_KEY: &str = "fake_api_key";
+/// Password accepted by the fake server.
+const FAKE_PASSWORD: &str = "fake_password";
+/// Username accepted by the fake server.
+const FAKE_USERNAME: &str = "fake_username";
+/// Historical price data points allowance per week.
+const HISTORICAL_DATA_ALLOWANCE: u32 = 10_000;
+
+/// In-process fake of the IG REST API for offline testing. It keeps the state of a single demo
+/// account, so deals opened through it produce confirms, positions, activity and transactions.
+///
+/// Served endpoints: /session (v1, v2 and v3, and PUT to switch accounts), /session/encryptionKey,
+/// /session/refresh-token, /accounts, /confirms, /history/activity (v1 and v3),
+/// /history/transactions (v1 and v2), /markets, /positions, /prices and /workingorders.
+/// The server stops when this instance is dropped.
+#[derive(Debug)]
+pub struct FakeIgServer {
+    /// Address the server listens on.
+    address: SocketAddr,
+    /// State of the fake account.
+    state: SharedState,
+    /// Task running the server.
+    task: JoinHandle<()>,
+}
+
+/// Implementation for the FakeIgServer struct.
+impl FakeIgServer {
+    /// Add another account to the fake client, which can then be switched to through
+    /// PUT /session. Deals are still booked to the default account.
+    pub fn add_account(&self, account_id: &str, account_type: AccountType) {
+        let mut state = lock(&self.state);
+        state.other_accounts.push(FakeAccount {
+            account_id: account_id.to_string(),
+            account_type,
+        });
+    }
+
+    /// Returns an API configuration pointing to this server through base_url_demo, with the
+    /// credentials it accepts. It uses session version 2 and auto_login.
+    pub fn api_config(&self) -> ApiConfig {
+        ApiConfig {
+            account_number_demo: FAKE_ACCOUNT_ID.to_string(),
+            api_key: FAKE_API_KEY.to_string(),
+            auto_login: Some(true),
+            base_url_demo: self.base_url(),
+            execution_environment: ExecutionEnvironment::Demo,
+            password: FAKE_PASSWORD.to_string(),
+            session_version: Some(2),
+            username: FAKE_USERNAME.to_string(),
+            ..ApiConfig::new()
+        }
+    }
+
+    /// Returns the base URL of the server.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Invalidate all the session tokens and access tokens issued so far, so the next request of
+    /// each client fails as expired. Refresh tokens remain valid.
+    pub fn expire_sessions(&self) {
+        let mut state = lock(&self.state);
+        state.access_tokens.clear();
+        state.client_tokens.clear();
+    }
+
+    /// Start a new fake server listening on a random local port.
+    pub async fn start() -> Result<Self, IgError> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| IgError::Other {
+                message: format!("Failed to start the fake IG server: {}", e),
+            })?;
+        let address = listener.local_addr().map_err(|e| IgError::Other {
+            message: format!("Failed to start the fake IG server: {}", e),
+        })?;
+
+        let state = Arc::new(Mutex::new(FakeState::new()));
+        let router = router(state.clone());
+        let task = tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+
+        Ok(Self {
+            address,
+            state,
+            task,
+        })
+    }
+}
+
+impl Drop for FakeIgServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// State of the fake account, shared by all the request handlers.
+type SharedState = Arc<Mutex<FakeState>>;
+
+/// State of the fake account.
+#[derive(Debug)]
+struct FakeState {
+    /// Valid OAuth access tokens.
+    access_tokens: HashSet<String>,
+    /// Account selected by the last login or account switch.
+    active_account_id: String,
+    /// Activity history, oldest first.
+    activities: Vec<Value>,
+    /// Valid session tokens, as CST to X-SECURITY-TOKEN.
+    client_tokens: HashMap<String, String>,
+    /// Deal confirmations by deal reference.
+    confirms: HashMap<String, Value>,
+    /// Key used to decrypt encrypted passwords, generated on first use.
+    encryption_key: Option<RsaPrivateKey>,
+    /// Remaining historical price data points.
+    historical_data_allowance: u32,
+    /// Markets that can be dealt.
+    markets: Vec<FakeMarket>,
+    /// Counter used to generate identifiers and tokens.
+    next_id: u64,
+    /// Accounts of the client other than the default one.
+    other_accounts: Vec<FakeAccount>,
+    /// Open positions.
+    positions: Vec<FakePosition>,
+    /// Valid OAuth refresh tokens.
+    refresh_tokens: HashSet<String>,
+    /// Transaction history, oldest first.
+    transactions: Vec<Value>,
+    /// Working orders.
+    working_orders: Vec<FakeWorkingOrder>,
+}
+
+/// Implementation for the FakeState struct.
+impl FakeState {
+    /// Find a market by epic.
+    fn market(&self, epic: &str) -> Option<&FakeMarket> {
+        self.markets.iter().find(|market| market.epic == epic)
+    }
+
+    /// Create the initial state, with no deals and a few markets.
+    fn new() -> Self {
+        Self {
+            access_tokens: HashSet::new(),
+            active_account_id: FAKE_ACCOUNT_ID.to_string(),
+            activities: Vec::new(),
+            client_tokens: HashMap::new(),
+            confirms: HashMap::new(),
+            encryption_key: None,
+            historical_data_allowance: HISTORICAL_DATA_ALLOWANCE,
+            markets: vec![
+                FakeMarket {
+                    bid: 18_000.0,
+                    epic: "IX.D.DAX.IFMM.IP",
+                    instrument_name: "Germany 40 Cash (1€)",
+                    instrument_type: "INDICES",
+                    offer: 18_001.2,
+                },
+                FakeMarket {
+                    bid: 7_650.0,
+                    epic: "IX.D.FTSE.DAILY.IP",
+                    instrument_name: "FTSE 100",
+                    instrument_type: "INDICES",
+                    offer: 7_651.0,
+                },
+                FakeMarket {
+                    bid: 10_850.0,
+                    epic: "CS.D.EURUSD.MINI.IP",
+                    instrument_name: "EUR/USD Mini",
+                    instrument_type: "CURRENCIES",
+                    offer: 10_850.6,
+                },
+            ],
+            next_id: 0,
+            other_accounts: Vec::new(),
+            positions: Vec::new(),
+            refresh_tokens: HashSet::new(),
+            transactions: Vec::new(),
+            working_orders: Vec::new(),
+        }
+    }
+
+    /// Returns a new deal identifier.
+    fn next_deal_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("DIAAAAFAKE{:08}", self.next_id)
+    }
+
+    /// Returns a new deal reference.
+    fn next_deal_reference(&mut self) -> String {
+        self.next_id += 1;
+        format!("FAKEREF{:08}", self.next_id)
+    }
+
+    /// Returns a new token with the given number of hexadecimal digits.
+    fn next_token(&mut self, digits: usize) -> String {
+        self.next_id += 1;
+        format!("{:0>width$x}", self.next_id, width = digits)
+    }
+
+    /// Record an accepted deal in the activity history.
+    fn record_activity(
+        &mut self,
+        deal_id: &str,
+        epic: &str,
+        activity_type: &str,
+        description: String,
+    ) {
+        self.activities.push(json!({
+            "channel": "PUBLIC_WEB_API",
+            "date": iso_date(Utc::now()),
+            "dealId": deal_id,
+            "description": description,
+            "details": null,
+            "epic": epic,
+            "period": "-",
+            "status": "ACCEPTED",
+            "type": activity_type,
+        }));
+    }
+}
+
+/// Account of the fake client other than the default one.
+#[derive(Debug)]
+struct FakeAccount {
+    account_id: String,
+    account_type: AccountType,
+}
+
+/// Market that can be dealt on the fake server.
+#[derive(Debug)]
+struct FakeMarket {
+    bid: f64,
+    epic: &'static str,
+    instrument_name: &'static str,
+    instrument_type: &'static str,
+    offer: f64,
+}
+
+/// Implementation for the FakeMarket struct.
+impl FakeMarket {
+    /// Returns the level at which a market order in the given direction is filled.
+    fn fill_level(&self, direction: &Direction) -> f64 {
+        match direction {
+            Direction::Buy => self.offer,
+            _ => self.bid,
+        }
+    }
+
+    /// Returns the market data included in positions and working orders.
+    fn market_data(&self) -> Value {
+        let now = Utc::now();
+        json!({
+            "bid": self.bid,
+            "delayTime": 0.0,
+            "epic": self.epic,
+            "expiry": "-",
+            "high": self.offer + 50.0,
+            "instrumentName": self.instrument_name,
+            "instrumentType": self.instrument_type,
+            "lotSize": 1.0,
+            "low": self.bid - 50.0,
+            "marketStatus": "TRADEABLE",
+            "netChange": 0.0,
+            "offer": self.offer,
+            "percentageChange": 0.0,
+            "scalingFactor": 1.0,
+            "streamingPricesAvailable": true,
+            "updateTime": now.format("%H:%M:%S").to_string(),
+            "updateTimeUTC": now.format("%H:%M:%S").to_string(),
+        })
+    }
+
+    /// Returns the market details served by the markets endpoints.
+    fn market_details(&self) -> Value {
+        let rule = |value: f64| json!({ "unit": "POINTS", "value": value });
+        json!({
+            "dealingRules": {
+                "controlledRiskSpacing": rule(10.0),
+                "marketOrderPreference": "AVAILABLE_DEFAULT_ON",
+                "maxStopOrLimitDistance": { "unit": "PERCENTAGE", "value": 75.0 },
+                "minControlledRiskStopDistance": rule(10.0),
+                "minDealSize": rule(0.5),
+                "minNormalStopOrLimitDistance": rule(2.0),
+                "minStepDistance": rule(1.0),
+                "trailingStopsPreference": "AVAILABLE",
+            },
+            "instrument": {
+                "chartCode": self.epic,
+                "contractSize": "1",
+                "controlledRiskAllowed": true,
+                "country": null,
+                "currencies": [{
+                    "baseExchangeRate": 1.0,
+                    "code": "EUR",
+                    "exchangeRate": 1.0,
+                    "isDefault": true,
+                    "symbol": "E",
+                }],
+                "epic": self.epic,
+                "expiry": "-",
+                "expiryDetails": null,
+                "forceOpenAllowed": true,
+                "limitedRiskPremium": rule(1.0),
+                "lotSize": 1.0,
+                "marginDepositBands": [{
+                    "currency": "EUR",
+                    "margin": 5.0,
+                    "max": null,
+                    "min": 0.0,
+                }],
+                "marginFactor": 5.0,
+                "marginFactorUnit": "PERCENTAGE",
+                "marketId": self.epic,
+                "name": self.instrument_name,
+                "newsCode": "",
+                "onePipMeans": "1 Index Point",
+                "openingHours": null,
+                "rolloverDetails": null,
+                "slippageFactor": { "unit": "pct", "value": 50.0 },
+                "specialInfo": [],
+                "sprintMarketsMaximumExpiryTime": null,
+                "sprintMarketsMinimumExpiryTime": null,
+                "stopsLimitsAllowed": true,
+                "streamingPricesAvailable": true,
+                "type": self.instrument_type,
+                "unit": "CONTRACTS",
+                "valueOfOnePip": "1.00",
+            },
+            "snapshot": {
+                "bid": self.bid,
+                "binaryOdds": null,
+                "controlledRiskExtraSpread": 1.0,
+                "decimalPlacesFactor": 1.0,
+                "delayTime": 0.0,
+                "high": self.offer + 50.0,
+                "low": self.bid - 50.0,
+                "marketStatus": "TRADEABLE",
+                "netChange": 0.0,
+                "offer": self.offer,
+                "percentageChange": 0.0,
+                "scalingFactor": 1.0,
+                "updateTime": Utc::now().format("%H:%M:%S").to_string(),
+            },
+        })
+    }
+}
+
+/// Open position on the fake server.
+#[derive(Debug)]
+struct FakePosition {
+    created: DateTime<Utc>,
+    currency: String,
+    deal_id: String,
+    deal_reference: String,
+    direction: Direction,
+    epic: String,
+    level: f64,
+    limit_level: Option<f64>,
+    size: f64,
+    stop_level: Option<f64>,
+}
+
+/// Implementation for the FakePosition struct.
+impl FakePosition {
+    /// Returns the position as served by the positions endpoints.
+    fn to_json(&self, market: &FakeMarket) -> Value {
+        json!({
+            "market": market.market_data(),
+            "position": {
+                "contractSize": 1.0,
+                "controlledRisk": false,
+                "createdDate": ig_date(self.created),
+                "createdDateUTC": iso_date(self.created),
+                "currency": self.currency,
+                "dealId": self.deal_id,
+                "dealReference": self.deal_reference,
+                "direction": self.direction,
+                "level": self.level,
+                "limitLevel": self.limit_level,
+                "limitedRiskPremium": null,
+                "size": self.size,
+                "stopLevel": self.stop_level,
+                "trailingStep": null,
+                "trailingStopDistance": null,
+            },
+        })
+    }
+}
+
+/// Working order on the fake server.
+#[derive(Debug)]
+struct FakeWorkingOrder {
+    created: DateTime<Utc>,
+    deal_id: String,
+    request: WorkingOrderRequest,
+}
+
+/// Implementation for the FakeWorkingOrder struct.
+impl FakeWorkingOrder {
+    /// Returns the working order as served by the working orders endpoint.
+    fn to_json(&self, market: &FakeMarket) -> Value {
+        json!({
+            "marketData": market.market_data(),
+            "workingOrderData": {
+                "createdDate": ig_date(self.created),
+                "createdDateUTC": iso_date(self.created),
+                "currencyCode": self.request.currency_code,
+                "dealId": self.deal_id,
+                "direction": self.request.direction,
+                "dma": false,
+                "epic": self.request.epic,
+                "goodTillDate": self.request.good_till_date,
+                "goodTillDateISO": self.request.good_till_date,
+                "guaranteedStop": self.request.guaranteed_stop.unwrap_or(false),
+                "limitDistance": self.request.limit_distance,
+                "limitedRiskPremium": null,
+                "orderLevel": self.request.level,
+                "orderSize": self.request.size,
+                "orderType": self.request.r#type,
+                "stopDistance": self.request.stop_distance,
+                "timeInForce": self.request.time_in_force,
+            },
+        })
+    }
+}
+
+/// Error response of the fake server.
+#[derive(Debug)]
+struct FakeError {
+    error_code: &'static str,
+    status: StatusCode,
+}
+
+impl IntoResponse for FakeError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "errorCode": self.error_code }))).into_response()
+    }
+}
+
+/// Body of an account switch request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountSwitchRequest {
+    account_id: String,
+}
+
+/// Body of a login request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginRequest {
+    #[serde(default)]
+    encrypted_password: bool,
+    identifier: String,
+    password: String,
+}
+
+/// Body of a request to close a position.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionCloseRequest {
+    deal_id: Option<String>,
+    #[serde(deserialize_with = "known_direction")]
+    direction: Direction,
+    epic: Option<String>,
+    size: f64,
+}
+
+/// Body of a request to open a position.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionOpenRequest {
+    currency_code: String,
+    deal_reference: Option<String>,
+    #[serde(deserialize_with = "known_direction")]
+    direction: Direction,
+    epic: String,
+    limit_distance: Option<f64>,
+    limit_level: Option<f64>,
+    size: f64,
+    stop_distance: Option<f64>,
+    stop_level: Option<f64>,
+}
+
+/// Body of a request to update a position.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionUpdateRequest {
+    limit_level: Option<f64>,
+    stop_level: Option<f64>,
+}
+
+/// Body of a request to create or update a working order.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkingOrderRequest {
+    #[serde(default)]
+    currency_code: String,
+    deal_reference: Option<String>,
+    #[serde(default, deserialize_with = "known_direction")]
+    direction: Direction,
+    #[serde(default)]
+    epic: String,
+    good_till_date: Option<String>,
+    guaranteed_stop: Option<bool>,
+    level: f64,
+    limit_distance: Option<f64>,
+    #[serde(default)]
+    size: f64,
+    stop_distance: Option<f64>,
+    time_in_force: String,
+    r#type: String,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// ROUTING AND HELPER FUNCTIONS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Check the API key and the session tokens of a request.
+fn authorize(state: &FakeState, headers: &HeaderMap) -> Result<(), FakeError> {
+    if header(headers, "X-IG-API-KEY") != Some(FAKE_API_KEY) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "error.security.api-key-invalid",
+        ));
+    }
+
+    if let Some(authorization) = header(headers, "Authorization") {
+        return match authorization.strip_prefix("Bearer ") {
+            Some(access_token) if state.access_tokens.contains(access_token) => Ok(()),
+            _ => Err(error_response(
+                StatusCode::UNAUTHORIZED,
+                "error.security.oauth-token-invalid",
+            )),
+        };
+    }
+
+    match (header(headers, "cst"), header(headers, "x-security-token")) {
+        (Some(cst), Some(security_token))
+            if state.client_tokens.get(cst).map(String::as_str) == Some(security_token) =>
+        {
+            Ok(())
+        }
+        _ => Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "error.security.client-token-invalid",
+        )),
+    }
+}
+
+/// Returns the base of a deal confirmation, accepted and with no affected deals.
+fn confirm(deal_reference: &str, deal_id: &str, epic: &str, direction: &Direction) -> Value {
+    json!({
+        "affectedDeals": [],
+        "date": iso_date(Utc::now()),
+        "dealId": deal_id,
+        "dealReference": deal_reference,
+        "dealStatus": "ACCEPTED",
+        "direction": direction,
+        "epic": epic,
+        "expiry": "-",
+        "guaranteedStop": false,
+        "level": null,
+        "limitDistance": null,
+        "limitLevel": null,
+        "profit": null,
+        "profitCurrency": null,
+        "reason": "SUCCESS",
+        "size": null,
+        "status": null,
+        "stopDistance": null,
+        "stopLevel": null,
+        "trailingStop": false,
+    })
+}
+
+/// Returns the symbol IG sends for a currency in the transaction history, or the ISO code of
+/// the currencies without one.
+fn currency_symbol(currency: &str) -> &str {
+    match currency {
+        "EUR" => "E",
+        "GBP" => "£",
+        "USD" => "$",
+        _ => currency,
+    }
+}
+
+/// Returns an error response with the given IG error code.
+fn error_response(status: StatusCode, error_code: &'static str) -> FakeError {
+    FakeError { error_code, status }
+}
+
+/// Returns the value of a header, if it is present and valid text.
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Format a date the way IG formats local dates.
+fn ig_date(date: DateTime<Utc>) -> String {
+    date.format("%Y/%m/%d %H:%M:%S:%3f").to_string()
+}
+
+/// Check whether a request is a DELETE request sent as a POST request.
+fn is_delete(headers: &HeaderMap) -> bool {
+    header(headers, "_method").is_some_and(|method| method.eq_ignore_ascii_case("DELETE"))
+}
+
+/// Format a date the way IG formats UTC dates.
+fn iso_date(date: DateTime<Utc>) -> String {
+    date.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+/// Deserialize a deal direction, rejecting the values unknown to the fake server.
+fn known_direction<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Direction, D::Error> {
+    match Direction::deserialize(deserializer)? {
+        Direction::Other(direction) => Err(serde::de::Error::custom(format!(
+            "unknown direction: {}",
+            direction
+        ))),
+        direction => Ok(direction),
+    }
+}
+
+/// Lock the state. A poisoned lock is recovered, as the state is always left consistent.
+fn lock(state: &SharedState) -> MutexGuard<'_, FakeState> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Deserialize a JSON request body.
+fn parse_body<T: DeserializeOwned>(body: &Bytes) -> Result<T, FakeError> {
+    serde_json::from_slice(body)
+        .map_err(|_| error_response(StatusCode::BAD_REQUEST, "validation.invalid.request"))
+}
+
+/// Parse the period of a version 1 history request: either the milliseconds of the last period,
+/// or the first and last days formatted as dd-MM-yyyy.
+fn parse_period(period: &str) -> Result<(NaiveDateTime, Option<NaiveDateTime>), FakeError> {
+    let invalid = || error_response(StatusCode::BAD_REQUEST, "validation.invalid.request");
+
+    match period.split_once('/') {
+        Some((from, to)) => {
+            let from = NaiveDate::parse_from_str(from, "%d-%m-%Y").map_err(|_| invalid())?;
+            let to = NaiveDate::parse_from_str(to, "%d-%m-%Y").map_err(|_| invalid())?;
+            Ok((
+                from.and_time(NaiveTime::MIN),
+                Some(to.and_time(NaiveTime::MIN) + Duration::days(1)),
+            ))
+        }
+        None => {
+            let millis: i64 = period.parse().map_err(|_| invalid())?;
+            Ok((
+                (Utc::now() - Duration::milliseconds(millis)).naive_utc(),
+                None,
+            ))
+        }
+    }
+}
+
+/// Parse a date query parameter.
+fn parse_date(params: &HashMap<String, String>, name: &str) -> Option<NaiveDateTime> {
+    params
+        .get(name)
+        .and_then(|value| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok())
+}
+
+/// Returns the length of a candle of the given prices resolution.
+fn resolution_duration(resolution: Option<&str>) -> Duration {
+    match resolution.unwrap_or("MINUTE") {
+        "SECOND" => Duration::seconds(1),
+        "MINUTE_2" => Duration::minutes(2),
+        "MINUTE_3" => Duration::minutes(3),
+        "MINUTE_5" => Duration::minutes(5),
+        "MINUTE_10" => Duration::minutes(10),
+        "MINUTE_15" => Duration::minutes(15),
+        "MINUTE_30" => Duration::minutes(30),
+        "HOUR" => Duration::hours(1),
+        "HOUR_2" => Duration::hours(2),
+        "HOUR_3" => Duration::hours(3),
+        "HOUR_4" => Duration::hours(4),
+        "DAY" => Duration::days(1),
+        "WEEK" => Duration::weeks(1),
+        "MONTH" => Duration::days(30),
+        _ => Duration::minutes(1),
+    }
+}
+
+/// Build the router serving all the fake endpoints.
+fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/accounts", get(accounts_get))
+        .route("/confirms/{deal_reference}", get(confirms_get))
+        .route("/history/activity", get(history_activity_get))
+        .route("/history/activity/{*period}", get(history_activity_v1_get))
+        .route("/history/transactions", get(history_transactions_get))
+        .route(
+            "/history/transactions/{transaction_type}/{*period}",
+            get(history_transactions_v1_get),
+        )
+        .route("/markets", get(markets_get))
+        .route("/markets/{epic}", get(market_get))
+        .route("/positions", get(positions_get))
+        .route("/positions/{deal_id}", get(position_get))
+        .route("/positions/otc", post(positions_otc_post))
+        .route("/positions/otc/{deal_id}", put(position_otc_put))
+        .route("/prices/{epic}", get(prices_get))
+        .route(
+            "/session",
+            get(session_get).post(session_post).put(session_put),
+        )
+        .route("/session/encryptionKey", get(session_encryption_key_get))
+        .route("/session/refresh-token", post(session_refresh_token_post))
+        .route("/workingorders", get(workingorders_get))
+        .route("/workingorders/otc", post(workingorders_otc_post))
+        .route(
+            "/workingorders/otc/{deal_id}",
+            put(workingorder_otc_put).post(workingorder_otc_post),
+        )
+        .fallback(|| async { error_response(StatusCode::NOT_FOUND, "error.not-found") })
+        .with_state(state)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// ACCOUNT, CONFIRMS AND HISTORY HANDLERS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+async fn accounts_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let profit_and_loss: f64 = state
+        .positions
+        .iter()
+        .filter_map(|position| {
+            let market = state.market(&position.epic)?;
+            let close_direction = match position.direction {
+                Direction::Buy => Direction::Sell,
+                _ => Direction::Buy,
+            };
+            Some(profit(
+                position,
+                market.fill_level(&close_direction),
+                position.size,
+            ))
+        })
+        .sum();
+    let balance: f64 = 10_000.0
+        + state
+            .transactions
+            .iter()
+            .filter_map(|transaction| transaction["profitAndLoss"].as_str())
+            .filter_map(|profit| {
+                profit
+                    .trim_start_matches(|c: char| !c.is_ascii_digit() && c != '-')
+                    .parse::<f64>()
+                    .ok()
+            })
+            .sum::<f64>();
+
+    let mut accounts = vec![json!({
+        "accountAlias": null,
+        "accountId": FAKE_ACCOUNT_ID,
+        "accountName": "Demo-CFD",
+        "accountType": "CFD",
+        "balance": {
+            "available": balance + profit_and_loss,
+            "balance": balance,
+            "deposit": 0.0,
+            "profitLoss": profit_and_loss,
+        },
+        "canTransferFrom": true,
+        "canTransferTo": true,
+        "currency": "EUR",
+        "preferred": true,
+        "status": "ENABLED",
+    })];
+    accounts.extend(state.other_accounts.iter().map(|account| {
+        json!({
+            "accountAlias": null,
+            "accountId": account.account_id,
+            "accountName": account.account_id,
+            "accountType": account.account_type,
+            "balance": { "available": 0.0, "balance": 0.0, "deposit": 0.0, "profitLoss": 0.0 },
+            "canTransferFrom": true,
+            "canTransferTo": true,
+            "currency": "EUR",
+            "preferred": false,
+            "status": "ENABLED",
+        })
+    }));
+
+    Ok(Json(json!({ "accounts": accounts })))
+}
+
+async fn confirms_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(deal_reference): Path<String>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    match state.confirms.get(&deal_reference) {
+        Some(confirm) => Ok(Json(confirm.clone())),
+        None => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "error.confirms.deal-not-found",
+        )),
+    }
+}
+
+async fn history_activity_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let activities: Vec<&Value> = in_date_range(&state.activities, &params).collect();
+
+    Ok(Json(json!({
+        "activities": activities,
+        "metadata": { "paging": { "next": null, "size": activities.len() } },
+    })))
+}
+
+async fn history_activity_v1_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(period): Path<String>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let (from, to) = parse_period(&period)?;
+    let activities: Vec<Value> = in_period(&state.activities, Some(from), to)
+        .map(|activity| activity_v1(&state, activity))
+        .collect();
+
+    Ok(Json(json!({ "activities": activities })))
+}
+
+/// Convert an activity to its version 1 shape.
+fn activity_v1(state: &FakeState, activity: &Value) -> Value {
+    let epic = activity["epic"].as_str().unwrap_or_default();
+    let market_name = state
+        .market(epic)
+        .map_or(epic, |market| market.instrument_name);
+    let date = activity["date"]
+        .as_str()
+        .and_then(|date| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S").ok())
+        .unwrap_or_default();
+
+    json!({
+        "actionStatus": "ACCEPT",
+        "activity": activity["type"],
+        "activityHistoryId": null,
+        "channel": activity["channel"],
+        "currency": "EUR",
+        "date": date.format("%d/%m/%y").to_string(),
+        "dealId": activity["dealId"],
+        "epic": epic,
+        "level": "-",
+        "limit": null,
+        "marketName": market_name,
+        "period": activity["period"],
+        "result": activity["description"],
+        "size": "-",
+        "stop": null,
+        "stopType": null,
+        "time": date.format("%H:%M").to_string(),
+    })
+}
+
+async fn history_transactions_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let transactions: Vec<&Value> = in_date_range(&state.transactions, &params).collect();
+
+    Ok(Json(json!({
+        "metadata": {
+            "pageData": { "pageNumber": 1, "pageSize": transactions.len(), "totalPages": 1 },
+            "size": transactions.len(),
+        },
+        "transactions": transactions,
+    })))
+}
+
+async fn history_transactions_v1_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((transaction_type, period)): Path<(String, String)>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let (from, to) = parse_period(&period)?;
+    let transactions: Vec<Value> = in_period(&state.transactions, Some(from), to)
+        .filter(|transaction| match transaction_type.as_str() {
+            "ALL" => true,
+            "ALL_DEAL" => transaction["transactionType"] == "DEAL",
+            other => transaction["transactionType"] == other,
+        })
+        .map(|transaction| {
+            let mut transaction = transaction.clone();
+            if let Some(transaction) = transaction.as_object_mut() {
+                transaction.remove("dateUtc");
+                transaction.remove("openDateUtc");
+            }
+            transaction
+        })
+        .collect();
+
+    Ok(Json(json!({ "transactions": transactions })))
+}
+
+/// Filter history items by the from and to query parameters.
+fn in_date_range<'a>(
+    items: &'a [Value],
+    params: &HashMap<String, String>,
+) -> impl Iterator<Item = &'a Value> {
+    in_period(items, parse_date(params, "from"), parse_date(params, "to"))
+}
+
+/// Filter history items by date.
+fn in_period(
+    items: &[Value],
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+) -> impl Iterator<Item = &Value> {
+    items.iter().filter(move |item| {
+        let date = item["date"]
+            .as_str()
+            .or_else(|| item["dateUtc"].as_str())
+            .and_then(|date| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S").ok());
+        match date {
+            Some(date) => from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to),
+            None => true,
+        }
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// MARKETS AND PRICES HANDLERS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+async fn market_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(epic): Path<String>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    match state.market(&epic) {
+        Some(market) => Ok(Json(market.market_details())),
+        None => Err(error_response(
+            StatusCode::NOT_FOUND,
+            "error.service.marketdata.instrument.epic.unavailable",
+        )),
+    }
+}
+
+async fn markets_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let market_details: Vec<Value> = params
+        .get("epics")
+        .map(|epics| {
+            epics
+                .split(',')
+                .filter_map(|epic| state.market(epic))
+                .collect()
+        })
+        .unwrap_or_else(Vec::new)
+        .into_iter()
+        .map(FakeMarket::market_details)
+        .collect();
+
+    Ok(Json(json!({ "marketDetails": market_details })))
+}
+
+async fn prices_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(epic): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let (bid, offer, instrument_type) = match state.market(&epic) {
+        Some(market) => (market.bid, market.offer, market.instrument_type),
+        None => {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                "error.service.marketdata.instrument.epic.unavailable",
+            ))
+        }
+    };
+
+    let resolution = resolution_duration(params.get("resolution").map(String::as_str));
+    let data_points = params
+        .get("max")
+        .and_then(|max| max.parse::<u32>().ok())
+        .unwrap_or(10);
+    if data_points > state.historical_data_allowance {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "error.public-api.exceeded-account-historical-data-allowance",
+        ));
+    }
+    state.historical_data_allowance -= data_points;
+
+    // Generate candles oscillating around the current prices, oldest first.
+    let end = parse_date(&params, "to")
+        .map(|to| to.and_utc())
+        .unwrap_or_else(Utc::now);
+    let prices: Vec<Value> = (0..data_points)
+        .map(|index| {
+            let snapshot_time = end - resolution * (data_points - index) as i32;
+            let offset = ((index % 5) as f64 - 2.0) * 0.5;
+            let price = |delta: f64| {
+                json!({ "ask": offer + offset + delta, "bid": bid + offset + delta, "lastTraded": null })
+            };
+            json!({
+                "closePrice": price(0.2),
+                "highPrice": price(1.0),
+                "lastTradedVolume": 100.0,
+                "lowPrice": price(-1.0),
+                "openPrice": price(-0.2),
+                "snapshotTime": snapshot_time.format("%Y/%m/%d %H:%M:%S").to_string(),
+                "snapshotTimeUTC": iso_date(snapshot_time),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "instrumentType": instrument_type,
+        "metadata": {
+            "allowance": {
+                "allowanceExpiry": 604_800,
+                "remainingAllowance": state.historical_data_allowance,
+                "totalAllowance": HISTORICAL_DATA_ALLOWANCE,
+            },
+            "pageData": { "pageNumber": 1, "pageSize": data_points, "totalPages": 1 },
+            "size": data_points,
+        },
+        "prices": prices,
+    })))
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// POSITIONS HANDLERS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+async fn position_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(deal_id): Path<String>,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    state
+        .positions
+        .iter()
+        .find(|position| position.deal_id == deal_id)
+        .and_then(|position| Some(position.to_json(state.market(&position.epic)?)))
+        .map(Json)
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "error.position.notfound"))
+}
+
+async fn position_otc_put(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(deal_id): Path<String>,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+    let request: PositionUpdateRequest = parse_body(&body)?;
+
+    let deal_reference = state.next_deal_reference();
+    let position = match state
+        .positions
+        .iter_mut()
+        .find(|position| position.deal_id == deal_id)
+    {
+        Some(position) => position,
+        None => {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                "error.position.notfound",
+            ))
+        }
+    };
+    position.limit_level = request.limit_level;
+    position.stop_level = request.stop_level;
+
+    let mut confirm = confirm(
+        &deal_reference,
+        &deal_id,
+        &position.epic,
+        &position.direction,
+    );
+    confirm["affectedDeals"] = json!([{ "dealId": deal_id, "status": "AMENDED" }]);
+    confirm["level"] = json!(position.level);
+    confirm["limitLevel"] = json!(position.limit_level);
+    confirm["size"] = json!(position.size);
+    confirm["status"] = json!("AMENDED");
+    confirm["stopLevel"] = json!(position.stop_level);
+    let epic = position.epic.clone();
+
+    state.record_activity(
+        &deal_id,
+        &epic,
+        "EDIT_STOP_AND_LIMIT",
+        "Position edited".to_string(),
+    );
+    state.confirms.insert(deal_reference.clone(), confirm);
+
+    Ok(Json(json!({ "dealReference": deal_reference })))
+}
+
+async fn positions_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let positions: Vec<Value> = state
+        .positions
+        .iter()
+        .filter_map(|position| Some(position.to_json(state.market(&position.epic)?)))
+        .collect();
+
+    Ok(Json(json!({ "positions": positions })))
+}
+
+/// Open a position, or close it if the request is a DELETE request sent as a POST request.
+async fn positions_otc_post(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let deal_reference = if is_delete(&headers) {
+        close_position(&mut state, parse_body(&body)?)
+    } else {
+        open_position(&mut state, parse_body(&body)?)
+    };
+
+    Ok(Json(json!({ "dealReference": deal_reference })))
+}
+
+/// Close a position, fully or partially, at the current market level, and return the deal
+/// reference of its confirmation.
+fn close_position(state: &mut FakeState, request: PositionCloseRequest) -> String {
+    let deal_reference = state.next_deal_reference();
+    let index = state
+        .positions
+        .iter()
+        .position(|position| match &request.deal_id {
+            Some(deal_id) => &position.deal_id == deal_id,
+            None => {
+                request.epic.as_deref() == Some(position.epic.as_str())
+                    && position.direction != request.direction
+            }
+        });
+
+    let (index, level, instrument_name) = match index.and_then(|index| {
+        let position = &state.positions[index];
+        let market = state.market(&position.epic)?;
+        Some((
+            index,
+            market.fill_level(&request.direction),
+            market.instrument_name,
+        ))
+    }) {
+        Some(found) => found,
+        None => {
+            let deal_id = request.deal_id.clone().unwrap_or_default();
+            let epic = request.epic.clone().unwrap_or_default();
+            let mut confirm = confirm(&deal_reference, &deal_id, &epic, &request.direction);
+            confirm["dealStatus"] = json!("REJECTED");
+            confirm["reason"] = json!("POSITION_NOT_AVAILABLE_TO_CLOSE");
+            state.confirms.insert(deal_reference.clone(), confirm);
+            return deal_reference;
+        }
+    };
+
+    let size = request.size.min(state.positions[index].size);
+    let fully_closed = size >= state.positions[index].size;
+    let position = &mut state.positions[index];
+    let profit = profit(position, level, size);
+    position.size -= size;
+
+    let status = if fully_closed {
+        "FULLY_CLOSED"
+    } else {
+        "PARTIALLY_CLOSED"
+    };
+    let mut confirm = confirm(
+        &deal_reference,
+        &position.deal_id,
+        &position.epic,
+        &request.direction,
+    );
+    confirm["affectedDeals"] = json!([{ "dealId": position.deal_id, "status": status }]);
+    confirm["level"] = json!(level);
+    confirm["profit"] = json!(profit);
+    confirm["profitCurrency"] = json!(position.currency);
+    confirm["size"] = json!(size);
+    confirm["status"] = json!(if fully_closed {
+        "CLOSED"
+    } else {
+        "PARTIALLY_CLOSED"
+    });
+
+    let now = Utc::now();
+    let signed_size = match position.direction {
+        Direction::Buy => format!("+{}", size),
+        _ => format!("-{}", size),
+    };
+    let symbol = currency_symbol(&position.currency);
+    let transaction = json!({
+        "cashTransaction": false,
+        "closeLevel": level.to_string(),
+        // IG sends the currency symbol in the transaction history.
+        "currency": symbol,
+        "date": iso_date(now),
+        "dateUtc": iso_date(now),
+        "instrumentName": instrument_name,
+        "openDateUtc": iso_date(position.created),
+        "openLevel": position.level.to_string(),
+        "period": "-",
+        "profitAndLoss": format!("{}{:.2}", symbol, profit),
+        "reference": position.deal_reference,
+        "size": signed_size,
+        "transactionType": "DEAL",
+    });
+    let deal_id = position.deal_id.clone();
+    let epic = position.epic.clone();
+    let description = format!(
+        "Position {}: {}",
+        status.to_lowercase().replace('_', " "),
+        deal_id
+    );
+
+    if fully_closed {
+        state.positions.remove(index);
+    }
+    state.transactions.push(transaction);
+    state.record_activity(&deal_id, &epic, "POSITION", description);
+    state.confirms.insert(deal_reference.clone(), confirm);
+
+    deal_reference
+}
+
+/// Open a position at the current market level and return the deal reference of its
+/// confirmation.
+fn open_position(state: &mut FakeState, request: PositionOpenRequest) -> String {
+    let deal_reference = match &request.deal_reference {
+        Some(deal_reference) => deal_reference.clone(),
+        None => state.next_deal_reference(),
+    };
+    let deal_id = state.next_deal_id();
+
+    let level = match state.market(&request.epic) {
+        Some(market) => market.fill_level(&request.direction),
+        None => {
+            let mut confirm = confirm(&deal_reference, &deal_id, &request.epic, &request.direction);
+            confirm["dealStatus"] = json!("REJECTED");
+            confirm["reason"] = json!("INSTRUMENT_NOT_FOUND");
+            state.confirms.insert(deal_reference.clone(), confirm);
+            return deal_reference;
+        }
+    };
+
+    // Distances are converted to levels, as IG does.
+    let sign = match request.direction {
+        Direction::Buy => 1.0,
+        _ => -1.0,
+    };
+    let limit_level = request.limit_level.or(request
+        .limit_distance
+        .map(|distance| level + sign * distance));
+    let stop_level = request.stop_level.or(request
+        .stop_distance
+        .map(|distance| level - sign * distance));
+
+    let mut confirm = confirm(&deal_reference, &deal_id, &request.epic, &request.direction);
+    confirm["affectedDeals"] = json!([{ "dealId": deal_id, "status": "OPENED" }]);
+    confirm["level"] = json!(level);
+    confirm["limitDistance"] = json!(request.limit_distance);
+    confirm["limitLevel"] = json!(limit_level);
+    confirm["size"] = json!(request.size);
+    confirm["status"] = json!("OPEN");
+    confirm["stopDistance"] = json!(request.stop_distance);
+    confirm["stopLevel"] = json!(stop_level);
+
+    state.record_activity(
+        &deal_id,
+        &request.epic,
+        "POSITION",
+        format!("Position opened: {}", deal_id),
+    );
+    state.confirms.insert(deal_reference.clone(), confirm);
+    state.positions.push(FakePosition {
+        created: Utc::now(),
+        currency: request.currency_code,
+        deal_id,
+        deal_reference: deal_reference.clone(),
+        direction: request.direction,
+        epic: request.epic,
+        level,
+        limit_level,
+        size: request.size,
+        stop_level,
+    });
+
+    deal_reference
+}
+
+/// Returns the profit of closing part of a position at the given level.
+fn profit(position: &FakePosition, level: f64, size: f64) -> f64 {
+    match position.direction {
+        Direction::Buy => (level - position.level) * size,
+        _ => (position.level - level) * size,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// SESSION HANDLERS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+async fn session_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    // Session version 3 selects the account of each request through the IG-ACCOUNT-ID header.
+    let account_id = header(&headers, "IG-ACCOUNT-ID").unwrap_or(&state.active_account_id);
+
+    Ok(Json(json!({
+        "accountId": account_id,
+        "clientId": "100000001",
+        "currency": "EUR",
+        "lightstreamerEndpoint": "https://demo-apd.marketdatasystems.com",
+        "locale": "en_GB",
+        "timezoneOffset": 1.0,
+    })))
+}
+
+/// Log in, or log out if the request is a DELETE request sent as a POST request.
+async fn session_post(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, FakeError> {
+    let mut state = lock(&state);
+
+    if is_delete(&headers) {
+        authorize(&state, &headers)?;
+        if let Some(cst) = header(&headers, "cst") {
+            state.client_tokens.remove(cst);
+        }
+        if let Some(access_token) =
+            header(&headers, "Authorization").and_then(|value| value.strip_prefix("Bearer "))
+        {
+            state.access_tokens.remove(access_token);
+        }
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+
+    if header(&headers, "X-IG-API-KEY") != Some(FAKE_API_KEY) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "error.security.api-key-invalid",
+        ));
+    }
+    let request: LoginRequest = parse_body(&body)?;
+    let password = match request.encrypted_password {
+        true => decrypt_password(&state, &request.password),
+        false => Some(request.password),
+    };
+    if request.identifier != FAKE_USERNAME || password.as_deref() != Some(FAKE_PASSWORD) {
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "error.security.invalid-details",
+        ));
+    }
+
+    // Logging in selects the default account.
+    state.active_account_id = FAKE_ACCOUNT_ID.to_string();
+
+    let version = header(&headers, "Version").unwrap_or("1");
+    match version {
+        "3" => {
+            let oauth_token = issue_oauth_token(&mut state);
+            Ok(Json(json!({
+                "accountId": FAKE_ACCOUNT_ID,
+                "clientId": "100000001",
+                "lightstreamerEndpoint": "https://demo-apd.marketdatasystems.com",
+                "oauthToken": oauth_token,
+                "timezoneOffset": 1.0,
+            }))
+            .into_response())
+        }
+        _ => {
+            let cst = state.next_token(69);
+            let security_token = state.next_token(69);
+            state
+                .client_tokens
+                .insert(cst.clone(), security_token.clone());
+
+            let mut body = json!({
+                "accountInfo": {
+                    "available": 10_000.0,
+                    "balance": 10_000.0,
+                    "deposit": 0.0,
+                    "profitLoss": 0.0,
+                },
+                "accountType": "CFD",
+                "accounts": session_accounts(&state),
+                "clientId": "100000001",
+                "currencyIsoCode": "EUR",
+                "currencySymbol": "E",
+                "currentAccountId": FAKE_ACCOUNT_ID,
+                "dealingEnabled": true,
+                "hasActiveDemoAccounts": true,
+                "hasActiveLiveAccounts": false,
+                "lightstreamerEndpoint": "https://demo-apd.marketdatasystems.com",
+                "timezoneOffset": 1.0,
+                "trailingStopsEnabled": false,
+            });
+            // Only version 1 returns the client type.
+            if version == "1" {
+                body["clientType"] = json!("RETAIL");
+            }
+            let tokens = [("CST", cst), ("X-SECURITY-TOKEN", security_token)];
+            Ok((tokens, Json(body)).into_response())
+        }
+    }
+}
+
+/// Switch the active account of the CST sessions.
+async fn session_put(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let request: AccountSwitchRequest = parse_body(&body)?;
+    if !session_accounts(&state)
+        .iter()
+        .any(|account| account["accountId"] == request.account_id.as_str())
+    {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "error.switch.accountId-invalid",
+        ));
+    }
+    if request.account_id == state.active_account_id {
+        return Err(error_response(
+            StatusCode::PRECONDITION_FAILED,
+            "error.switch.accountId-must-be-different",
+        ));
+    }
+    state.active_account_id = request.account_id;
+
+    Ok(Json(json!({
+        "dealingEnabled": true,
+        "hasActiveDemoAccounts": true,
+        "hasActiveLiveAccounts": false,
+        "trailingStopsEnabled": false,
+    })))
+}
+
+/// Returns the accounts of the client as listed in the response to a login request.
+fn session_accounts(state: &FakeState) -> Vec<Value> {
+    let mut accounts = vec![json!({
+        "accountId": FAKE_ACCOUNT_ID,
+        "accountName": "Demo-CFD",
+        "accountType": "CFD",
+        "preferred": true,
+    })];
+    accounts.extend(state.other_accounts.iter().map(|account| {
+        json!({
+            "accountId": account.account_id,
+            "accountName": account.account_id,
+            "accountType": account.account_type,
+            "preferred": false,
+        })
+    }));
+
+    accounts
+}
+
+/// Decrypt a password encrypted with the key returned by GET /session/encryptionKey, dropping
+/// the timestamp appended to it.
+fn decrypt_password(state: &FakeState, password: &str) -> Option<String> {
+    let encrypted = BASE64_STANDARD.decode(password).ok()?;
+    let decrypted = state
+        .encryption_key
+        .as_ref()?
+        .decrypt(Pkcs1v15Encrypt, &encrypted)
+        .ok()?;
+    let plain_text = String::from_utf8(BASE64_STANDARD.decode(decrypted).ok()?).ok()?;
+
+    plain_text
+        .rsplit_once('|')
+        .map(|(password, _)| password.to_string())
+}
+
+async fn session_encryption_key_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+
+    if header(&headers, "X-IG-API-KEY") != Some(FAKE_API_KEY) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "error.security.api-key-invalid",
+        ));
+    }
+
+    let internal_error = || error_response(StatusCode::INTERNAL_SERVER_ERROR, "error.unexpected");
+    let private_key = match state.encryption_key.take() {
+        Some(private_key) => private_key,
+        None => RsaPrivateKey::new(&mut OsRng, 1024).map_err(|_| internal_error())?,
+    };
+    let public_key_der = private_key
+        .to_public_key()
+        .to_public_key_der()
+        .map_err(|_| internal_error())?;
+    state.encryption_key = Some(private_key);
+
+    Ok(Json(json!({
+        "encryptionKey": BASE64_STANDARD.encode(public_key_der.as_bytes()),
+        "timeStamp": Utc::now().timestamp_millis(),
+    })))
+}
+
+async fn session_refresh_token_post(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+
+    if header(&headers, "X-IG-API-KEY") != Some(FAKE_API_KEY) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "error.security.api-key-invalid",
+        ));
+    }
+    let request: Value = parse_body(&body)?;
+    let refresh_token = request["refresh_token"].as_str().unwrap_or_default();
+    if !state.refresh_tokens.remove(refresh_token) {
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "error.security.oauth-token-invalid",
+        ));
+    }
+
+    Ok(Json(issue_oauth_token(&mut state)))
+}
+
+/// Issue a new OAuth access token and refresh token.
+fn issue_oauth_token(state: &mut FakeState) -> Value {
+    let access_token = format!(
+        "{}-{}-{}-{}-{}",
+        state.next_token(8),
+        state.next_token(4),
+        state.next_token(4),
+        state.next_token(4),
+        state.next_token(12)
+    );
+    let refresh_token = state.next_token(32);
+    state.access_tokens.insert(access_token.clone());
+    state.refresh_tokens.insert(refresh_token.clone());
+
+    json!({
+        "access_token": access_token,
+        "expires_in": "60",
+        "refresh_token": refresh_token,
+        "scope": "profile",
+        "token_type": "Bearer",
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// WORKINGORDERS HANDLERS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Delete a working order, sent as a POST request with a DELETE _method header.
+async fn workingorder_otc_post(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(deal_id): Path<String>,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+
+    if !is_delete(&headers) {
+        return Err(error_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "error.method-not-allowed",
+        ));
+    }
+
+    let index = match state
+        .working_orders
+        .iter()
+        .position(|working_order| working_order.deal_id == deal_id)
+    {
+        Some(index) => index,
+        None => {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                "error.workingorder.notfound",
+            ))
+        }
+    };
+    let working_order = state.working_orders.remove(index);
+
+    let deal_reference = state.next_deal_reference();
+    let mut confirm = confirm(
+        &deal_reference,
+        &deal_id,
+        &working_order.request.epic,
+        &working_order.request.direction,
+    );
+    confirm["affectedDeals"] = json!([{ "dealId": deal_id, "status": "DELETED" }]);
+    confirm["level"] = json!(working_order.request.level);
+    confirm["size"] = json!(working_order.request.size);
+    confirm["status"] = json!("DELETED");
+
+    state.record_activity(
+        &deal_id,
+        &working_order.request.epic,
+        "WORKING_ORDER",
+        format!("Working order deleted: {}", deal_id),
+    );
+    state.confirms.insert(deal_reference.clone(), confirm);
+
+    Ok(Json(json!({ "dealReference": deal_reference })))
+}
+
+async fn workingorder_otc_put(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(deal_id): Path<String>,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+    let request: WorkingOrderRequest = parse_body(&body)?;
+
+    let deal_reference = state.next_deal_reference();
+    let working_order = match state
+        .working_orders
+        .iter_mut()
+        .find(|working_order| working_order.deal_id == deal_id)
+    {
+        Some(working_order) => working_order,
+        None => {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                "error.workingorder.notfound",
+            ))
+        }
+    };
+
+    // The market, direction and size of a working order can't be changed.
+    working_order.request.good_till_date = request.good_till_date;
+    working_order.request.guaranteed_stop = request
+        .guaranteed_stop
+        .or(working_order.request.guaranteed_stop);
+    working_order.request.level = request.level;
+    working_order.request.limit_distance = request.limit_distance;
+    working_order.request.stop_distance = request.stop_distance;
+    working_order.request.time_in_force = request.time_in_force;
+    working_order.request.r#type = request.r#type;
+
+    let mut confirm = confirm(
+        &deal_reference,
+        &deal_id,
+        &working_order.request.epic,
+        &working_order.request.direction,
+    );
+    confirm["affectedDeals"] = json!([{ "dealId": deal_id, "status": "AMENDED" }]);
+    confirm["level"] = json!(working_order.request.level);
+    confirm["size"] = json!(working_order.request.size);
+    confirm["status"] = json!("AMENDED");
+    let epic = working_order.request.epic.clone();
+
+    state.record_activity(
+        &deal_id,
+        &epic,
+        "WORKING_ORDER",
+        format!("Working order amended: {}", deal_id),
+    );
+    state.confirms.insert(deal_reference.clone(), confirm);
+
+    Ok(Json(json!({ "dealReference": deal_reference })))
+}
+
+async fn workingorders_get(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, FakeError> {
+    let state = lock(&state);
+    authorize(&state, &headers)?;
+
+    let working_orders: Vec<Value> = state
+        .working_orders
+        .iter()
+        .filter_map(|working_order| {
+            Some(working_order.to_json(state.market(&working_order.request.epic)?))
+        })
+        .collect();
+
+    Ok(Json(json!({ "workingOrders": working_orders })))
+}
+
+async fn workingorders_otc_post(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let mut state = lock(&state);
+    authorize(&state, &headers)?;
+    let request: WorkingOrderRequest = parse_body(&body)?;
+
+    let deal_reference = match &request.deal_reference {
+        Some(deal_reference) => deal_reference.clone(),
+        None => state.next_deal_reference(),
+    };
+    let deal_id = state.next_deal_id();
+
+    let mut confirm = confirm(&deal_reference, &deal_id, &request.epic, &request.direction);
+    if state.market(&request.epic).is_none() {
+        confirm["dealStatus"] = json!("REJECTED");
+        confirm["reason"] = json!("INSTRUMENT_NOT_FOUND");
+        state.confirms.insert(deal_reference.clone(), confirm);
+        return Ok(Json(json!({ "dealReference": deal_reference })));
+    }
+
+    confirm["affectedDeals"] = json!([{ "dealId": deal_id, "status": "OPENED" }]);
+    confirm["level"] = json!(request.level);
+    confirm["size"] = json!(request.size);
+    confirm["status"] = json!("OPEN");
+
+    state.record_activity(
+        &deal_id,
+        &request.epic,
+        "WORKING_ORDER",
+        format!("Working order created: {}", deal_id),
+    );
+    state.confirms.insert(deal_reference.clone(), confirm);
+    state.working_orders.push(FakeWorkingOrder {
+        created: Utc::now(),
+        deal_id,
+        request,
+    });
+
+    Ok(Json(json!({ "dealReference": deal_reference })))
+}
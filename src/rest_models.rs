@@ -1,11 +1,18 @@
 use crate::common::*;
 use crate::rest_regex::*;
-use chrono::{NaiveDateTime, Utc};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::rand_core::OsRng;
+use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
 use serde::de::DeserializeOwned;
 use serde::ser::SerializeStruct;
-use serde::{Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
-use std::error::Error;
+use std::any::type_name;
+use std::fmt;
+use std::str::FromStr;
+use tracing::trace;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 //
@@ -15,7 +22,7 @@ use std::error::Error;
 
 ///Trait to validate the fields of a request before sending it to the REST API.
 pub trait ValidateRequest: Serialize {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         Ok(())
     }
 }
@@ -23,128 +30,349 @@ pub trait ValidateRequest: Serialize {
 /// Trait to validate the fields of a response coming from the REST API.
 pub trait ValidateResponse: DeserializeOwned {
     /// Improved deserialization function that provides better error messages using serde_path_to_error.
-    fn deserialize<'de, T>(value: &'de Value) -> Result<T, Box<dyn Error>>
+    fn deserialize<'de, T>(value: &'de Value) -> Result<T, IgError>
     where
         T: Deserialize<'de>,
     {
-        let result = serde_path_to_error::deserialize(value);
-
-        match result {
-            Ok(value) => Ok(value),
-            Err(e) => Err(Box::new(ApiError {
-                message: format!("Failed to deserialize JSON serde_json::Value: {}", e),
-            })),
-        }
+        Ok(serde_path_to_error::deserialize(value)?)
     }
 
-    fn from_value(value: &Value) -> Result<Self, Box<dyn Error>>
+    fn from_value(value: &Value) -> Result<Self, IgError>
     where
         Self: Sized,
     {
         let instance: Self = <Self as ValidateResponse>::deserialize(value)?;
+        instance.validate()?;
 
-        match instance.validate() {
-            Ok(()) => Ok(instance),
-            Err(e) => Err(Box::new(ApiError {
-                message: format!("Validation failed: {}", e),
-            })),
-        }
+        Ok(instance)
     }
 
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         Ok(())
     }
 }
 
 /// Struct to represent an empty object for use in optional parameters that
 /// must implement Serialize and ValidateRequest traits.
-#[derive(Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Empty {}
 
 impl ValidateRequest for Empty {}
-impl ValidateRequest for &Empty {}
+impl ValidateResponse for Empty {}
+
+/// Validate borrowed requests like the requests they point to.
+impl<T: ValidateRequest + ?Sized> ValidateRequest for &T {
+    fn validate(&self) -> Result<(), IgError> {
+        (**self).validate()
+    }
+}
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 //
-// REST API MODELS.
+// ENUM DESERIALIZATION.
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[derive(Debug, Default)]
-pub struct SentimentQuery {
-    pub market_ids: Option<Vec<String>>,
+/// Deserialize a value of an IG enum not known to this version of the library into its Other
+/// variant instead of failing the deserialization of the whole response. The value is logged at
+/// trace level, as the same unknown value is usually received by every response.
+fn deserialize_other_variant<'de, T, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    trace!(
+        "Unknown {} value from the IG API: {}",
+        type_name::<T>().rsplit("::").next().unwrap_or_default(),
+        value
+    );
+
+    Ok(value)
 }
 
-impl Serialize for SentimentQuery {
-    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("SentimentQuery", 1)?;
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// DATE AND TIME TYPES.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-        match self.market_ids.as_ref() {
-            Some(ids) => {
-                state.serialize_field("marketIds", &ids.join(","))?;
-            }
-            None => {
-                state.serialize_field("marketIds", &None::<()>)?;
-            }
-        }
+/// Formats of the dates and times sent by IG without an offset.
+const DATE_TIME_FORMATS: [&str; 5] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M:%S:%3f",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+/// Formats of the dates sent by IG without a time.
+const DATE_FORMATS: [&str; 3] = ["%d/%m/%y", "%Y-%m-%d", "%Y/%m/%d"];
+
+/// Date and time sent by IG without an offset, usually in the local time of the account or the
+/// market. The original string is kept.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LocalDateTime {
+    /// The parsed date and time.
+    pub date_time: NaiveDateTime,
+    /// The original string sent by IG.
+    pub raw: String,
+}
+
+impl<'de> Deserialize<'de> for LocalDateTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
 
-        state.end()
+impl FromStr for LocalDateTime {
+    type Err = IgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            date_time: parse_date_time(s)?,
+            raw: s.to_string(),
+        })
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Sentiments {
-    pub client_sentiments: Vec<Sentiment>,
+impl Serialize for LocalDateTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.raw)
+    }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Sentiment {
-    pub long_position_percentage: f64,
-    pub market_id: String,
-    pub short_position_percentage: f64,
+/// Build a date and time in the yyyy/MM/dd HH:mm:ss format expected by IG in requests.
+impl From<NaiveDateTime> for LocalDateTime {
+    fn from(date_time: NaiveDateTime) -> Self {
+        Self {
+            date_time,
+            raw: date_time.format("%Y/%m/%d %H:%M:%S").to_string(),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Application {
-    pub allow_equities: bool,
-    pub allow_quote_orders: bool,
-    pub allowance_account_historical_data: f64,
-    pub allowance_account_overall: f64,
-    pub allowance_account_trading: f64,
-    pub allowance_application_overall: f64,
-    pub api_key: String,
-    pub concurrent_subscriptions_limit: f64,
-    pub created_date: String,
-    pub name: String,
-    pub status: ApplicationStatus,
+/// Date sent by IG without a time, usually in the local time of the account. The original string
+/// is kept.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LocalDate {
+    /// The parsed date.
+    pub date: NaiveDate,
+    /// The original string sent by IG.
+    pub raw: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum ApplicationStatus {
-    Disabled,
-    Enabled,
-    Revoked,
+impl<'de> Deserialize<'de> for LocalDate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UpdateApplication {
-    pub allowance_account_overall: f64,
-    pub allowance_account_trading: f64,
-    pub api_key: String,
-    pub status: ApplicationStatus,
+impl FromStr for LocalDate {
+    type Err = IgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            date: parse_date(s)?,
+            raw: s.to_string(),
+        })
+    }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MarketSearch {
-    pub markets: Vec<MarketData>,
+impl Serialize for LocalDate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+/// Deserialize a UTC date and time sent by IG, with or without an offset.
+fn deserialize_utc_date_time<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_date_time(&value)
+        .map(|date_time| date_time.and_utc())
+        .map_err(serde::de::Error::custom)
+}
+
+/// Deserialize the expiry date and time of an order, which IG sends as GTC when the order is
+/// good till cancelled.
+fn deserialize_good_till<'de, D>(deserializer: D) -> Result<Option<LocalDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if value != "GTC" => value.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Parse a date and time in any of the formats used by IG. Dates and times with an offset are
+/// converted to UTC.
+pub fn parse_date_time(value: &str) -> Result<NaiveDateTime, IgError> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.naive_utc());
+    }
+
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| IgError::Validation {
+            message: format!("Invalid date and time: {}", value),
+        })
+}
+
+/// Parse a date in any of the formats used by IG. Dates sent with a time keep only the date.
+pub fn parse_date(value: &str) -> Result<NaiveDate, IgError> {
+    if let Some(date) = DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+    {
+        return Ok(date);
+    }
+
+    parse_date_time(value)
+        .map(|date_time| date_time.date())
+        .map_err(|_| IgError::Validation {
+            message: format!("Invalid date: {}", value),
+        })
 }
 
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// IDENTIFIER TYPES.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Define a string identifier that is validated against a regex when it is constructed through
+/// FromStr or TryFrom and when it is deserialized.
+macro_rules! identifier {
+    ($(#[$attr:meta])* $name:ident, $regex:ident, $field:literal) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+        #[serde(try_from = "String")]
+        pub struct $name(String);
+
+        #[doc = concat!("Implementation for the ", stringify!($name), " struct.")]
+        impl $name {
+            /// Returns the identifier as a string slice.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IgError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::try_from(s.to_string())
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = IgError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::try_from(value.to_string())
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = IgError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                if !$regex.is_match(&value) {
+                    return Err(IgError::Validation {
+                        message: format!(concat!($field, " field is invalid: {}"), value),
+                    });
+                }
+
+                Ok(Self(value))
+            }
+        }
+    };
+}
+
+identifier!(
+    /// Account identifier, following the pattern `[A-Za-z0-9-]{1,30}`.
+    AccountId,
+    ACCOUNT_ID_REGEX,
+    "Account ID"
+);
+
+identifier!(
+    /// Deal identifier, following the pattern `[A-Za-z0-9-]{1,30}`.
+    DealId,
+    DEAL_ID_REGEX,
+    "Deal ID"
+);
+
+identifier!(
+    /// Deal reference, following the pattern `[A-Za-z0-9_-]{1,30}`.
+    DealReference,
+    DEAL_REFERENCE_REGEX,
+    "Deal reference"
+);
+
+identifier!(
+    /// Instrument epic identifier, following the pattern `[A-Za-z0-9._]{6,30}`.
+    Epic,
+    EPIC_REGEX,
+    "Epic"
+);
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// REST API MODELS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PricesQuery {
@@ -161,21 +389,54 @@ pub struct PricesQuery {
 pub enum Resolution {
     Day,
     Hour,
+    #[serde(rename = "HOUR_2")]
     Hour2,
+    #[serde(rename = "HOUR_3")]
     Hour3,
+    #[serde(rename = "HOUR_4")]
     Hour4,
     Minute,
+    #[serde(rename = "MINUTE_10")]
     Minute10,
+    #[serde(rename = "MINUTE_15")]
     Minute15,
+    #[serde(rename = "MINUTE_2")]
     Minute2,
+    #[serde(rename = "MINUTE_3")]
     Minute3,
+    #[serde(rename = "MINUTE_30")]
     Minute30,
+    #[serde(rename = "MINUTE_5")]
     Minute5,
     Month,
     Second,
     Week,
 }
 
+/// Implementation for the Resolution enum.
+impl Resolution {
+    /// Returns the resolution as sent to the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Resolution::Day => "DAY",
+            Resolution::Hour => "HOUR",
+            Resolution::Hour2 => "HOUR_2",
+            Resolution::Hour3 => "HOUR_3",
+            Resolution::Hour4 => "HOUR_4",
+            Resolution::Minute => "MINUTE",
+            Resolution::Minute10 => "MINUTE_10",
+            Resolution::Minute15 => "MINUTE_15",
+            Resolution::Minute2 => "MINUTE_2",
+            Resolution::Minute3 => "MINUTE_3",
+            Resolution::Minute30 => "MINUTE_30",
+            Resolution::Minute5 => "MINUTE_5",
+            Resolution::Month => "MONTH",
+            Resolution::Second => "SECOND",
+            Resolution::Week => "WEEK",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Prices {
@@ -216,60 +477,20 @@ pub struct Price {
     pub last_traded_volume: f64,
     pub low_price: AskBid,
     pub open_price: AskBid,
-    pub snapshot_time: String,
-    #[serde(rename = "snapshotTimeUTC")]
-    pub snapshot_time_utc: String,
+    pub snapshot_time: LocalDateTime,
+    #[serde(
+        deserialize_with = "deserialize_utc_date_time",
+        rename = "snapshotTimeUTC"
+    )]
+    pub snapshot_time_utc: DateTime<Utc>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AskBid {
-    pub ask: f64,
-    pub bid: f64,
-    pub last_traded: Option<f64>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Watchlists {
-    pub watchlists: Vec<Watchlist>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Watchlist {
-    pub default_system_watchlist: bool,
-    pub deleteable: bool,
-    pub editable: bool,
-    pub id: String,
-    pub name: String,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CreateWatchlist {
-    pub epics: Vec<String>,
-    pub name: String,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CreateWatchlistResult {
-    pub status: CreateWatchlistStatus,
-    pub watchlist_id: String,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum CreateWatchlistStatus {
-    Success,
-    SuccesNotAllInstrumentsAdded,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AddToWatchlist {
-    pub epic: String,
+    pub ask: Number,
+    pub bid: Number,
+    pub last_traded: Option<Number>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -285,7 +506,7 @@ pub struct Account {
     /// Account alias.
     pub account_alias: Option<String>,
     /// Account identifier.
-    pub account_id: String,
+    pub account_id: AccountId,
     /// Account name.
     pub account_name: String,
     /// Account type.
@@ -314,6 +535,12 @@ pub enum AccountStatus {
     Enabled,
     /// Account is suspended from dealing.
     SuspendedFromDealing,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<AccountStatus, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Account type.
@@ -326,6 +553,12 @@ pub enum AccountType {
     Physical,
     /// Spread bet account.
     Spreadbet,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<AccountType, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Response to the GET /accounts request.
@@ -389,6 +622,143 @@ pub struct Balance {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum AccountsPreferencesPutRequestStatus {
     Success,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<AccountsPreferencesPutRequestStatus, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// CLIENT SENTIMENT ENDPOINT MODELS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Maximum deviation allowed between the sum of long and short position percentages and 100.
+const SENTIMENT_PERCENTAGE_TOLERANCE: f64 = 1.0;
+
+/// Client sentiment for a market.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sentiment {
+    /// Percentage of clients holding long positions.
+    pub long_position_percentage: f64,
+    /// Market identifier.
+    pub market_id: String,
+    /// Percentage of clients holding short positions.
+    pub short_position_percentage: f64,
+}
+
+/// Validate the client sentiment response.
+impl ValidateResponse for Sentiment {
+    fn validate(&self) -> Result<(), IgError> {
+        // Constraint: long and short position percentages must add up to 100.
+        let total = self.long_position_percentage + self.short_position_percentage;
+        if (total - 100.0).abs() > SENTIMENT_PERCENTAGE_TOLERANCE {
+            return Err(IgError::Validation {
+                message: format!(
+                    "Long and short position percentages of market '{}' add up to {} instead of 100.",
+                    self.market_id, total
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Request the client sentiment for a single market by sending a GET request to the
+/// /clientsentiment/{marketId} or /clientsentiment/related/{marketId} endpoints.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentimentMarketRequest {
+    /// Market identifier.
+    pub market_id: String,
+}
+
+/// Validate the single market client sentiment request.
+impl ValidateRequest for SentimentMarketRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        if !MARKET_ID_REGEX.is_match(&self.market_id) {
+            return Err(IgError::Validation {
+                message: "Market ID field is invalid.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Request the client sentiment for a list of markets by sending a GET request to the
+/// /clientsentiment endpoint.
+#[derive(Debug, Default)]
+pub struct SentimentQuery {
+    /// Market identifiers, serialized as a comma separated list.
+    pub market_ids: Option<Vec<String>>,
+}
+
+/// Implement the Serialize trait for the SentimentQuery struct.
+impl Serialize for SentimentQuery {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SentimentQuery", 1)?;
+
+        match self.market_ids.as_ref() {
+            Some(ids) => {
+                state.serialize_field("marketIds", &ids.join(","))?;
+            }
+            None => {
+                state.serialize_field("marketIds", &None::<()>)?;
+            }
+        }
+
+        state.end()
+    }
+}
+
+/// Validate the client sentiment query.
+impl ValidateRequest for SentimentQuery {
+    fn validate(&self) -> Result<(), IgError> {
+        let market_ids = match &self.market_ids {
+            Some(market_ids) if !market_ids.is_empty() => market_ids,
+            _ => {
+                return Err(IgError::Validation {
+                    message: "The 'market_ids' field cannot be empty.".to_string(),
+                });
+            }
+        };
+
+        for market_id in market_ids {
+            if !MARKET_ID_REGEX.is_match(market_id) {
+                return Err(IgError::Validation {
+                    message: format!("Market ID '{}' field is invalid.", market_id),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// List of client sentiments. Response to the GET /clientsentiment and
+/// GET /clientsentiment/related/{marketId} requests.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sentiments {
+    /// Client sentiment for each market.
+    pub client_sentiments: Vec<Sentiment>,
+}
+
+/// Validate the client sentiments response.
+impl ValidateResponse for Sentiments {
+    fn validate(&self) -> Result<(), IgError> {
+        for sentiment in &self.client_sentiments {
+            sentiment.validate()?;
+        }
+
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -402,7 +772,7 @@ pub enum AccountsPreferencesPutRequestStatus {
 #[serde(rename_all = "camelCase")]
 pub struct AffectedDeal {
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
     /// Deal status.
     pub status: AffectedDealStatus,
 }
@@ -421,6 +791,12 @@ pub enum AffectedDealStatus {
     Opened,
     /// Partially closed.
     PartiallyClosed,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<AffectedDealStatus, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// GET Request to /confirms/{dealReference} endpoint to retrieve deal confirmations for
@@ -429,7 +805,7 @@ pub enum AffectedDealStatus {
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfirmsGetRequest {
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
 }
 
 impl ValidateRequest for ConfirmsGetRequest {}
@@ -441,27 +817,27 @@ pub struct ConfirmsGetResponse {
     /// Affected deals.
     pub affected_deals: Vec<AffectedDeal>,
     /// Transaction date.
-    pub date: String,
+    pub date: LocalDateTime,
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
     /// Deal reference.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
     /// Deal status.
     pub deal_status: DealStatus,
     /// Deal direction.
     pub direction: Direction,
     /// Instrument epic identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// Instrument expiry.
     pub expiry: Option<String>,
     /// True if guaranteed stop.
     pub guaranteed_stop: bool,
     /// Level at which the deal was opened.
-    pub level: Option<f64>,
+    pub level: Option<Number>,
     /// Limit distance.
-    pub limit_distance: Option<f64>,
+    pub limit_distance: Option<Number>,
     /// Limit level.
-    pub limit_level: Option<f64>,
+    pub limit_level: Option<Number>,
     /// Profit.
     pub profit: Option<f64>,
     /// Profit currency.
@@ -469,13 +845,13 @@ pub struct ConfirmsGetResponse {
     /// Describes the error (or success) condition for the specified trading operation.
     pub reason: DealReason,
     /// Size of the deal.
-    pub size: Option<f64>,
+    pub size: Option<Number>,
     /// Position status.
     pub status: Option<PositionStatus>,
     /// Stop distance.
-    pub stop_distance: Option<f64>,
+    pub stop_distance: Option<Number>,
     /// Stop level.
-    pub stop_level: Option<f64>,
+    pub stop_level: Option<Number>,
     /// True if trailing stop.
     pub trailing_stop: bool,
 }
@@ -624,6 +1000,12 @@ pub enum DealReason {
     Unknown,
     /// The requested operation has been attempted on the wrong direction.
     WrongSideOfMarket,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<DealReason, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Deal status.
@@ -634,6 +1016,12 @@ pub enum DealStatus {
     Accepted,
     /// Rejected.
     Rejected,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<DealStatus, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -648,15 +1036,15 @@ pub struct Activity {
     /// The channel which triggered the activity.
     pub channel: ActivityChannel,
     /// The date of the activity item.
-    pub date: String,
+    pub date: LocalDateTime,
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
     /// Activity description.
     pub description: String,
     /// Activity details.
     pub details: Option<ActivityDetails>,
     /// Instrument epic identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// The period of the activity item, e.g. "DFB" or "02-SEP-11".
     /// This will be the expiry time/date for sprint markets,
     /// e.g. "2015-10-13T12:42:05"
@@ -674,7 +1062,7 @@ pub struct ActivityAction {
     /// Action type.
     pub action_type: ActivityActionType,
     /// Affected deal ID.
-    pub affected_deal_id: String,
+    pub affected_deal_id: DealId,
 }
 
 /// Activity action type.
@@ -717,6 +1105,12 @@ pub enum ActivityActionType {
     Unknown,
     /// Working order amended.
     WorkingOrderDeleted,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<ActivityActionType, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// The channel which triggered the activity.
@@ -735,6 +1129,12 @@ pub enum ActivityChannel {
     System,
     /// Web.
     Web,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<ActivityChannel, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Activity details.
@@ -746,31 +1146,32 @@ pub struct ActivityDetails {
     /// Currency.
     pub currency: String,
     /// Deal reference.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
     /// Deal direction.
     pub direction: Direction,
-    /// Good till date.
-    pub good_till_date: String,
+    /// Good till date, or None if the order is good till cancelled.
+    #[serde(default, deserialize_with = "deserialize_good_till")]
+    pub good_till_date: Option<LocalDateTime>,
     /// Guaranteed stop.
     pub guaranteed_stop: bool,
     /// Level.
-    pub level: f64,
+    pub level: Number,
     /// Limit distance.
-    pub limit_distance: f64,
+    pub limit_distance: Number,
     /// Limit level.
-    pub limit_level: f64,
+    pub limit_level: Number,
     /// Market name.
     pub market_name: String,
     /// Size.
-    pub size: f64,
+    pub size: Number,
     /// Stop distance.
-    pub stop_distance: f64,
+    pub stop_distance: Number,
     /// Stop level.
-    pub stop_level: f64,
+    pub stop_level: Number,
     /// Trailing step size.
-    pub trailing_step: f64,
+    pub trailing_step: Number,
     /// Trailing stop distance.
-    pub trailing_stop_distance: f64,
+    pub trailing_stop_distance: Number,
 }
 
 /// Returns the activity history by sending a GET request to the /history/activity endpoint.
@@ -785,7 +1186,7 @@ pub struct ActivityHistoryGetRequest {
     /// Indicates whether to retrieve additional details about the activity.
     pub detailed: Option<bool>,
     /// Deal ID.
-    pub deal_id: Option<String>,
+    pub deal_id: Option<DealId>,
     /// FIQL filter (supported operators: ==|!=|,|;).
     pub filter: Option<String>,
     /// Page size (min: 10, max: 500).
@@ -794,20 +1195,20 @@ pub struct ActivityHistoryGetRequest {
 
 /// Implement the ValidateRequest trait for the ActivityHistoryGetRequest struct.
 impl ValidateRequest for ActivityHistoryGetRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Check if the 'from' date is not greater than today.
         if self.from > Utc::now().naive_utc() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "'From' date cannot be greater than today.".to_string(),
-            }));
+            });
         }
 
         // Check if the 'from' date is not greater than 'to'.
         if let Some(to) = self.to {
             if self.from > to {
-                return Err(Box::new(ApiError {
+                return Err(IgError::Validation {
                     message: "'From' date cannot be greater than 'to' date.".to_string(),
-                }));
+                });
             }
         }
 
@@ -825,6 +1226,64 @@ pub struct ActivityHistoryGetResponse {
 
 impl ValidateResponse for ActivityHistoryGetResponse {}
 
+/// Response to the version 1 GET /history/activity/{lastPeriod} and
+/// GET /history/activity/{fromDate}/{toDate} requests.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityHistoryV1GetResponse {
+    /// Activity data.
+    pub activities: Vec<ActivityV1>,
+}
+
+impl ValidateResponse for ActivityHistoryV1GetResponse {}
+
+/// Returns the activity history by sending a version 2 GET request to the /history/activity
+/// endpoint.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityHistoryV2GetRequest {
+    /// Start date.
+    pub from: Option<NaiveDateTime>,
+    /// Limits the timespan in seconds through to the current time. Not applicable if a date
+    /// range has been specified.
+    pub max_span_seconds: Option<u64>,
+    /// Page number.
+    pub page_number: Option<u32>,
+    /// Page size (disable paging = 0).
+    pub page_size: Option<u32>,
+    /// End date.
+    pub to: Option<NaiveDateTime>,
+}
+
+/// Implement the ValidateRequest trait for the ActivityHistoryV2GetRequest struct.
+impl ValidateRequest for ActivityHistoryV2GetRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        // Check if the 'from' date is not greater than 'to'.
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(IgError::Validation {
+                    message: "'From' date cannot be greater than 'to' date.".to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Response to the version 2 GET /history/activity request, which returns the version 1
+/// activity items with paging metadata.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityHistoryV2GetResponse {
+    /// Activity data.
+    pub activities: Vec<ActivityV1>,
+    /// Paging metadata.
+    pub metadata: ActivityV2Metadata,
+}
+
+impl ValidateResponse for ActivityHistoryV2GetResponse {}
+
 /// Paging metadata.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -853,6 +1312,12 @@ pub enum ActivityStatus {
     Rejected,
     /// Unknown.
     Unknown,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<ActivityStatus, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Activity type.
@@ -867,6 +1332,97 @@ pub enum ActivityType {
     System,
     /// Working order activity.
     WorkingOrder,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<ActivityType, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Activity item returned by version 1 of the activity history endpoints. Levels and sizes
+/// are formatted strings, which may be "-" if not applicable.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityV1 {
+    /// Action status.
+    pub action_status: ActivityV1ActionStatus,
+    /// Activity description.
+    pub activity: String,
+    /// Activity history identifier.
+    pub activity_history_id: Option<String>,
+    /// The channel which triggered the activity.
+    pub channel: String,
+    /// Currency.
+    pub currency: String,
+    /// Local date, formatted as dd/MM/yy.
+    pub date: LocalDate,
+    /// Deal identifier.
+    pub deal_id: DealId,
+    /// Instrument epic identifier.
+    pub epic: Epic,
+    /// Level.
+    pub level: String,
+    /// Limit level.
+    pub limit: Option<String>,
+    /// Market name.
+    pub market_name: String,
+    /// The period of the activity item, e.g. "DFB" or "02-SEP-11".
+    pub period: String,
+    /// Action result.
+    pub result: String,
+    /// Formatted size, including the direction (+ for buy, - for sell).
+    pub size: String,
+    /// Stop level.
+    pub stop: Option<String>,
+    /// Stop type.
+    pub stop_type: Option<String>,
+    /// Local time, formatted as HH:mm.
+    pub time: NaiveTime,
+}
+
+/// Action status of a version 1 activity item.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivityV1ActionStatus {
+    /// Accepted.
+    Accept,
+    /// Manual.
+    Manual,
+    /// Not set.
+    NotSet,
+    /// Rejected.
+    Reject,
+    /// Unknown.
+    Unknown,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<ActivityV1ActionStatus, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Paging metadata of the version 2 activity history.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityV2Metadata {
+    /// Paging metadata.
+    pub page_data: ActivityV2PageData,
+    /// Size.
+    pub size: u32,
+}
+
+/// Paging metadata.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityV2PageData {
+    /// Page number.
+    pub page_number: u32,
+    /// Page size.
+    pub page_size: u32,
+    /// Total number of pages.
+    pub total_pages: u32,
 }
 
 /// Deal direction.
@@ -878,6 +1434,71 @@ pub enum Direction {
     Buy,
     /// Sell.
     Sell,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<Direction, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Time span of the version 1 history requests, which is sent as part of the request path.
+#[derive(Debug)]
+pub enum HistoryPeriod {
+    /// Range of days, both included.
+    DateRange {
+        /// First day.
+        from: NaiveDate,
+        /// Last day.
+        to: NaiveDate,
+    },
+    /// Period up to the current time, sent in milliseconds.
+    LastPeriod(std::time::Duration),
+}
+
+/// Implementation for the HistoryPeriod enum.
+impl HistoryPeriod {
+    /// Returns the path segments of the period: the milliseconds of the last period, or the
+    /// first and last days formatted as dd-MM-yyyy.
+    pub fn path(&self) -> String {
+        match self {
+            HistoryPeriod::DateRange { from, to } => {
+                format!("{}/{}", from.format("%d-%m-%Y"), to.format("%d-%m-%Y"))
+            }
+            HistoryPeriod::LastPeriod(period) => period.as_millis().to_string(),
+        }
+    }
+
+    /// Validate the period.
+    pub fn validate(&self) -> Result<(), IgError> {
+        match self {
+            HistoryPeriod::DateRange { from, to } => {
+                // Check if the 'from' date is not greater than today.
+                if *from > Utc::now().date_naive() {
+                    return Err(IgError::Validation {
+                        message: "'From' date cannot be greater than today.".to_string(),
+                    });
+                }
+
+                // Check if the 'from' date is not greater than 'to'.
+                if from > to {
+                    return Err(IgError::Validation {
+                        message: "'From' date cannot be greater than 'to' date.".to_string(),
+                    });
+                }
+            }
+            HistoryPeriod::LastPeriod(period) => {
+                if period.as_millis() == 0 {
+                    return Err(IgError::Validation {
+                        message: "The last period cannot be shorter than a millisecond."
+                            .to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -886,6 +1507,15 @@ pub enum Direction {
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Monetary amount with its ISO currency code.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Money {
+    /// Signed amount.
+    pub amount: Number,
+    /// ISO 4217 currency code.
+    pub currency: String,
+}
+
 /// Transaction data.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -897,13 +1527,15 @@ pub struct Transaction {
     /// Order currency.
     pub currency: String,
     /// Local date.
-    pub date: String,
+    pub date: LocalDateTime,
     /// UTC date.
-    pub date_utc: String,
+    #[serde(deserialize_with = "deserialize_utc_date_time")]
+    pub date_utc: DateTime<Utc>,
     /// Instrument name.
     pub instrument_name: String,
     /// Position opened date.
-    pub open_date_utc: String,
+    #[serde(deserialize_with = "deserialize_utc_date_time")]
+    pub open_date_utc: DateTime<Utc>,
     /// Level at which the order was opened.
     pub open_level: String,
     /// Period.
@@ -918,6 +1550,29 @@ pub struct Transaction {
     pub transaction_type: String,
 }
 
+/// Implement the TransactionValues trait for the Transaction struct.
+impl TransactionValues for Transaction {
+    fn close_level(&self) -> &str {
+        &self.close_level
+    }
+
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    fn open_level(&self) -> &str {
+        &self.open_level
+    }
+
+    fn profit_and_loss(&self) -> &str {
+        &self.profit_and_loss
+    }
+
+    fn size(&self) -> &str {
+        &self.size
+    }
+}
+
 /// Returns the transaction history by sending a GET request to the /history/transactions endpoint.
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -939,20 +1594,20 @@ pub struct TransactionHistoryGetRequest {
 
 /// Implement the ValidateRequest trait for the TransactionHistoryGetRequest struct.
 impl ValidateRequest for TransactionHistoryGetRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Check if the 'from' date is not greater than today.
         if self.from > Utc::now().naive_utc() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "'From' date cannot be greater than today.".to_string(),
-            }));
+            });
         }
 
         // Check if the 'from' date is not greater than 'to'.
         if let Some(to) = self.to {
             if self.from > to {
-                return Err(Box::new(ApiError {
+                return Err(IgError::Validation {
                     message: "'From' date cannot be greater than 'to' date.".to_string(),
-                }));
+                });
             }
         }
 
@@ -972,6 +1627,18 @@ pub struct TransactionHistoryGetResponse {
 
 impl ValidateResponse for TransactionHistoryGetResponse {}
 
+/// List of transactions. Response to the version 1
+/// GET /history/transactions/{transactionType}/{lastPeriod} and
+/// GET /history/transactions/{transactionType}/{fromDate}/{toDate} requests.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionHistoryV1GetResponse {
+    /// Transaction data.
+    pub transactions: Vec<TransactionV1>,
+}
+
+impl ValidateResponse for TransactionHistoryV1GetResponse {}
+
 /// Paging metadata.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -1006,6 +1673,150 @@ pub enum TransactionType {
     Deposit,
     /// Withdrawal.
     Withdrawal,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<TransactionType, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Implementation for the TransactionType enum.
+impl TransactionType {
+    /// Returns the transaction type as sent to the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            TransactionType::All => "ALL",
+            TransactionType::AllDeal => "ALL_DEAL",
+            TransactionType::Deposit => "DEPOSIT",
+            TransactionType::Withdrawal => "WITHDRAWAL",
+            TransactionType::Other(transaction_type) => transaction_type,
+        }
+    }
+}
+
+/// Transaction data returned by version 1 of the transaction history endpoints, which has no
+/// UTC dates.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionV1 {
+    /// True if this was a cash transaction.
+    pub cash_transaction: bool,
+    /// Level at which the order was closed.
+    pub close_level: String,
+    /// Order currency.
+    pub currency: String,
+    /// Local date.
+    pub date: LocalDate,
+    /// Instrument name.
+    pub instrument_name: String,
+    /// Level at which the order was opened.
+    pub open_level: String,
+    /// Period.
+    pub period: String,
+    /// Profit and loss.
+    pub profit_and_loss: String,
+    /// Reference.
+    pub reference: String,
+    /// Formatted order size, including the direction (+ for buy, - for sell)
+    pub size: String,
+    /// Transaction type.
+    pub transaction_type: String,
+}
+
+/// Implement the TransactionValues trait for the TransactionV1 struct.
+impl TransactionValues for TransactionV1 {
+    fn close_level(&self) -> &str {
+        &self.close_level
+    }
+
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    fn open_level(&self) -> &str {
+        &self.open_level
+    }
+
+    fn profit_and_loss(&self) -> &str {
+        &self.profit_and_loss
+    }
+
+    fn size(&self) -> &str {
+        &self.size
+    }
+}
+
+/// Values parsed from the formatted levels, profit and loss, and size of the transactions
+/// returned by every version of the transaction history endpoints.
+pub trait TransactionValues {
+    /// Formatted level at which the order was closed.
+    fn close_level(&self) -> &str;
+    /// Order currency, which may be a symbol.
+    fn currency(&self) -> &str;
+    /// Formatted level at which the order was opened.
+    fn open_level(&self) -> &str;
+    /// Formatted profit and loss, prefixed by its currency.
+    fn profit_and_loss(&self) -> &str;
+    /// Formatted order size, including the direction.
+    fn size(&self) -> &str;
+
+    /// Level at which the order was closed, or None if IG sent no level.
+    fn close_level_value(&self) -> Option<Number> {
+        parse_formatted_number(self.close_level()).map(|(level, _)| level)
+    }
+
+    /// Level at which the order was opened, or None if IG sent no level.
+    fn open_level_value(&self) -> Option<Number> {
+        parse_formatted_number(self.open_level()).map(|(level, _)| level)
+    }
+
+    /// Signed profit and loss with its ISO currency code. The account currency, e.g. from
+    /// accounts_get, is used when IG only sends a currency symbol.
+    fn profit_and_loss_amount(&self, account_currency: &str) -> Option<Money> {
+        let (amount, symbol) = parse_formatted_number(self.profit_and_loss())?;
+
+        Some(Money {
+            amount,
+            currency: iso_currency(&[self.currency(), symbol], account_currency),
+        })
+    }
+
+    /// Signed order size: positive for buy orders and negative for sell orders.
+    fn size_value(&self) -> Option<Number> {
+        parse_formatted_number(self.size()).map(|(size, _)| size)
+    }
+}
+
+/// Get the first of the given currencies that is an ISO currency code, falling back to the
+/// account currency when IG only sends currency symbols.
+fn iso_currency(currencies: &[&str], account_currency: &str) -> String {
+    currencies
+        .iter()
+        .find(|currency| currency.len() == 3 && currency.chars().all(|c| c.is_ascii_uppercase()))
+        .copied()
+        .unwrap_or(account_currency)
+        .to_string()
+}
+
+/// Parse a number formatted by IG in the transaction history, such as `E-1,234.50` or `+1.5`,
+/// returning it with its currency prefix. Returns None if there is no number, e.g. for `-`.
+fn parse_formatted_number(value: &str) -> Option<(Number, &str)> {
+    let value = value.trim();
+    let (prefix, digits) = value.split_at(value.find(|c: char| c.is_ascii_digit() || c == '.')?);
+    let digits: String = digits
+        .chars()
+        .filter(|c| !matches!(c, ',' | '\'' | ' '))
+        .collect();
+    let number = digits.parse::<Number>().ok()?;
+    let number = if prefix.contains('-') {
+        -number
+    } else {
+        number
+    };
+    let currency = prefix.trim_matches(|c: char| c == '+' || c == '-' || c.is_whitespace());
+
+    Some((number, currency))
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -1037,7 +1848,16 @@ pub struct DealingRule {
     /// Describes the dimension for a dealing rule value.
     pub unit: RuleUnit,
     /// Value.
-    pub value: f64,
+    pub value: Number,
+}
+
+/// Implementation for the DealingRule struct.
+impl DealingRule {
+    /// Round a value to the nearest multiple of the rule value, e.g. a size to the
+    /// min_step_distance rule.
+    pub fn round_to_step(&self, value: Number) -> Number {
+        round_to_step(value, self.value)
+    }
 }
 
 /// Dealing rules.
@@ -1081,7 +1901,7 @@ pub struct DepositBand {
 #[serde(rename_all = "camelCase")]
 pub struct Expiry {
     /// Last dealing date.
-    pub last_dealing_date: String,
+    pub last_dealing_date: LocalDateTime,
     /// Settlement information.
     pub settlement_info: String,
 }
@@ -1101,7 +1921,7 @@ pub struct InstrumentDetails {
     /// Currencies.
     pub currencies: Vec<Currency>,
     /// Instrument identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// Expiry.
     pub expiry: String,
     /// Market expiry details.
@@ -1153,6 +1973,72 @@ pub struct InstrumentDetails {
     pub value_of_one_pip: String,
 }
 
+/// Instrument details returned by versions 1 and 2 of the GET /markets/{epic} endpoint, which
+/// have no limited risk premium.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentDetailsV1 {
+    /// Chart code.
+    pub chart_code: String,
+    /// Contract size.
+    pub contract_size: String,
+    /// True if controlled risk trades are allowed.
+    pub controlled_risk_allowed: bool,
+    /// Country.
+    pub country: Option<String>,
+    /// Currencies.
+    pub currencies: Vec<Currency>,
+    /// Instrument identifier.
+    pub epic: Epic,
+    /// Expiry.
+    pub expiry: String,
+    /// Market expiry details.
+    pub expiry_details: Option<Expiry>,
+    /// True if force open is allowed.
+    pub force_open_allowed: bool,
+    /// Lot size.
+    pub lot_size: f64,
+    /// Margin deposit bands.
+    pub margin_deposit_bands: Vec<DepositBand>,
+    /// Margin requirement factor.
+    pub margin_factor: f64,
+    /// Describes the dimension for a dealing rule value.
+    pub margin_factor_unit: RuleUnit,
+    /// Market identifier.
+    pub market_id: String,
+    /// Market name.
+    pub name: String,
+    /// Reuters news code.
+    pub news_code: String,
+    /// Meaning of one pip.
+    pub one_pip_means: String,
+    /// Market open and close times.
+    pub opening_hours: Option<OpeningHours>,
+    /// Market rollover details.
+    pub rollover_details: Option<Rollover>,
+    /// Slippage factor details for a given market.
+    pub slippage_factor: SlippageFactor,
+    /// List of special information notices.
+    pub special_info: Vec<String>,
+    /// For sprint markets only, the maximum value to be specified
+    /// as the expiry of a sprint markets trade.
+    pub sprint_markets_maximum_expiry_time: Option<f64>,
+    /// For sprint markets only, the minimum value to be specified
+    /// as the expiry of a sprint markets trade.
+    pub sprint_markets_minimum_expiry_time: Option<f64>,
+    /// True if stops and limits are allowed.
+    pub stops_limits_allowed: bool,
+    /// True if streaming prices are available, i.e. the market is
+    /// open and the client has appropriate permissions.
+    pub streaming_prices_available: bool,
+    /// Instrument type.
+    pub r#type: InstrumentType,
+    /// Unit used to qualify the size of a trade.
+    pub unit: InstrumentUnit,
+    /// Value of one pip.
+    pub value_of_one_pip: String,
+}
+
 /// Unit used to qualify the size of a trade.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -1163,6 +2049,12 @@ pub enum InstrumentUnit {
     Contracts,
     /// Shares.
     Shares,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<InstrumentUnit, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Market details.
@@ -1177,8 +2069,24 @@ pub struct MarketDetails {
     pub snapshot: MarketSnapshot,
 }
 
+impl ValidateResponse for MarketDetails {}
+
+/// Market details returned by versions 1 and 2 of the GET /markets/{epic} endpoint.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketDetailsV1 {
+    /// Dealing rules.
+    pub dealing_rules: DealingRules,
+    /// Instrument details.
+    pub instrument: InstrumentDetailsV1,
+    /// Market snapshot data.
+    pub snapshot: MarketSnapshot,
+}
+
+impl ValidateResponse for MarketDetailsV1 {}
+
 /// Filter for the market details.
-#[derive(Debug, Serialize)]
+#[derive(Clone, Copy, Debug, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum MarketDetailsFilterType {
     /// Display all market details. Market details includes all instrument data,
@@ -1191,6 +2099,16 @@ pub enum MarketDetailsFilterType {
     SnapshotOnly,
 }
 
+/// Request the details of a single market by sending a GET request to the /markets/{epic} endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketGetRequest {
+    /// Instrument epic identifier.
+    pub epic: Epic,
+}
+
+impl ValidateRequest for MarketGetRequest {}
+
 /// Market navigation data.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -1235,13 +2153,50 @@ pub enum MarketOrderPreference {
     AvailableDefaultOn,
     /// Market orders are not allowed for the current site and/or instrument.
     NotAvailable,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<MarketOrderPreference, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// List of markets matching a search term. Response to the GET /markets?searchTerm={searchTerm} request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSearch {
+    /// Market data.
+    pub markets: Vec<MarketData>,
+}
+
+impl ValidateResponse for MarketSearch {}
+
+/// Request to search markets by sending a GET request to the /markets?searchTerm={searchTerm} endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSearchRequest {
+    /// The term to be used in the search.
+    pub search_term: String,
+}
+
+/// Validate the market search request.
+impl ValidateRequest for MarketSearchRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        if self.search_term.trim().is_empty() {
+            return Err(IgError::Validation {
+                message: "The 'search_term' field cannot be empty.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
 }
 
 /// Request to the GET /markets endpoint.
 #[derive(Debug, Default)]
 pub struct MarketsGetRequest {
     /// The epics of the market to be retrieved, separated by a comma.
-    pub epics: Vec<String>,
+    pub epics: Vec<Epic>,
     /// Filter for the market details.
     pub filter: Option<MarketDetailsFilterType>,
 }
@@ -1251,7 +2206,8 @@ impl Serialize for MarketsGetRequest {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut state = serializer.serialize_struct("MarketsQuery", 2)?;
 
-        state.serialize_field("epics", &self.epics.join(","))?;
+        let epics: Vec<&str> = self.epics.iter().map(Epic::as_str).collect();
+        state.serialize_field("epics", &epics.join(","))?;
 
         match self.filter.as_ref() {
             Some(filter) => {
@@ -1268,27 +2224,19 @@ impl Serialize for MarketsGetRequest {
 
 /// Implement the ValidateRequest trait for the MarketsGetRequest struct.
 impl ValidateRequest for MarketsGetRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: Size(min=1).
         if self.epics.is_empty() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "The 'epics' field cannot be empty.".to_string(),
-            }));
+            });
         }
 
         // Constraint: Size(max=50).
         if self.epics.len() > 50 {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "The 'epics' field cannot be greater than 50.".to_string(),
-            }));
-        }
-
-        // Constraint: Pattern(regexp="^([A-Z]+(?:\.[A-Z]+)*(?:,[A-Z]+(?:\.[A-Z]+)*)*)$").
-        let serialized_epics = self.epics.join(",");
-        if !EPICS_REGEX.is_match(&serialized_epics) {
-            return Err(Box::new(ApiError {
-                message: format!("Epics field is invalid. Fields: {}", serialized_epics),
-            }));
+            });
         }
 
         Ok(())
@@ -1313,6 +2261,12 @@ pub enum RuleUnit {
     Percentage,
     /// Points.
     Points,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<RuleUnit, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Market snapshot data.
@@ -1320,7 +2274,7 @@ pub enum RuleUnit {
 #[serde(rename_all = "camelCase")]
 pub struct MarketSnapshot {
     /// Bid price.
-    pub bid: f64,
+    pub bid: Number,
     /// Binary odds.
     pub binary_odds: Option<f64>,
     /// The number of points to add on each side of the market as an
@@ -1331,22 +2285,30 @@ pub struct MarketSnapshot {
     /// Price delay.
     pub delay_time: f64,
     /// Highest price on the day.
-    pub high: f64,
+    pub high: Number,
     /// Lowest price on the day.
-    pub low: f64,
+    pub low: Number,
     /// Describes the current status of a given market.
     pub market_status: MarketStatus,
     /// Net price change on the day.
-    pub net_change: f64,
+    pub net_change: Number,
     /// Offer price.
-    pub offer: f64,
+    pub offer: Number,
     /// Percentage price change on the day.
     pub percentage_change: f64,
     /// Multiplying factor to determine actual pip value for the
     /// levels used by the instrument.
     pub scaling_factor: f64,
     /// Time of last price update.
-    pub update_time: String,
+    pub update_time: NaiveTime,
+}
+
+/// Implementation for the MarketSnapshot struct.
+impl MarketSnapshot {
+    /// Round a level to the number of decimal places of the market levels.
+    pub fn round_level(&self, level: Number) -> Number {
+        round_to_decimal_places(level, self.decimal_places_factor as u32)
+    }
 }
 
 /// Market time range.
@@ -1395,6 +2357,110 @@ pub enum TrailingStopsPreference {
     Available,
     /// Trailing stops are not allowed for the current market.
     NotAvailable,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<TrailingStopsPreference, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// OPERATIONS ENDPOINT MODELS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Client application data.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Application {
+    /// True if the application is allowed to access equities.
+    pub allow_equities: bool,
+    /// True if the application is allowed to place quote orders.
+    pub allow_quote_orders: bool,
+    /// Per account request per minute allowance for historical price data.
+    pub allowance_account_historical_data: f64,
+    /// Overall request per minute allowance per account.
+    pub allowance_account_overall: f64,
+    /// Per account trading request per minute allowance.
+    pub allowance_account_trading: f64,
+    /// Overall request per minute allowance for the application.
+    pub allowance_application_overall: f64,
+    /// API key.
+    pub api_key: String,
+    /// Maximum number of concurrent Lightstreamer subscriptions.
+    pub concurrent_subscriptions_limit: f64,
+    /// Application creation date.
+    pub created_date: LocalDate,
+    /// Application name.
+    pub name: String,
+    /// Application status.
+    pub status: ApplicationStatus,
+}
+
+impl ValidateResponse for Application {}
+
+/// Application status.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApplicationStatus {
+    /// Disabled.
+    Disabled,
+    /// Enabled.
+    Enabled,
+    /// Revoked.
+    Revoked,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<ApplicationStatus, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// List of client applications. Response to the GET /operations/application request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Applications {
+    /// Client applications.
+    pub applications: Vec<Application>,
+}
+
+impl ValidateResponse for Applications {}
+
+/// Request to update an application by sending a PUT request to the /operations/application endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateApplication {
+    /// Overall request per minute allowance per account.
+    pub allowance_account_overall: f64,
+    /// Per account trading request per minute allowance.
+    pub allowance_account_trading: f64,
+    /// API key.
+    pub api_key: String,
+    /// Application status.
+    pub status: ApplicationStatus,
+}
+
+/// Validate the update application request.
+impl ValidateRequest for UpdateApplication {
+    fn validate(&self) -> Result<(), IgError> {
+        if self.api_key.is_empty() {
+            return Err(IgError::Validation {
+                message: "API key field is empty.".to_string(),
+            });
+        }
+
+        // Constraint: allowances cannot be negative.
+        if self.allowance_account_overall < 0.0 || self.allowance_account_trading < 0.0 {
+            return Err(IgError::Validation {
+                message: "Allowance fields cannot be negative.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -1408,15 +2474,15 @@ pub enum TrailingStopsPreference {
 #[serde(rename_all = "camelCase")]
 pub struct MarketData {
     /// Bid.
-    pub bid: Option<f64>,
+    pub bid: Option<Number>,
     /// Instrument price delay (minutes).
     pub delay_time: f64,
     /// Instrument epic identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// Instrument expiry period.
     pub expiry: String,
     /// High price.
-    pub high: Option<f64>,
+    pub high: Option<Number>,
     /// Instrument name.
     pub instrument_name: String,
     /// Instrument type.
@@ -1424,13 +2490,13 @@ pub struct MarketData {
     /// Instrument lot size.
     pub lot_size: Option<f64>,
     /// Low price.
-    pub low: Option<f64>,
+    pub low: Option<Number>,
     /// Describes the current status of a given market.
     pub market_status: MarketStatus,
     /// Price net change.
-    pub net_change: f64,
+    pub net_change: Number,
     /// Offer.
-    pub offer: Option<f64>,
+    pub offer: Option<Number>,
     /// Price percentage change.
     pub percentage_change: f64,
     /// Multiplying factor to determine actual pip value for the
@@ -1440,10 +2506,51 @@ pub struct MarketData {
     /// tradeable and the client has appropriate permissions.
     pub streaming_prices_available: bool,
     /// Local time of last instrument price update.
-    pub update_time: String,
+    pub update_time: NaiveTime,
     /// UTC time of last instrument price update.
     #[serde(rename = "updateTimeUTC")]
-    pub update_time_utc: String,
+    pub update_time_utc: NaiveTime,
+}
+
+/// Market data returned by version 1 of the positions and working orders endpoints, which
+/// has no UTC update time.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketDataV1 {
+    /// Bid.
+    pub bid: Option<Number>,
+    /// Instrument price delay (minutes).
+    pub delay_time: f64,
+    /// Instrument epic identifier.
+    pub epic: Epic,
+    /// Instrument expiry period.
+    pub expiry: String,
+    /// High price.
+    pub high: Option<Number>,
+    /// Instrument name.
+    pub instrument_name: String,
+    /// Instrument type.
+    pub instrument_type: InstrumentType,
+    /// Instrument lot size.
+    pub lot_size: Option<f64>,
+    /// Low price.
+    pub low: Option<Number>,
+    /// Describes the current status of a given market.
+    pub market_status: MarketStatus,
+    /// Price net change.
+    pub net_change: Number,
+    /// Offer.
+    pub offer: Option<Number>,
+    /// Price percentage change.
+    pub percentage_change: f64,
+    /// Multiplying factor to determine actual pip value for the
+    /// levels used by the instrument.
+    pub scaling_factor: f64,
+    /// True if streaming prices are available, i.e. the market is
+    /// tradeable and the client has appropriate permissions.
+    pub streaming_prices_available: bool,
+    /// Local time of last instrument price update.
+    pub update_time: NaiveTime,
 }
 
 /// Describes the current status of a given market.
@@ -1464,6 +2571,12 @@ pub enum MarketStatus {
     Suspended,
     /// Open for trades.
     Tradeable,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<MarketStatus, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Instrument type.
@@ -1493,6 +2606,12 @@ pub enum InstrumentType {
     SprintMarket,
     TestMarket,
     Unknown,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<InstrumentType, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Describes the order level model to be used for a position operation.
@@ -1509,6 +2628,12 @@ pub enum OrderType {
     /// Quote orders get executed at the specified level. The level has to be accompanied by a valid
     /// quote id. This type is only available subject to agreement with IG.
     Quote,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<OrderType, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -1519,6 +2644,12 @@ pub enum PositionStatus {
     Deleted,
     Open,
     PartiallyClosed,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<PositionStatus, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Request to close a position by sending a DELETE request to the /positions/otc endpoint.
@@ -1526,110 +2657,90 @@ pub enum PositionStatus {
 #[serde(rename_all = "camelCase")]
 pub struct PositionDeleteRequest {
     /// Deal identifier.
-    pub deal_id: Option<String>,
+    pub deal_id: Option<DealId>,
     /// Deal direction.
     pub direction: Option<Direction>,
     /// Instrument epic identifier.
-    pub epic: Option<String>,
+    pub epic: Option<Epic>,
     /// Instrument expiry.
     pub expiry: Option<String>,
     /// Closing deal level.
-    pub level: Option<f64>,
+    pub level: Option<Number>,
     /// Describes the order level model to be used for a position operation.
     pub order_type: Option<OrderType>,
     /// Lightstreamer price quote identifier.
     pub quote_id: Option<String>,
     /// Deal size.
-    pub size: f64,
+    pub size: Number,
     /// The time in force determines the order fill strategy.
     pub time_in_force: Option<TimeInForce>,
 }
 
 /// Implements the validation of the PositionDeleteRequest.
 impl ValidateRequest for PositionDeleteRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        // Constraint: Pattern(regexp=".{1,30}")
-        if let Some(deal_id) = &self.deal_id {
-            if !DEAL_ID_REGEX.is_match(deal_id) {
-                return Err(Box::new(ApiError {
-                    message: "Deal ID field is invalid.".to_string(),
-                }));
-            }
-        }
-
-        // Constraint: Pattern(regexp="[A-Za-z0-9._]{6,30}")
-        if let Some(epic) = &self.epic {
-            if !EPIC_REGEX.is_match(epic) {
-                return Err(Box::new(ApiError {
-                    message: "Epic field is invalid.".to_string(),
-                }));
-            }
-        }
-
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: Pattern(regexp="(\\d{2}-)?[A-Z]{3}-\\d{2}|-|DFB")
         if let Some(expiry) = &self.expiry {
             if !EXPIRY_REGEX.is_match(expiry) {
-                return Err(Box::new(ApiError {
+                return Err(IgError::Validation {
                     message: "Expiry field is invalid.".to_string(),
-                }));
+                });
             }
         }
 
         // Constraint: check precision of size is not more than 12 decimal places.
-        let size_str = format!("{}", self.size);
-        let parts: Vec<&str> = size_str.split('.').collect();
-        if parts.len() == 2 && parts[1].len() > 12 {
-            return Err(Box::new(ApiError {
+        if decimal_places(self.size) > 12 {
+            return Err(IgError::Validation {
                 message: "Size field has more thatn 12 decimal places.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if epic is defined, then set expiry.
         if self.epic.is_some() && self.expiry.is_none() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Expiry field is required when epic is defined.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals LIMIT, then DO NOT set quote_id.
         if self.order_type == Some(OrderType::Limit) && self.quote_id.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Quote ID field cannot be set when order type is LIMIT.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals LIMIT, then set level.
         if self.order_type == Some(OrderType::Limit) && self.level.is_none() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Level field is required when order type is LIMIT.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals MARKET, then DO NOT set level, quote_id.
         if self.order_type == Some(OrderType::Market)
             && (self.level.is_some() || self.quote_id.is_some())
         {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Level and quote ID fields cannot be set when order type is MARKET."
                     .to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals QUOTE, then set level, quoteId.
         if self.order_type == Some(OrderType::Quote)
             && (self.level.is_none() || self.quote_id.is_none())
         {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Level and quote ID fields are required when order type is QUOTE."
                     .to_string(),
-            }));
+            });
         }
 
         // Constraint: set only one of {deal_id, epic}.
         if self.deal_id.is_some() && self.epic.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Set only one of {deal_id, epic}.".to_string(),
-            }));
+            });
         }
 
         Ok(())
@@ -1637,10 +2748,10 @@ impl ValidateRequest for PositionDeleteRequest {
 }
 
 /// Response to position close request (DELETE /positions/otc).
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionDeleteResponse {
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
 }
 
 impl ValidateResponse for PositionDeleteResponse {}
@@ -1651,21 +2762,10 @@ impl ValidateResponse for PositionDeleteResponse {}
 #[serde(rename_all = "camelCase")]
 pub struct PositionGetRequest {
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
 }
 
-/// Implements the validation of the PositionGetRequest.
-impl ValidateRequest for PositionGetRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if !DEAL_ID_REGEX.is_match(&self.deal_id) {
-            return Err(Box::new(ApiError {
-                message: "Deal ID field is invalid.".to_string(),
-            }));
-        }
-
-        Ok(())
-    }
-}
+impl ValidateRequest for PositionGetRequest {}
 
 /// List of all the positions for the active account. Response to the GET /positions request.
 #[derive(Debug, Deserialize, Serialize)]
@@ -1690,17 +2790,17 @@ pub struct PositionGetResponse {
 impl ValidateResponse for PositionGetResponse {}
 
 /// Request to open a new position by sending a POST request to the /positions/otc endpoint.
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionPostRequest {
     /// Currency code.
     pub currency_code: String,
     /// Deal reference. A user-defined reference identifying the submission of the order.
-    pub deal_reference: Option<String>,
+    pub deal_reference: Option<DealReference>,
     /// Deal direction.
     pub direction: Direction,
     /// Instrument epic identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// Instrument expiry.
     pub expiry: String,
     /// True if force open is required.
@@ -1708,182 +2808,164 @@ pub struct PositionPostRequest {
     /// True if a guaranteed stop is required.
     pub guaranteed_stop: bool,
     /// Deal level.
-    pub level: Option<f64>,
+    pub level: Option<Number>,
     /// Limit distance.
-    pub limit_distance: Option<f64>,
+    pub limit_distance: Option<Number>,
     /// Limit level.
-    pub limit_level: Option<f64>,
+    pub limit_level: Option<Number>,
     /// Describes the order level model to be used for a position operation.
     pub order_type: OrderType,
     /// Lightstreamer price quote identifier.
     pub quote_id: Option<String>,
     /// Deal size.
-    pub size: f64,
+    pub size: Number,
     /// Stop distance.
-    pub stop_distance: Option<f64>,
+    pub stop_distance: Option<Number>,
     /// Stop level.
-    pub stop_level: Option<f64>,
+    pub stop_level: Option<Number>,
     /// The time in force determines the order fill strategy.
     pub time_in_force: Option<TimeInForce>,
     /// Whether the stop has to be moved towards the current level in case of a favourable trade.
     pub trailing_stop: Option<bool>,
     /// Increment step in pips for the trailing stop.
-    pub trailing_stop_increment: Option<f64>,
+    pub trailing_stop_increment: Option<Number>,
 }
 
 /// Implements the validation of the PositionPostRequest.
 impl ValidateRequest for PositionPostRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: if limit_distance is set, then force_open must be true.
         if self.limit_distance.is_some() && self.force_open != true {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "force_open field must be true when limit_distance is set.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if limit_level is set, then force_open must be true.
         if self.limit_level.is_some() && self.force_open != true {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "force_open field must be true when limit_level is set.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if stop_distance is set, then force_open must be true.
         if self.stop_distance.is_some() && self.force_open != true {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "force_open field must be true when stop_distance is set.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if stop_level is set, then force_open must be true.
         if self.stop_level.is_some() && self.force_open != true {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "force_open field must be true when stop_level is set.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if guaranteed_stop equals true, then set only one of stop_level, stop_distance.
         if self.guaranteed_stop == true && self.stop_level.is_some() && self.stop_distance.is_some()
         {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Only one of stop_level or stop_distance can be set when guaranteed_stop is true.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals LIMIT, then DO NOT set quote_id.
         if self.order_type == OrderType::Limit && self.quote_id.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "quote_id cannot be set when order_type is LIMIT.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals LIMIT, then set level.
         if self.order_type == OrderType::Limit && self.level.is_none() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "level must be set when order_type is LIMIT.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals MARKET, then DO NOT set level, quote_id.
         if self.order_type == OrderType::Market && (self.level.is_some() || self.quote_id.is_some())
         {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Neither level nor quote_id can be set when order_type is MARKET."
                     .to_string(),
-            }));
+            });
         }
 
         // Constraint: if order_type equals QUOTE, then set level, quote_id.
         if self.order_type == OrderType::Quote && (self.level.is_none() || self.quote_id.is_none())
         {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Both level and quote_id must be set when order_type is QUOTE."
                     .to_string(),
-            }));
+            });
         }
 
         // Constraint: if trailing_stop equals false, then DO NOT set trailing_stop_increment.
         if self.trailing_stop == Some(false) && self.trailing_stop_increment.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "trailing_stop_increment cannot be set when trailing_stop is false."
                     .to_string(),
-            }));
+            });
         }
 
         // Constraint: if trailing_stop equals true, then DO NOT set stop_level.
         if self.trailing_stop == Some(true) && self.stop_level.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "stop_level cannot be set when trailing_stop is true.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if trailing_stop equals true, then guaranteed_stop must be false.
         if self.trailing_stop == Some(true) && self.guaranteed_stop != false {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "guaranteed_stop must be false when trailing_stop is true.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if trailing_stop equals true, then set stop_distance, trailing_stop_increment.
         if self.trailing_stop == Some(true)
             && (self.stop_distance.is_none() || self.trailing_stop_increment.is_none())
         {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Both stop_distance and trailing_stop_increment must be set when trailing_stop is true.".to_string(),
-            }));
+            });
         }
 
         // Constraint: set only one of limit_level, limit_distance.
         if self.limit_level.is_some() && self.limit_distance.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Only one of limit_level or limit_distance can be set.".to_string(),
-            }));
+            });
         }
 
         // Constraint: set only one of stop_level, stop_distance.
         if self.stop_level.is_some() && self.stop_distance.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Only one of stop_level or stop_distance can be set.".to_string(),
-            }));
+            });
         }
 
         // Constraint: field currency_code follows pattern(regexp="[A-Z]{3}").
         if !CURRENCY_CODE_REGEX.is_match(&self.currency_code) {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Currency code field is invalid.".to_string(),
-            }));
-        }
-
-        // Constraint: field deal_reference follows pattern(regexp="[A-Za-z0-9_\\-]{1,30}")].
-        if let Some(deal_reference) = &self.deal_reference {
-            if !DEAL_REFERENCE_REGEX.is_match(deal_reference) {
-                return Err(Box::new(ApiError {
-                    message: "Deal reference field is invalid.".to_string(),
-                }));
-            }
-        }
-
-        // Constraint: field epic follows pattern(regexp="[A-Za-z0-9._]{6,30}").
-        if !EPIC_REGEX.is_match(&self.epic) {
-            return Err(Box::new(ApiError {
-                message: "Epic field is invalid.".to_string(),
-            }));
+            });
         }
 
         // Constraint: field expiry follows pattern(regexp="(\\d{2}-)?[A-Z]{3}-\\d{2}|-|DFB").
         if !EXPIRY_REGEX.is_match(&self.expiry) {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Expiry field is invalid.".to_string(),
-            }));
+            });
         }
 
         // Constraint: check precision of size is not more than 12 decimal places.
-        let size_str = format!("{}", self.size);
-        let parts: Vec<&str> = size_str.split('.').collect();
-        if parts.len() == 2 && parts[1].len() > 12 {
-            return Err(Box::new(ApiError {
+        if decimal_places(self.size) > 12 {
+            return Err(IgError::Validation {
                 message: "Size field has more thatn 12 decimal places.".to_string(),
-            }));
+            });
         }
 
         Ok(())
@@ -1891,11 +2973,11 @@ impl ValidateRequest for PositionPostRequest {
 }
 
 /// Response to position open request (POST /positions/otc).
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionPostResponse {
     /// Deal reference of the transaction.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
 }
 
 impl ValidateResponse for PositionPostResponse {}
@@ -1907,112 +2989,303 @@ pub struct PositionPutRequest {
     /// True if a guaranteed stop is required.
     pub guaranteed_stop: Option<bool>,
     /// Limit level.
-    pub limit_level: Option<f64>,
+    pub limit_level: Option<Number>,
     /// Stop level.
-    pub stop_level: Option<f64>,
+    pub stop_level: Option<Number>,
     /// True if Trailing stop is required.
     pub trailing_stop: Option<bool>,
     ///	Trailing stop distance.
-    pub trailing_stop_distance: Option<f64>,
+    pub trailing_stop_distance: Option<Number>,
     /// Trailing stop increment.
-    pub trailing_stop_increment: Option<f64>,
+    pub trailing_stop_increment: Option<Number>,
 }
 
 /// Implement the ValidateRequest trait for PositionPutRequest.
 impl ValidateRequest for PositionPutRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: if guaranteed_stop equals true, then set stop_level.
         if self.guaranteed_stop == Some(true) && self.stop_level.is_none() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "stop_level must be set when guaranteed_stop is true.".to_string(),
-            }));
+            });
+        }
+
+        // Constraint: if guaranteed_stop equals true, then trailing_stop must be false.
+        if self.guaranteed_stop == Some(true) && self.trailing_stop == Some(true) {
+            return Err(IgError::Validation {
+                message: "trailing_stop must be false when guaranteed_stop is true.".to_string(),
+            });
+        }
+
+        // Constraint: if trailing_stop equals false, then DO NOT set trailing_stop_distance, trailing_stop_increment.
+        if self.trailing_stop == Some(false)
+            && (self.trailing_stop_distance.is_some() || self.trailing_stop_increment.is_some())
+        {
+            return Err(IgError::Validation {
+                message: "Neither trailing_stop_distance nor trailing_stop_increment can be set when trailing_stop is false.".to_string(),
+            });
+        }
+
+        // Constraint: if trailing_stop equals true, then guaranteed_stop must be false.
+        if self.trailing_stop == Some(true) && self.guaranteed_stop == Some(true) {
+            return Err(IgError::Validation {
+                message: "guaranteed_stop must be false when trailing_stop is true.".to_string(),
+            });
+        }
+
+        // Constraint: if trailing_stop equals true, then set trailing_stop_distance, trailing_stop_increment, stop_level.
+        if self.trailing_stop == Some(true)
+            && (self.trailing_stop_distance.is_none()
+                || self.trailing_stop_increment.is_none()
+                || self.stop_level.is_none())
+        {
+            return Err(IgError::Validation {
+                message: "All of trailing_stop_distance, trailing_stop_increment, stop_level must be set when trailing_stop is true.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Response to position update request (PUT /positions/otc/{deal_id}).
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionPutResponse {
+    /// Deal reference.
+    pub deal_reference: DealReference,
+}
+
+impl ValidateResponse for PositionPutResponse {}
+
+/// Position data.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionData {
+    /// Size of the contract.
+    pub contract_size: f64,
+    /// True if position is risk controlled.
+    pub controlled_risk: bool,
+    /// Local date the position was opened.
+    pub created_date: LocalDateTime,
+    /// UTC date the position was opened.
+    #[serde(
+        deserialize_with = "deserialize_utc_date_time",
+        rename = "createdDateUTC"
+    )]
+    pub created_date_utc: DateTime<Utc>,
+    /// Position currency ISO code.
+    pub currency: String,
+    /// Deal identifier.
+    pub deal_id: DealId,
+    /// Deal reference.
+    pub deal_reference: DealReference,
+    /// Deal direction.
+    pub direction: Direction,
+    /// Level at which the position was opened.
+    pub level: Number,
+    /// Limit level.
+    pub limit_level: Option<Number>,
+    /// Limited Risk Premium.
+    pub limited_risk_premium: Option<f64>,
+    /// Deal size.
+    pub size: Number,
+    /// Stop level.
+    pub stop_level: Option<Number>,
+    /// Trailing step size.
+    pub trailing_step: Option<Number>,
+    /// Trailing stop distance.
+    pub trailing_stop_distance: Option<Number>,
+}
+
+/// Position data returned by version 1 of the positions endpoints, which has no UTC date and
+/// deal reference.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionDataV1 {
+    /// Size of the contract.
+    pub contract_size: f64,
+    /// True if position is risk controlled.
+    pub controlled_risk: bool,
+    /// Local date the position was opened.
+    pub created_date: LocalDateTime,
+    /// Position currency ISO code.
+    pub currency: String,
+    /// Deal identifier.
+    pub deal_id: DealId,
+    /// Deal size.
+    pub deal_size: Number,
+    /// Deal direction.
+    pub direction: Direction,
+    /// Limit level.
+    pub limit_level: Option<Number>,
+    /// Level at which the position was opened.
+    pub open_level: Number,
+    /// Stop level.
+    pub stop_level: Option<Number>,
+    /// Trailing step size.
+    pub trailing_step: Option<Number>,
+    /// Trailing stop distance.
+    pub trailing_stop_distance: Option<Number>,
+}
+
+/// Open position data. Response to the version 1 GET /positions/{dealId} request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionV1GetResponse {
+    /// Market data.
+    pub market: MarketDataV1,
+    /// Position data.
+    pub position: PositionDataV1,
+}
+
+impl ValidateResponse for PositionV1GetResponse {}
+
+/// Request to open a new position by sending a version 1 POST request to the /positions/otc
+/// endpoint, which has no deal reference, time in force or trailing stop.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionV1PostRequest {
+    /// Currency code.
+    pub currency_code: String,
+    /// Deal direction.
+    pub direction: Direction,
+    /// Instrument epic identifier.
+    pub epic: Epic,
+    /// Instrument expiry.
+    pub expiry: String,
+    /// True if force open is required.
+    pub force_open: bool,
+    /// True if a guaranteed stop is required.
+    pub guaranteed_stop: bool,
+    /// Deal level.
+    pub level: Option<Number>,
+    /// Limit distance.
+    pub limit_distance: Option<Number>,
+    /// Limit level.
+    pub limit_level: Option<Number>,
+    /// Describes the order level model to be used for a position operation.
+    pub order_type: OrderType,
+    /// Lightstreamer price quote identifier.
+    pub quote_id: Option<String>,
+    /// Deal size.
+    pub size: Number,
+    /// Stop distance.
+    pub stop_distance: Option<Number>,
+    /// Stop level.
+    pub stop_level: Option<Number>,
+}
+
+/// Implements the validation of the PositionV1PostRequest.
+impl ValidateRequest for PositionV1PostRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        // Constraint: if a limit or stop is set, then force_open must be true.
+        if (self.limit_distance.is_some()
+            || self.limit_level.is_some()
+            || self.stop_distance.is_some()
+            || self.stop_level.is_some())
+            && !self.force_open
+        {
+            return Err(IgError::Validation {
+                message: "force_open field must be true when a limit or stop is set.".to_string(),
+            });
+        }
+
+        // Constraint: if order_type equals LIMIT, then DO NOT set quote_id.
+        if self.order_type == OrderType::Limit && self.quote_id.is_some() {
+            return Err(IgError::Validation {
+                message: "quote_id cannot be set when order_type is LIMIT.".to_string(),
+            });
+        }
+
+        // Constraint: if order_type equals LIMIT, then set level.
+        if self.order_type == OrderType::Limit && self.level.is_none() {
+            return Err(IgError::Validation {
+                message: "level must be set when order_type is LIMIT.".to_string(),
+            });
+        }
+
+        // Constraint: if order_type equals MARKET, then DO NOT set level, quote_id.
+        if self.order_type == OrderType::Market && (self.level.is_some() || self.quote_id.is_some())
+        {
+            return Err(IgError::Validation {
+                message: "Neither level nor quote_id can be set when order_type is MARKET."
+                    .to_string(),
+            });
+        }
+
+        // Constraint: if order_type equals QUOTE, then set level, quote_id.
+        if self.order_type == OrderType::Quote && (self.level.is_none() || self.quote_id.is_none())
+        {
+            return Err(IgError::Validation {
+                message: "Both level and quote_id must be set when order_type is QUOTE."
+                    .to_string(),
+            });
         }
 
-        // Constraint: if guaranteed_stop equals true, then trailing_stop must be false.
-        if self.guaranteed_stop == Some(true) && self.trailing_stop == Some(true) {
-            return Err(Box::new(ApiError {
-                message: "trailing_stop must be false when guaranteed_stop is true.".to_string(),
-            }));
+        // Constraint: set only one of limit_level, limit_distance.
+        if self.limit_level.is_some() && self.limit_distance.is_some() {
+            return Err(IgError::Validation {
+                message: "Only one of limit_level or limit_distance can be set.".to_string(),
+            });
         }
 
-        // Constraint: if trailing_stop equals false, then DO NOT set trailing_stop_distance, trailing_stop_increment.
-        if self.trailing_stop == Some(false)
-            && (self.trailing_stop_distance.is_some() || self.trailing_stop_increment.is_some())
-        {
-            return Err(Box::new(ApiError {
-                message: "Neither trailing_stop_distance nor trailing_stop_increment can be set when trailing_stop is false.".to_string(),
-            }));
+        // Constraint: set only one of stop_level, stop_distance.
+        if self.stop_level.is_some() && self.stop_distance.is_some() {
+            return Err(IgError::Validation {
+                message: "Only one of stop_level or stop_distance can be set.".to_string(),
+            });
         }
 
-        // Constraint: if trailing_stop equals true, then guaranteed_stop must be false.
-        if self.trailing_stop == Some(true) && self.guaranteed_stop == Some(true) {
-            return Err(Box::new(ApiError {
-                message: "guaranteed_stop must be false when trailing_stop is true.".to_string(),
-            }));
+        // Constraint: field currency_code follows pattern(regexp="[A-Z]{3}").
+        if !CURRENCY_CODE_REGEX.is_match(&self.currency_code) {
+            return Err(IgError::Validation {
+                message: "Currency code field is invalid.".to_string(),
+            });
         }
 
-        // Constraint: if trailing_stop equals true, then set trailing_stop_distance, trailing_stop_increment, stop_level.
-        if self.trailing_stop == Some(true)
-            && (self.trailing_stop_distance.is_none()
-                || self.trailing_stop_increment.is_none()
-                || self.stop_level.is_none())
-        {
-            return Err(Box::new(ApiError {
-                message: "All of trailing_stop_distance, trailing_stop_increment, stop_level must be set when trailing_stop is true.".to_string(),
-            }));
+        // Constraint: field expiry follows pattern(regexp="(\\d{2}-)?[A-Z]{3}-\\d{2}|-|DFB").
+        if !EXPIRY_REGEX.is_match(&self.expiry) {
+            return Err(IgError::Validation {
+                message: "Expiry field is invalid.".to_string(),
+            });
+        }
+
+        // Constraint: check precision of size is not more than 12 decimal places.
+        if decimal_places(self.size) > 12 {
+            return Err(IgError::Validation {
+                message: "Size field has more than 12 decimal places.".to_string(),
+            });
         }
 
         Ok(())
     }
 }
 
-/// Response to position update request (PUT /positions/otc/{deal_id}).
-#[derive(Debug, Default, Deserialize, Serialize)]
+/// Request to update a position by sending a version 1 PUT request to the
+/// /positions/otc/{deal_id} endpoint, which only updates the limit and stop levels.
+#[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct PositionPutResponse {
-    /// Deal reference.
-    pub deal_reference: String,
+pub struct PositionV1PutRequest {
+    /// Limit level.
+    pub limit_level: Option<Number>,
+    /// Stop level.
+    pub stop_level: Option<Number>,
 }
 
-impl ValidateResponse for PositionPutResponse {}
+impl ValidateRequest for PositionV1PutRequest {}
 
-/// Position data.
+/// List of all the positions for the active account. Response to the version 1 GET /positions
+/// request.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct PositionData {
-    /// Size of the contract.
-    pub contract_size: f64,
-    /// True if position is risk controlled.
-    pub controlled_risk: bool,
-    /// Local date the position was opened.
-    pub created_date: String,
-    /// UTC date the position was opened.
-    #[serde(rename = "createdDateUTC")]
-    pub created_date_utc: String,
-    /// Position currency ISO code.
-    pub currency: String,
-    /// Deal identifier.
-    pub deal_id: String,
-    /// Deal reference.
-    pub deal_reference: String,
-    /// Deal direction.
-    pub direction: Direction,
-    /// Level at which the position was opened.
-    pub level: f64,
-    /// Limit level.
-    pub limit_level: Option<f64>,
-    /// Limited Risk Premium.
-    pub limited_risk_premium: Option<f64>,
-    /// Deal size.
-    pub size: f64,
-    /// Stop level.
-    pub stop_level: Option<f64>,
-    /// Trailing step size.
-    pub trailing_step: Option<f64>,
-    /// Trailing stop distance.
-    pub trailing_stop_distance: Option<f64>,
+pub struct PositionsV1GetResponse {
+    /// List of positions.
+    pub positions: Vec<PositionV1GetResponse>,
 }
 
+impl ValidateResponse for PositionsV1GetResponse {}
+
 /// The time in force determines the order fill strategy.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -2021,6 +3294,12 @@ pub enum TimeInForce {
     ExecuteAndEliminate,
     /// Fill or kill.
     FillOrKill,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<TimeInForce, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -2050,17 +3329,17 @@ pub enum SprintMarketExpiryPeriod {
 #[serde(rename_all = "camelCase")]
 pub struct SprintMarketPosition {
     /// Date the position was opened.
-    pub created_date: String,
+    pub created_date: LocalDateTime,
     /// Currency of the payout.
     pub currency: String,
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
     /// Description.
     pub description: String,
     /// Deal direction.
     pub direction: Direction,
     /// Instrument epic identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// Expiry time.
     pub expiry_time: String,
     /// Instrument name.
@@ -2070,9 +3349,9 @@ pub struct SprintMarketPosition {
     /// Payout amount.
     pub payout_amount: f64,
     /// Size.
-    pub size: f64,
+    pub size: Number,
     /// Strike price.
-    pub strike_level: f64,
+    pub strike_level: Number,
 }
 
 /// Request to get the sprint market positions by sending a GET request to the /positions/sprintmarkets endpoint.
@@ -2085,16 +3364,16 @@ pub struct SprintMarketPositionsGetResponse {
 
 /// Validate the sprint market positions response.
 impl ValidateResponse for SprintMarketPositionsGetResponse {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         for sprint_market_position in &self.sprint_market_positions {
             // Constraint: field currency follows pattern(regexp="[A-Z]{3}").
             if !CURRENCY_CODE_REGEX.is_match(&sprint_market_position.currency) {
-                return Err(Box::new(ApiError {
+                return Err(IgError::Validation {
                     message: format!(
                         "Currency code '{}' field is invalid.",
                         sprint_market_position.currency
                     ),
-                }));
+                });
             }
         }
 
@@ -2106,47 +3385,29 @@ impl ValidateResponse for SprintMarketPositionsGetResponse {
 /// the current trade odds and strike level. An indicative payout amount (payout = premium / odds)
 /// can be evaluated by obtaining the binary odds ratio from the market details endpoint prior to
 /// placing an order.
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SprintMarketPositionsPostRequest {
     /// A user-defined reference identifying the submission of the order.
-    pub deal_reference: Option<String>,
+    pub deal_reference: Option<DealReference>,
     /// Deal direction.
     pub direction: Option<Direction>,
     /// Instrument epic identifier.
-    pub epic: String,
+    pub epic: Epic,
     /// Sprint market expiry period.
     pub expiry_period: Option<SprintMarketExpiryPeriod>,
     /// Deal size.
-    pub size: f64,
+    pub size: Number,
 }
 
 /// Validate the sprint market position request.
 impl ValidateRequest for SprintMarketPositionsPostRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        // Constraint: field deal_reference follows pattern(regexp="[A-Za-z0-9_\\-]{1,30}")].
-        if let Some(deal_reference) = &self.deal_reference {
-            if !DEAL_REFERENCE_REGEX.is_match(deal_reference) {
-                return Err(Box::new(ApiError {
-                    message: "Deal reference field is invalid.".to_string(),
-                }));
-            }
-        }
-
-        // Constraint: field epic follows pattern(regexp="[A-Za-z0-9._]{6,30}").
-        if !EPIC_REGEX.is_match(&self.epic) {
-            return Err(Box::new(ApiError {
-                message: "Epic field is invalid.".to_string(),
-            }));
-        }
-
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: check precision of size is not more than 12 decimal places.
-        let size_str = format!("{}", self.size);
-        let parts: Vec<&str> = size_str.split('.').collect();
-        if parts.len() == 2 && parts[1].len() > 12 {
-            return Err(Box::new(ApiError {
+        if decimal_places(self.size) > 12 {
+            return Err(IgError::Validation {
                 message: "Size field has more thatn 12 decimal places.".to_string(),
-            }));
+            });
         }
 
         Ok(())
@@ -2154,11 +3415,11 @@ impl ValidateRequest for SprintMarketPositionsPostRequest {
 }
 
 /// Response to the create sprint market position request.
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SprintMarketPositionsPostResponse {
     /// Deal reference of the transaction.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
 }
 
 impl ValidateResponse for SprintMarketPositionsPostResponse {}
@@ -2173,22 +3434,11 @@ impl ValidateResponse for SprintMarketPositionsPostResponse {}
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountSwitchPutRequest {
-    pub account_id: String,
+    pub account_id: AccountId,
     pub default_account: Option<bool>,
 }
 
-/// Validate the account switch request.
-impl ValidateRequest for AccountSwitchPutRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if !ACCOUNT_ID_REGEX.is_match(&self.account_id) {
-            return Err(Box::new(ApiError {
-                message: "Account ID field is invalid.".to_string(),
-            }));
-        }
-
-        Ok(())
-    }
-}
+impl ValidateRequest for AccountSwitchPutRequest {}
 
 /// Response to the PUT /session request for account switching.
 #[derive(Debug, Deserialize, Serialize)]
@@ -2207,34 +3457,114 @@ impl ValidateResponse for AccountSwitchPutResponse {}
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthenticationPostRequest {
+    /// Whether the password is encrypted with the key returned by GET /session/encryptionKey.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encrypted_password: Option<bool>,
     pub identifier: String,
     pub password: String,
 }
 
 /// Validate the authentication request.
 impl ValidateRequest for AuthenticationPostRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         if !IDENTIFIER_REGEX.is_match(&self.identifier) {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Identifier field is invalid.".to_string(),
-            }));
+            });
         }
 
         if !PASSWORD_REGEX.is_match(&self.password) {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Password field is invalid.".to_string(),
-            }));
+            });
         }
 
         Ok(())
     }
 }
 
+/// Response to the authentication request (POST) when using session_version 1.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationPostResponseV1 {
+    /// Balances of the active account.
+    pub account_info: Balance,
+    /// Type of the active account.
+    pub account_type: AccountType,
+    /// Accounts of the client.
+    pub accounts: Vec<SessionAccount>,
+    /// Client identifier.
+    pub client_id: String,
+    /// Client type, which only version 1 returns.
+    pub client_type: Option<String>,
+    /// Currency ISO code of the active account.
+    pub currency_iso_code: String,
+    /// Currency symbol of the active account.
+    pub currency_symbol: String,
+    /// Active account identifier.
+    pub current_account_id: AccountId,
+    /// True if dealing is enabled for the active account.
+    pub dealing_enabled: bool,
+    /// True if the client has active demo accounts.
+    pub has_active_demo_accounts: bool,
+    /// True if the client has active live accounts.
+    pub has_active_live_accounts: bool,
+    /// Lightstreamer endpoint.
+    pub lightstreamer_endpoint: String,
+    /// Environment the client should be rerouted to, if any.
+    pub rerouting_environment: Option<String>,
+    /// Client account timezone offset relative to UTC, in hours.
+    pub timezone_offset: f64,
+    /// True if trailing stops are enabled for the active account.
+    pub trailing_stops_enabled: bool,
+}
+
+/// Validate the authentication response.
+impl ValidateResponse for AuthenticationPostResponseV1 {}
+
+/// Response to the authentication request (POST) when using session_version 2. It has the
+/// same fields as version 1, without the client type.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationPostResponseV2 {
+    /// Balances of the active account.
+    pub account_info: Balance,
+    /// Type of the active account.
+    pub account_type: AccountType,
+    /// Accounts of the client.
+    pub accounts: Vec<SessionAccount>,
+    /// Client identifier.
+    pub client_id: String,
+    /// Currency ISO code of the active account.
+    pub currency_iso_code: String,
+    /// Currency symbol of the active account.
+    pub currency_symbol: String,
+    /// Active account identifier.
+    pub current_account_id: AccountId,
+    /// True if dealing is enabled for the active account.
+    pub dealing_enabled: bool,
+    /// True if the client has active demo accounts.
+    pub has_active_demo_accounts: bool,
+    /// True if the client has active live accounts.
+    pub has_active_live_accounts: bool,
+    /// Lightstreamer endpoint.
+    pub lightstreamer_endpoint: String,
+    /// Environment the client should be rerouted to, if any.
+    pub rerouting_environment: Option<String>,
+    /// Client account timezone offset relative to UTC, in hours.
+    pub timezone_offset: f64,
+    /// True if trailing stops are enabled for the active account.
+    pub trailing_stops_enabled: bool,
+}
+
+/// Validate the authentication response.
+impl ValidateResponse for AuthenticationPostResponseV2 {}
+
 /// Response to the authentication request (POST) when using session_version 3.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthenticationPostResponseV3 {
-    pub account_id: String,
+    pub account_id: AccountId,
     pub client_id: String,
     pub lightstreamer_endpoint: String,
     pub oauth_token: OauthToken,
@@ -2271,12 +3601,26 @@ pub struct OauthToken {
     pub token_type: String,
 }
 
+/// Account of the client, as listed in the response to the POST /session request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAccount {
+    /// Account identifier.
+    pub account_id: AccountId,
+    /// Account name.
+    pub account_name: String,
+    /// Account type.
+    pub account_type: AccountType,
+    /// True if this is the default account.
+    pub preferred: bool,
+}
+
 /// User's session details response. Response to the GET /session request.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionDetailsGetResponse {
     /// Active account identifier.
-    pub account_id: String,
+    pub account_id: AccountId,
     /// Client identifier.
     pub client_id: String,
     /// Currency.
@@ -2303,9 +3647,96 @@ pub struct SessionEncryptionKeyGetResponse {
     pub time_stamp: u64,
 }
 
+/// Implementation for the SessionEncryptionKeyGetResponse struct.
+impl SessionEncryptionKeyGetResponse {
+    /// Encrypt a password the way IG expects it when encryptedPassword is true: the Base 64 form
+    /// of "password|timeStamp" is RSA-encrypted (PKCS #1 v1.5) with the encryption key, and the
+    /// result is returned in Base 64 format.
+    pub fn encrypt_password(&self, password: &str) -> Result<String, IgError> {
+        let key_der = BASE64_STANDARD
+            .decode(&self.encryption_key)
+            .map_err(|e| IgError::Other {
+                message: format!("Invalid encryption key: {}", e),
+            })?;
+        let public_key =
+            RsaPublicKey::from_public_key_der(&key_der).map_err(|e| IgError::Other {
+                message: format!("Invalid encryption key: {}", e),
+            })?;
+
+        let plain_text = BASE64_STANDARD.encode(format!("{}|{}", password, self.time_stamp));
+        let encrypted = public_key
+            .encrypt(&mut OsRng, Pkcs1v15Encrypt, plain_text.as_bytes())
+            .map_err(|e| IgError::Other {
+                message: format!("Password encryption failed: {}", e),
+            })?;
+
+        Ok(BASE64_STANDARD.encode(encrypted))
+    }
+}
+
 /// Validate the session encryption key response.
 impl ValidateResponse for SessionEncryptionKeyGetResponse {}
 
+/// Session created by the POST /session request, in the shape of the session version used.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum SessionPostResponse {
+    /// Session version 1.
+    V1(AuthenticationPostResponseV1),
+    /// Session version 2.
+    V2(AuthenticationPostResponseV2),
+    /// Session version 3.
+    V3(AuthenticationPostResponseV3),
+}
+
+/// Implementation for the SessionPostResponse enum.
+impl SessionPostResponse {
+    /// Returns the active account identifier.
+    pub fn account_id(&self) -> &AccountId {
+        match self {
+            SessionPostResponse::V1(session) => &session.current_account_id,
+            SessionPostResponse::V2(session) => &session.current_account_id,
+            SessionPostResponse::V3(session) => &session.account_id,
+        }
+    }
+
+    /// Returns the client identifier.
+    pub fn client_id(&self) -> &str {
+        match self {
+            SessionPostResponse::V1(session) => &session.client_id,
+            SessionPostResponse::V2(session) => &session.client_id,
+            SessionPostResponse::V3(session) => &session.client_id,
+        }
+    }
+
+    /// Deserialize and validate the body of a POST /session response of the given version.
+    pub fn from_version(version: usize, value: &Value) -> Result<Self, IgError> {
+        match version {
+            1 => Ok(SessionPostResponse::V1(
+                AuthenticationPostResponseV1::from_value(value)?,
+            )),
+            2 => Ok(SessionPostResponse::V2(
+                AuthenticationPostResponseV2::from_value(value)?,
+            )),
+            3 => Ok(SessionPostResponse::V3(
+                AuthenticationPostResponseV3::from_value(value)?,
+            )),
+            _ => Err(IgError::Other {
+                message: format!("Invalid session version: {}", version),
+            }),
+        }
+    }
+
+    /// Returns the Lightstreamer endpoint.
+    pub fn lightstreamer_endpoint(&self) -> &str {
+        match self {
+            SessionPostResponse::V1(session) => &session.lightstreamer_endpoint,
+            SessionPostResponse::V2(session) => &session.lightstreamer_endpoint,
+            SessionPostResponse::V3(session) => &session.lightstreamer_endpoint,
+        }
+    }
+}
+
 /// Request a new session token by sending a POST request
 /// to the /session/refresh-token endpoint.
 #[derive(Debug, Serialize)]
@@ -2314,36 +3745,211 @@ pub struct SessionRefreshTokenPostRequest {
     pub refresh_token: String,
 }
 
-/// Validate the session refresh token request.
-impl ValidateRequest for SessionRefreshTokenPostRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if self.refresh_token.is_empty() {
-            return Err(Box::new(ApiError {
-                message: "Refresh token field is empty.".to_string(),
-            }));
-        }
-
-        Ok(())
-    }
+/// Validate the session refresh token request.
+impl ValidateRequest for SessionRefreshTokenPostRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        if self.refresh_token.is_empty() {
+            return Err(IgError::Validation {
+                message: "Refresh token field is empty.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Access token response. Response to the POST /session/refresh-token request.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SessionRefreshTokenPostResponse {
+    /// Access token.
+    pub access_token: String,
+    /// Access token expiry in seconds.
+    pub expires_in: String,
+    /// Refresh token.
+    pub refresh_token: String,
+    /// Scope of the access token.
+    pub scope: String,
+    /// Token type.
+    pub token_type: String,
+}
+
+/// Validate the session encryption key response.
+impl ValidateResponse for SessionRefreshTokenPostResponse {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// WATCHLISTS ENDPOINT MODELS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Request to add a market to a watchlist by sending a PUT request to the
+/// /watchlists/{watchlistId} endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddToWatchlist {
+    /// Instrument epic identifier.
+    pub epic: Epic,
+}
+
+impl ValidateRequest for AddToWatchlist {}
+
+/// Request to create a watchlist by sending a POST request to the /watchlists endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWatchlist {
+    /// List of instrument epic identifiers to add to the new watchlist.
+    pub epics: Vec<Epic>,
+    /// Watchlist name.
+    pub name: String,
+}
+
+/// Validate the create watchlist request.
+impl ValidateRequest for CreateWatchlist {
+    fn validate(&self) -> Result<(), IgError> {
+        // Constraint: Size(min=1).
+        if self.name.is_empty() {
+            return Err(IgError::Validation {
+                message: "Name field cannot be empty.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Response to the POST /watchlists request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWatchlistResult {
+    /// Status of the request.
+    pub status: CreateWatchlistStatus,
+    /// Identifier of the new watchlist.
+    pub watchlist_id: String,
+}
+
+impl ValidateResponse for CreateWatchlistResult {}
+
+/// Status of the create watchlist request.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CreateWatchlistStatus {
+    /// The watchlist was created with all the requested instruments.
+    Success,
+    /// The watchlist was created but some of the requested instruments could not be added.
+    SuccessNotAllInstrumentsAdded,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<CreateWatchlistStatus, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Watchlist data.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Watchlist {
+    /// True if this is the default system watchlist.
+    pub default_system_watchlist: bool,
+    /// True if the watchlist can be deleted by the user.
+    pub deleteable: bool,
+    /// True if the watchlist can be altered by the user.
+    pub editable: bool,
+    /// Watchlist identifier.
+    pub id: String,
+    /// Watchlist name.
+    pub name: String,
+}
+
+/// Request to delete an instrument from a watchlist by sending a DELETE request
+/// to the /watchlists/{watchlistId}/{epic} endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistEpicDeleteRequest {
+    /// Instrument epic identifier.
+    pub epic: Epic,
+    /// Watchlist identifier.
+    pub watchlist_id: String,
+}
+
+/// Validate the watchlist epic delete request.
+impl ValidateRequest for WatchlistEpicDeleteRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        if !WATCHLIST_ID_REGEX.is_match(&self.watchlist_id) {
+            return Err(IgError::Validation {
+                message: "Watchlist ID field is invalid.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Request a single watchlist by sending a GET, PUT or DELETE request to the
+/// /watchlists/{watchlistId} endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistRequest {
+    /// Watchlist identifier.
+    pub watchlist_id: String,
+}
+
+/// Validate the watchlist request.
+impl ValidateRequest for WatchlistRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        if !WATCHLIST_ID_REGEX.is_match(&self.watchlist_id) {
+            return Err(IgError::Validation {
+                message: "Watchlist ID field is invalid.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// List of markets in a watchlist. Response to the GET /watchlists/{watchlistId} request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistMarkets {
+    /// Market data.
+    pub markets: Vec<MarketData>,
+}
+
+impl ValidateResponse for WatchlistMarkets {}
+
+/// Status of a watchlist update or delete request.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WatchlistStatus {
+    Success,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<WatchlistStatus, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Response to the PUT /watchlists/{watchlistId}, DELETE /watchlists/{watchlistId} and
+/// DELETE /watchlists/{watchlistId}/{epic} requests.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistStatusResult {
+    /// Status of the request.
+    pub status: WatchlistStatus,
 }
 
-/// Access token response. Response to the POST /session/refresh-token request.
+impl ValidateResponse for WatchlistStatusResult {}
+
+/// List of watchlists belonging to the active account. Response to the GET /watchlists request.
 #[derive(Debug, Deserialize, Serialize)]
-pub struct SessionRefreshTokenPostResponse {
-    /// Access token.
-    pub access_token: String,
-    /// Access token expiry in seconds.
-    pub expires_in: String,
-    /// Refresh token.
-    pub refresh_token: String,
-    /// Scope of the access token.
-    pub scope: String,
-    /// Token type.
-    pub token_type: String,
+#[serde(rename_all = "camelCase")]
+pub struct Watchlists {
+    /// List of watchlists.
+    pub watchlists: Vec<Watchlist>,
 }
 
-/// Validate the session encryption key response.
-impl ValidateResponse for SessionRefreshTokenPostResponse {}
+impl ValidateResponse for Watchlists {}
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 //
@@ -2364,116 +3970,98 @@ pub struct WorkingOrder {
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderData {
     /// Local date and time when the order was created. Format is yyyy/MM/dd kk:mm:ss:SSS.
-    pub created_date: String,
+    pub created_date: LocalDateTime,
     /// Date and time when the order was created.
-    #[serde(rename = "createdDateUTC")]
-    pub created_date_utc: String,
+    #[serde(
+        deserialize_with = "deserialize_utc_date_time",
+        rename = "createdDateUTC"
+    )]
+    pub created_date_utc: DateTime<Utc>,
     /// Currency ISO code.
     pub currency_code: String,
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
     /// Deal direction.
     pub direction: Direction,
     /// True if this is a DMA (Direct Market Access) working order.
     pub dma: Option<bool>,
     /// Instrument epic identifier.
-    pub epic: Option<String>,
+    pub epic: Option<Epic>,
     /// The date and time the working order will be deleted if not triggered till then. Date format is yyyy/MM/dd hh:mm.
-    pub good_till_date: Option<String>,
+    pub good_till_date: Option<LocalDateTime>,
     #[serde(rename = "goodTillDateISO")]
     /// The date and time the working order will be deleted if not triggered till then.
-    pub good_till_date_iso: Option<String>,
+    pub good_till_date_iso: Option<LocalDateTime>,
     /// True if controlled risk.
     pub guaranteed_stop: bool,
     /// Limit distance.
-    pub limit_distance: Option<f64>,
+    pub limit_distance: Option<Number>,
     /// Limited risk premium.
     pub limited_risk_premium: Option<f64>,
     /// Price at which to execute the trade.
-    pub order_level: Option<f64>,
+    pub order_level: Option<Number>,
     /// Order size.
-    pub order_size: Option<f64>,
+    pub order_size: Option<Number>,
     /// Working order type.
     pub order_type: WorkingOrderType,
     /// Stop distance.
-    pub stop_distance: Option<f64>,
+    pub stop_distance: Option<Number>,
     /// Describes the type of time in force for a given order
     pub time_in_force: WorkingOrderTimeInForce,
 }
 
 /// Request to delete a working order by sending a DELETE request to the /workingorders/otc/{dealId} endpoint.
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderDeleteRequest {
     /// Deal identifier.
-    pub deal_id: String,
+    pub deal_id: DealId,
 }
 
-impl ValidateRequest for WorkingOrderDeleteRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if !DEAL_ID_REGEX.is_match(&self.deal_id) {
-            return Err(Box::new(ApiError {
-                message: "Deal ID field is invalid.".to_string(),
-            }));
-        }
-
-        Ok(())
-    }
-}
+impl ValidateRequest for WorkingOrderDeleteRequest {}
 
 /// Response to working order deletion request through the DELETE /workingorders/otc/{dealId} endpoint.
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderDeleteResponse {
     /// Deal reference of the transaction.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
 }
 
-impl ValidateResponse for WorkingOrderDeleteResponse {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if !DEAL_REFERENCE_REGEX.is_match(&self.deal_reference) {
-            return Err(Box::new(ApiError {
-                message: "Deal reference field is invalid.".to_string(),
-            }));
-        }
-
-        Ok(())
-    }
-}
+impl ValidateResponse for WorkingOrderDeleteResponse {}
 
 /// Request to create a new working order by sending a POST request to the /workingorders/otc endpoint.
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderPostRequest {
     /// Currency. Restricted to available instrument currencies.
     pub currency_code: String,
     /// A user-defined reference identifying the submission of the order.
-    pub deal_reference: Option<String>,
+    pub deal_reference: Option<DealReference>,
     /// Deal direction.
     pub direction: Direction,
     /// Instrument epic.
-    pub epic: String,
+    pub epic: Epic,
     /// Expiry.
     pub expiry: String,
     /// Force open.
     pub force_open: Option<bool>,
-    /// Good till date - This accepts two possible formats either yyyy/mm/dd hh:mm:ss in UTC Time
-    /// or Unix Timestamp in milliseconds.
-    pub good_till_date: Option<String>,
+    /// Good till date in UTC time, sent in the yyyy/MM/dd HH:mm:ss format.
+    pub good_till_date: Option<LocalDateTime>,
     /// Guaranteed stop.
     pub guaranteed_stop: bool,
     /// Deal level.
-    pub level: f64,
+    pub level: Number,
     /// Limit distance.
-    pub limit_distance: Option<f64>,
+    pub limit_distance: Option<Number>,
     /// Limit level.
-    pub limit_level: Option<f64>,
+    pub limit_level: Option<Number>,
     /// Order size.
-    pub size: f64,
+    pub size: Number,
     /// Stop distance.
-    pub stop_distance: Option<f64>,
+    pub stop_distance: Option<Number>,
     /// Stop level.
-    pub stop_level: Option<f64>,
+    pub stop_level: Option<Number>,
     /// Time in force.
     pub time_in_force: WorkingOrderTimeInForce,
     /// Working order type.
@@ -2481,63 +4069,45 @@ pub struct WorkingOrderPostRequest {
 }
 
 impl ValidateRequest for WorkingOrderPostRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: field currency_code follows pattern(regexp="[A-Z]{3}").
         if !CURRENCY_CODE_REGEX.is_match(&self.currency_code) {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Currency code field is invalid.".to_string(),
-            }));
-        }
-
-        // Constraint: field deal_reference follows pattern(regexp="[A-Za-z0-9_\\-]{1,30}")].
-        if let Some(deal_reference) = &self.deal_reference {
-            if !DEAL_REFERENCE_REGEX.is_match(deal_reference) {
-                return Err(Box::new(ApiError {
-                    message: "Deal reference field is invalid.".to_string(),
-                }));
-            }
-        }
-
-        // Constraint: field epic follows pattern(regexp="[A-Za-z0-9._]{6,30}").
-        if !EPIC_REGEX.is_match(&self.epic) {
-            return Err(Box::new(ApiError {
-                message: "Epic field is invalid.".to_string(),
-            }));
+            });
         }
 
         // Constraint: field expiry follows pattern(regexp="(\\d{2}-)?[A-Z]{3}-\\d{2}|-|DFB").
         if !EXPIRY_REGEX.is_match(&self.expiry) {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Expiry field is invalid.".to_string(),
-            }));
+            });
         }
 
         // Constraint: check precision of size is not more than 12 decimal places.
-        let size_str = format!("{}", self.size);
-        let parts: Vec<&str> = size_str.split('.').collect();
-        if parts.len() == 2 && parts[1].len() > 12 {
-            return Err(Box::new(ApiError {
+        if decimal_places(self.size) > 12 {
+            return Err(IgError::Validation {
                 message: "Size field has more thatn 12 decimal places.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if guaranteed_stop equals true, then set stop_distance.
         if self.guaranteed_stop == true && self.stop_distance.is_none() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "stop_distance field is required when guaranteed_stop is true."
                     .to_string(),
-            }));
+            });
         }
 
         // Constraint: If time_in_force equals GOOD_TILL_DATE, then set good_till_date field.
         match &self.time_in_force {
             WorkingOrderTimeInForce::GoodTillDate => {
                 if self.good_till_date.is_none() {
-                    return Err(Box::new(ApiError {
+                    return Err(IgError::Validation {
                         message:
                             "good_till_date field is required when time_in_force is GOOD_TILL_DATE."
                                 .to_string(),
-                    }));
+                    });
                 }
             }
             _ => {}
@@ -2545,16 +4115,16 @@ impl ValidateRequest for WorkingOrderPostRequest {
 
         // Constraint: set only one of {limit_level, limit_distance}.
         if self.limit_level.is_some() && self.limit_distance.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Set only one of {limit_level, limit_distance}.".to_string(),
-            }));
+            });
         }
 
         // Constraint: set only one of {stop_level,stop_distance}.
         if self.stop_level.is_some() && self.stop_distance.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Set only one of {stop_level, stop_distance}.".to_string(),
-            }));
+            });
         }
 
         Ok(())
@@ -2562,44 +4132,33 @@ impl ValidateRequest for WorkingOrderPostRequest {
 }
 
 /// Response to working order creation request through the POST /workingorders/otc endpoint.
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderPostResponse {
     /// Deal reference of the transaction.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
 }
 
-impl ValidateResponse for WorkingOrderPostResponse {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if !DEAL_REFERENCE_REGEX.is_match(&self.deal_reference) {
-            return Err(Box::new(ApiError {
-                message: "Deal reference field is invalid.".to_string(),
-            }));
-        }
-
-        Ok(())
-    }
-}
+impl ValidateResponse for WorkingOrderPostResponse {}
 
 /// Request to update a working order by sending a PUT request to the /workingorders/otc/{dealId} endpoint.
 #[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderPutRequest {
-    /// Good till date - This accepts two possible formats either yyyy/mm/dd hh:mm:ss
-    /// in UTC Time or Unix Timestamp in milliseconds.
-    pub good_till_date: Option<String>,
+    /// Good till date in UTC time, sent in the yyyy/MM/dd HH:mm:ss format.
+    pub good_till_date: Option<LocalDateTime>,
     /// True if a guaranteed stop is required.
     pub guaranteed_stop: Option<bool>,
     /// Deal level.
-    pub level: f64,
+    pub level: Number,
     /// Limit distance.
-    pub limit_distance: Option<f64>,
+    pub limit_distance: Option<Number>,
     /// Limit level.
-    pub limit_level: Option<f64>,
+    pub limit_level: Option<Number>,
     /// Stop distance.
-    pub stop_distance: Option<f64>,
+    pub stop_distance: Option<Number>,
     /// Stop level.
-    pub stop_level: Option<f64>,
+    pub stop_level: Option<Number>,
     /// Time in force.
     pub time_in_force: WorkingOrderTimeInForce,
     /// Working order type.
@@ -2607,23 +4166,23 @@ pub struct WorkingOrderPutRequest {
 }
 
 impl ValidateRequest for WorkingOrderPutRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
         // Constraint: if guaranteed_stop equals true, then set stop_level.
         if self.guaranteed_stop == Some(true) && self.stop_level.is_none() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "stop_level must be set when guaranteed_stop is true.".to_string(),
-            }));
+            });
         }
 
         // Constraint: if time_in_force equals GOOD_TILL_DATE, then set good_till_date field.
         match &self.time_in_force {
             WorkingOrderTimeInForce::GoodTillDate => {
                 if self.good_till_date.is_none() {
-                    return Err(Box::new(ApiError {
+                    return Err(IgError::Validation {
                         message:
                             "good_till_date field is required when time_in_force is GOOD_TILL_DATE."
                                 .to_string(),
-                    }));
+                    });
                 }
             }
             _ => {}
@@ -2631,16 +4190,16 @@ impl ValidateRequest for WorkingOrderPutRequest {
 
         // Constraint: set only one of {limit_level, limit_distance}.
         if self.limit_level.is_some() && self.limit_distance.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Set only one of {limit_level, limit_distance}.".to_string(),
-            }));
+            });
         }
 
         // Constraint: set only one of {stop_level, stop_distance}.
         if self.stop_level.is_some() && self.stop_distance.is_some() {
-            return Err(Box::new(ApiError {
+            return Err(IgError::Validation {
                 message: "Set only one of {stop_level, stop_distance}.".to_string(),
-            }));
+            });
         }
 
         Ok(())
@@ -2648,34 +4207,249 @@ impl ValidateRequest for WorkingOrderPutRequest {
 }
 
 /// Response to working order update request through the PUT /workingorders/otc/{dealId} endpoint.
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkingOrderPutResponse {
     /// Deal reference of the transaction.
-    pub deal_reference: String,
+    pub deal_reference: DealReference,
+}
+
+impl ValidateResponse for WorkingOrderPutResponse {}
+
+/// Response to the GET /workingorders request, which returns a list of working orders for the active account.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingOrdersGetResponse {
+    /// List of working orders.
+    pub working_orders: Vec<WorkingOrder>,
+}
+
+impl ValidateResponse for WorkingOrdersGetResponse {}
+
+/// Working order data returned by version 1 of the GET /workingorders endpoint.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingOrderDataV1 {
+    /// Limit level.
+    pub contingent_limit: Option<Number>,
+    /// Stop level.
+    pub contingent_stop: Option<Number>,
+    /// True if controlled risk.
+    pub controlled_risk: bool,
+    /// Local date and time when the order was created.
+    pub created_date: LocalDateTime,
+    /// Currency ISO code.
+    pub currency_code: String,
+    /// Deal identifier.
+    pub deal_id: DealId,
+    /// Deal direction.
+    pub direction: Direction,
+    /// True if this is a DMA (Direct Market Access) working order.
+    pub dma: Option<bool>,
+    /// Instrument epic identifier.
+    pub epic: Epic,
+    /// The date and time the working order will be deleted if not triggered till then, or None
+    /// if it is good till cancelled.
+    #[serde(default, deserialize_with = "deserialize_good_till")]
+    pub good_till: Option<LocalDateTime>,
+    /// Price at which to execute the trade.
+    pub level: Number,
+    /// Working order request type.
+    pub request_type: WorkingOrderV1RequestType,
+    /// Order size.
+    pub size: Number,
+    /// Trailing stop distance.
+    pub trailing_stop_distance: Option<Number>,
+    /// Trailing stop increment.
+    pub trailing_stop_increment: Option<Number>,
+    /// Trailing trigger distance.
+    pub trailing_trigger_distance: Option<Number>,
+    /// Trailing trigger increment.
+    pub trailing_trigger_increment: Option<Number>,
+}
+
+/// Working order returned by version 1 of the GET /workingorders endpoint.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingOrderV1 {
+    /// Market data.
+    pub market_data: MarketDataV1,
+    /// Working order data.
+    pub working_order_data: WorkingOrderDataV1,
+}
+
+/// Request to create a new working order by sending a version 1 POST request to the
+/// /workingorders/otc endpoint, which has no deal reference or force open.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingOrderV1PostRequest {
+    /// Currency. Restricted to available instrument currencies.
+    pub currency_code: String,
+    /// Deal direction.
+    pub direction: Direction,
+    /// Instrument epic.
+    pub epic: Epic,
+    /// Expiry.
+    pub expiry: String,
+    /// Good till date in UTC time, sent in the yyyy/MM/dd HH:mm:ss format.
+    pub good_till_date: Option<LocalDateTime>,
+    /// Guaranteed stop.
+    pub guaranteed_stop: bool,
+    /// Deal level.
+    pub level: Number,
+    /// Limit distance.
+    pub limit_distance: Option<Number>,
+    /// Limit level.
+    pub limit_level: Option<Number>,
+    /// Order size.
+    pub size: Number,
+    /// Stop distance.
+    pub stop_distance: Option<Number>,
+    /// Stop level.
+    pub stop_level: Option<Number>,
+    /// Time in force.
+    pub time_in_force: WorkingOrderTimeInForce,
+    /// Working order type.
+    pub r#type: WorkingOrderType,
+}
+
+impl ValidateRequest for WorkingOrderV1PostRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        // Constraint: field currency_code follows pattern(regexp="[A-Z]{3}").
+        if !CURRENCY_CODE_REGEX.is_match(&self.currency_code) {
+            return Err(IgError::Validation {
+                message: "Currency code field is invalid.".to_string(),
+            });
+        }
+
+        // Constraint: field expiry follows pattern(regexp="(\\d{2}-)?[A-Z]{3}-\\d{2}|-|DFB").
+        if !EXPIRY_REGEX.is_match(&self.expiry) {
+            return Err(IgError::Validation {
+                message: "Expiry field is invalid.".to_string(),
+            });
+        }
+
+        // Constraint: check precision of size is not more than 12 decimal places.
+        if decimal_places(self.size) > 12 {
+            return Err(IgError::Validation {
+                message: "Size field has more than 12 decimal places.".to_string(),
+            });
+        }
+
+        // Constraint: if guaranteed_stop equals true, then set stop_distance.
+        if self.guaranteed_stop && self.stop_distance.is_none() {
+            return Err(IgError::Validation {
+                message: "stop_distance field is required when guaranteed_stop is true."
+                    .to_string(),
+            });
+        }
+
+        // Constraint: If time_in_force equals GOOD_TILL_DATE, then set good_till_date field.
+        if matches!(self.time_in_force, WorkingOrderTimeInForce::GoodTillDate)
+            && self.good_till_date.is_none()
+        {
+            return Err(IgError::Validation {
+                message: "good_till_date field is required when time_in_force is GOOD_TILL_DATE."
+                    .to_string(),
+            });
+        }
+
+        // Constraint: set only one of {limit_level, limit_distance}.
+        if self.limit_level.is_some() && self.limit_distance.is_some() {
+            return Err(IgError::Validation {
+                message: "Set only one of {limit_level, limit_distance}.".to_string(),
+            });
+        }
+
+        // Constraint: set only one of {stop_level, stop_distance}.
+        if self.stop_level.is_some() && self.stop_distance.is_some() {
+            return Err(IgError::Validation {
+                message: "Set only one of {stop_level, stop_distance}.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Request to update a working order by sending a version 1 PUT request to the
+/// /workingorders/otc/{dealId} endpoint, which has no guaranteed stop.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingOrderV1PutRequest {
+    /// Good till date in UTC time, sent in the yyyy/MM/dd HH:mm:ss format.
+    pub good_till_date: Option<LocalDateTime>,
+    /// Deal level.
+    pub level: Number,
+    /// Limit distance.
+    pub limit_distance: Option<Number>,
+    /// Limit level.
+    pub limit_level: Option<Number>,
+    /// Stop distance.
+    pub stop_distance: Option<Number>,
+    /// Stop level.
+    pub stop_level: Option<Number>,
+    /// Time in force.
+    pub time_in_force: WorkingOrderTimeInForce,
+    /// Working order type.
+    pub r#type: WorkingOrderType,
 }
 
-impl ValidateResponse for WorkingOrderPutResponse {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
-        if !DEAL_REFERENCE_REGEX.is_match(&self.deal_reference) {
-            return Err(Box::new(ApiError {
-                message: "Deal reference field is invalid.".to_string(),
-            }));
+impl ValidateRequest for WorkingOrderV1PutRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        // Constraint: if time_in_force equals GOOD_TILL_DATE, then set good_till_date field.
+        if matches!(self.time_in_force, WorkingOrderTimeInForce::GoodTillDate)
+            && self.good_till_date.is_none()
+        {
+            return Err(IgError::Validation {
+                message: "good_till_date field is required when time_in_force is GOOD_TILL_DATE."
+                    .to_string(),
+            });
+        }
+
+        // Constraint: set only one of {limit_level, limit_distance}.
+        if self.limit_level.is_some() && self.limit_distance.is_some() {
+            return Err(IgError::Validation {
+                message: "Set only one of {limit_level, limit_distance}.".to_string(),
+            });
+        }
+
+        // Constraint: set only one of {stop_level, stop_distance}.
+        if self.stop_level.is_some() && self.stop_distance.is_some() {
+            return Err(IgError::Validation {
+                message: "Set only one of {stop_level, stop_distance}.".to_string(),
+            });
         }
 
         Ok(())
     }
 }
 
-/// Response to the GET /workingorders request, which returns a list of working orders for the active account.
+/// Request type of a version 1 working order.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WorkingOrderV1RequestType {
+    /// Limit order.
+    LimitOrder,
+    /// Stop order.
+    StopOrder,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<WorkingOrderV1RequestType, _>",
+        untagged
+    )]
+    Other(String),
+}
+
+/// Response to the version 1 GET /workingorders request.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct WorkingOrdersGetResponse {
+pub struct WorkingOrdersV1GetResponse {
     /// List of working orders.
-    pub working_orders: Vec<WorkingOrder>,
+    pub working_orders: Vec<WorkingOrderV1>,
 }
 
-impl ValidateResponse for WorkingOrdersGetResponse {}
+impl ValidateResponse for WorkingOrdersV1GetResponse {}
 
 /// Working order type.
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -2686,6 +4460,12 @@ pub enum WorkingOrderType {
     #[default]
     /// Stop working order.
     Stop,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<WorkingOrderType, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 /// Describes the type of time in force for a given order.
@@ -2697,6 +4477,12 @@ pub enum WorkingOrderTimeInForce {
     GoodTillCancelled,
     /// Good until specified date.
     GoodTillDate,
+    /// A value not known to this version of the library.
+    #[serde(
+        deserialize_with = "deserialize_other_variant::<WorkingOrderTimeInForce, _>",
+        untagged
+    )]
+    Other(String),
 }
 
 #[derive(Debug, Default, Serialize)]
@@ -2711,14 +4497,14 @@ pub struct PricesGetRequest {
 }
 
 impl ValidateRequest for PricesGetRequest {
-    fn validate(&self) -> Result<(), Box<dyn Error>> {
+    fn validate(&self) -> Result<(), IgError> {
 
         match (self.from, self.to) {
             (Some(from), Some(to)) => {
                 if to < from {
-                    return Err(Box::new(ApiError {
+                    return Err(IgError::Validation {
                         message: "End date cannot be before start date".to_string(),
-                    }));
+                    });
                 }
             }
             _ => {}
@@ -2737,3 +4523,352 @@ pub struct PricesGetResponse {
 }
 
 impl ValidateResponse for PricesGetResponse {}
+
+/// Prices requested from version 2 of the prices endpoints, which is sent as part of the
+/// request path.
+#[derive(Debug)]
+pub enum PriceRange {
+    /// Range of dates and times, both included.
+    DateRange {
+        /// Start date and time.
+        from: NaiveDateTime,
+        /// End date and time.
+        to: NaiveDateTime,
+    },
+    /// The last number of data points.
+    NumPoints(u32),
+}
+
+/// Implementation for the PriceRange enum.
+impl PriceRange {
+    /// Returns the path segments of the range: the number of data points, or the start and end
+    /// dates formatted as yyyy-MM-dd HH:mm:ss.
+    pub fn path(&self) -> String {
+        match self {
+            PriceRange::DateRange { from, to } => format!(
+                "{}/{}",
+                from.format("%Y-%m-%d %H:%M:%S"),
+                to.format("%Y-%m-%d %H:%M:%S")
+            ),
+            PriceRange::NumPoints(num_points) => num_points.to_string(),
+        }
+    }
+
+    /// Validate the range.
+    pub fn validate(&self) -> Result<(), IgError> {
+        match self {
+            PriceRange::DateRange { from, to } => {
+                if to < from {
+                    return Err(IgError::Validation {
+                        message: "End date cannot be before start date".to_string(),
+                    });
+                }
+            }
+            PriceRange::NumPoints(num_points) => {
+                if *num_points == 0 {
+                    return Err(IgError::Validation {
+                        message: "The number of data points cannot be zero.".to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Historical price returned by versions 1 and 2 of the prices endpoints, which has no UTC
+/// snapshot time.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceV1 {
+    /// Close price.
+    pub close_price: AskBid,
+    /// High price.
+    pub high_price: AskBid,
+    /// Last traded volume.
+    pub last_traded_volume: Option<f64>,
+    /// Low price.
+    pub low_price: AskBid,
+    /// Open price.
+    pub open_price: AskBid,
+    /// Local snapshot time.
+    pub snapshot_time: LocalDateTime,
+}
+
+/// Request to version 1 of the GET /prices/{epic}/{resolution} endpoint, which takes the dates
+/// formatted as yyyy:MM:dd-HH:mm:ss.
+#[derive(Debug)]
+pub struct PricesV1GetRequest {
+    /// End date and time.
+    pub end_date: NaiveDateTime,
+    /// Start date and time.
+    pub start_date: NaiveDateTime,
+}
+
+/// Implement the Serialize trait for the PricesV1GetRequest struct.
+impl Serialize for PricesV1GetRequest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PricesV1GetRequest", 2)?;
+        state.serialize_field(
+            "startdate",
+            &self.start_date.format("%Y:%m:%d-%H:%M:%S").to_string(),
+        )?;
+        state.serialize_field(
+            "enddate",
+            &self.end_date.format("%Y:%m:%d-%H:%M:%S").to_string(),
+        )?;
+        state.end()
+    }
+}
+
+impl ValidateRequest for PricesV1GetRequest {
+    fn validate(&self) -> Result<(), IgError> {
+        if self.end_date < self.start_date {
+            return Err(IgError::Validation {
+                message: "End date cannot be before start date".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Response to versions 1 and 2 of the prices requests.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricesV1GetResponse {
+    /// Historical data allowance.
+    pub allowance: PriceAllowance,
+    /// Instrument type.
+    pub instrument_type: InstrumentType,
+    /// Historical prices.
+    pub prices: Vec<PriceV1>,
+}
+
+impl ValidateResponse for PricesV1GetResponse {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs8::EncodePublicKey;
+    use rsa::RsaPrivateKey;
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_numbers_serialize_exactly() {
+        let size = "0.1".parse::<Number>().unwrap() + "0.2".parse::<Number>().unwrap();
+        let request = PositionDeleteRequest {
+            size,
+            ..Default::default()
+        };
+        let body = serde_json::to_string(&request).unwrap();
+        assert!(body.contains(r#""size":0.3"#));
+
+        let prices: AskBid =
+            serde_json::from_str(r#"{"ask": 1.12345678901234567, "bid": 0.1}"#).unwrap();
+        assert_eq!(prices.ask.to_string(), "1.12345678901234567");
+        assert_eq!(prices.bid.to_string(), "0.1");
+    }
+
+    #[test]
+    fn transaction_values_are_parsed() {
+        let number = |value: &str| value.parse::<Number>().unwrap();
+        let transaction: Transaction = serde_json::from_value(serde_json::json!({
+            "cashTransaction": false,
+            "closeLevel": "1,234.5",
+            "currency": "E",
+            "date": "2021-09-10T10:30:00",
+            "dateUtc": "2021-09-10T09:30:00",
+            "instrumentName": "Germany 40",
+            "openDateUtc": "2021-09-10T08:30:00",
+            "openLevel": "-",
+            "period": "-",
+            "profitAndLoss": "E-1,012.50",
+            "reference": "ABC",
+            "size": "-1.5",
+            "transactionType": "DEAL",
+        }))
+        .unwrap();
+
+        assert_eq!(transaction.close_level_value(), Some(number("1234.5")));
+        assert_eq!(transaction.open_level_value(), None);
+        assert_eq!(transaction.size_value(), Some(number("-1.5")));
+        assert_eq!(
+            transaction.profit_and_loss_amount("EUR"),
+            Some(Money {
+                amount: number("-1012.50"),
+                currency: "EUR".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_formatted_number("-USD 12.5"),
+            Some((number("-12.5"), "USD"))
+        );
+        assert_eq!(iso_currency(&["E", "GBP"], "EUR"), "GBP");
+    }
+
+    #[test]
+    fn identifiers_are_validated() {
+        let epic: Epic = "IX.D.DAX.IFMM.IP".parse().unwrap();
+        assert_eq!(epic, "IX.D.DAX.IFMM.IP");
+        assert_eq!(
+            serde_json::to_string(&epic).unwrap(),
+            r#""IX.D.DAX.IFMM.IP""#
+        );
+        assert!("not an epic".parse::<Epic>().is_err());
+        assert!(serde_json::from_str::<DealId>(r#""DIAAAA/1""#).is_err());
+
+        let deal_reference: DealReference = serde_json::from_str(r#""ABC_123""#).unwrap();
+        assert_eq!(deal_reference.to_string(), "ABC_123");
+        assert!(AccountId::try_from("").is_err());
+    }
+
+    #[test]
+    fn unknown_enum_values_are_kept() {
+        let reasons: Vec<DealReason> =
+            serde_json::from_str(r#"["INSUFFICIENT_FUNDS", "SOME_NEW_REASON"]"#).unwrap();
+        assert!(matches!(reasons[0], DealReason::InsufficientFunds));
+        assert!(matches!(&reasons[1], DealReason::Other(reason) if reason == "SOME_NEW_REASON"));
+        assert_eq!(
+            serde_json::to_string(&reasons).unwrap(),
+            r#"["INSUFFICIENT_FUNDS","SOME_NEW_REASON"]"#
+        );
+        assert!(serde_json::from_str::<DealReason>("1").is_err());
+    }
+
+    #[test]
+    fn ig_dates_and_times_are_parsed() {
+        let expected = NaiveDate::from_ymd_opt(2021, 9, 10)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap();
+        for value in [
+            "2021-09-10T10:30:00",
+            "2021-09-10T10:30",
+            "2021/09/10 10:30:00:000",
+            "2021/09/10 10:30:00",
+            "2021/09/10 10:30",
+            "2021-09-10T11:30:00+01:00",
+        ] {
+            assert_eq!(parse_date_time(value).unwrap(), expected, "{}", value);
+        }
+        assert!(parse_date_time("10/09/21").is_err());
+
+        let price: Price = serde_json::from_value(serde_json::json!({
+            "closePrice": { "ask": 1, "bid": 1 },
+            "highPrice": { "ask": 1, "bid": 1 },
+            "lastTradedVolume": 0,
+            "lowPrice": { "ask": 1, "bid": 1 },
+            "openPrice": { "ask": 1, "bid": 1 },
+            "snapshotTime": "2021/09/10 11:30:00",
+            "snapshotTimeUTC": "2021-09-10T10:30:00",
+        }))
+        .unwrap();
+        assert_eq!(price.snapshot_time.raw, "2021/09/10 11:30:00");
+        assert_eq!(price.snapshot_time_utc, expected.and_utc());
+    }
+
+    #[test]
+    fn ig_dates_are_parsed() {
+        let expected = NaiveDate::from_ymd_opt(2021, 9, 10).unwrap();
+        for value in [
+            "10/09/21",
+            "2021-09-10",
+            "2021/09/10",
+            "2021-09-10T10:30:00",
+        ] {
+            assert_eq!(parse_date(value).unwrap(), expected, "{}", value);
+        }
+        assert!(parse_date("2021.09.10").is_err());
+
+        let date: LocalDate = serde_json::from_str(r#""10/09/21""#).unwrap();
+        assert_eq!(date.date, expected);
+        assert_eq!(serde_json::to_string(&date).unwrap(), r#""10/09/21""#);
+    }
+
+    #[test]
+    fn ig_times_are_parsed() {
+        let time: NaiveTime = serde_json::from_str(r#""10:30:15""#).unwrap();
+        assert_eq!(time, NaiveTime::from_hms_opt(10, 30, 15).unwrap());
+
+        let time: NaiveTime = serde_json::from_str(r#""10:30""#).unwrap();
+        assert_eq!(time, NaiveTime::from_hms_opt(10, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn good_till_date_is_sent_in_ig_format() {
+        let date_time = NaiveDate::from_ymd_opt(2021, 9, 10)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap();
+        let good_till_date = LocalDateTime::from(date_time);
+        assert_eq!(
+            serde_json::to_string(&good_till_date).unwrap(),
+            r#""2021/09/10 10:30:00""#
+        );
+        assert_eq!(
+            serde_json::from_str::<LocalDateTime>(r#""2021/09/10 10:30""#)
+                .unwrap()
+                .date_time,
+            date_time
+        );
+
+        let good_till = |value: Value| {
+            deserialize_good_till(value)
+                .unwrap()
+                .map(|good_till| good_till.date_time)
+        };
+        assert_eq!(good_till(Value::from("2021/09/10 10:30")), Some(date_time));
+        assert_eq!(good_till(Value::from("GTC")), None);
+        assert_eq!(good_till(Value::Null), None);
+    }
+
+    #[test]
+    fn numbers_round_to_decimal_places_and_steps() {
+        let number = |value: &str| value.parse::<Number>().unwrap();
+        let min_step_distance = DealingRule {
+            unit: RuleUnit::Points,
+            value: number("0.1"),
+        };
+
+        assert_eq!(
+            min_step_distance.round_to_step(number("0.1") + number("0.2")),
+            number("0.3")
+        );
+        assert_eq!(
+            min_step_distance.round_to_step(number("0.26")),
+            number("0.3")
+        );
+        assert_eq!(round_to_decimal_places(number("1.2345"), 2), number("1.23"));
+        assert_eq!(decimal_places(number("1.2500")), 2);
+        assert_eq!(decimal_places(number("3")), 0);
+    }
+
+    #[test]
+    fn session_encryption_key_encrypts_password() {
+        let private_key = RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let key_der = RsaPublicKey::from(&private_key)
+            .to_public_key_der()
+            .unwrap();
+        let encryption_key = SessionEncryptionKeyGetResponse {
+            encryption_key: BASE64_STANDARD.encode(key_der.as_bytes()),
+            time_stamp: 1_700_000_000_000,
+        };
+
+        let encrypted = encryption_key.encrypt_password("secret").unwrap();
+        let decrypted = private_key
+            .decrypt(Pkcs1v15Encrypt, &BASE64_STANDARD.decode(encrypted).unwrap())
+            .unwrap();
+        assert_eq!(
+            BASE64_STANDARD.decode(decrypted).unwrap(),
+            b"secret|1700000000000"
+        );
+
+        let invalid_key = SessionEncryptionKeyGetResponse {
+            encryption_key: "not a key".to_string(),
+            time_stamp: 0,
+        };
+        assert!(invalid_key.encrypt_password("secret").is_err());
+    }
+}
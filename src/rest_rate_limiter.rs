@@ -0,0 +1,305 @@
+use crate::common::*;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Length of the window of the per minute allowances.
+const MINUTE: Duration = Duration::from_secs(60);
+/// Length of the window of the per week allowances.
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Struct to represent the remaining budget of each allowance tracked by the rate limiter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitBudget {
+    /// Remaining historical price data points in the current week.
+    pub historical_data_points: u32,
+    /// Remaining non-trading requests in the current minute.
+    pub non_trading_requests: u32,
+    /// Remaining trading requests in the current minute.
+    pub trading_requests: u32,
+}
+
+/// Client-side rate limiter tracking IG's trading, non-trading and historical data allowances.
+/// The budget is shared by all the clones of a rate limiter.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    /// What to do with a request exceeding an allowance.
+    mode: RateLimitMode,
+    /// Usage of each allowance.
+    windows: Arc<Mutex<Windows>>,
+}
+
+/// Implementation for the RateLimiter struct.
+impl RateLimiter {
+    /// Wait until the allowances used by a request have enough budget and take it. If the
+    /// rate limiter is in Reject mode, fail with IgError::RateLimited instead of waiting.
+    ///
+    /// Historical data requests only check that there are data points left, as their cost
+    /// is only known once the response is received. See RateLimiter::record_data_points.
+    pub async fn acquire(&self, bucket: RateLimitBucket) -> Result<(), IgError> {
+        loop {
+            let retry_after = match self.lock().try_acquire(bucket, Instant::now()) {
+                Ok(()) => return Ok(()),
+                Err(retry_after) => retry_after,
+            };
+
+            match self.mode {
+                RateLimitMode::Queue => tokio::time::sleep(retry_after).await,
+                RateLimitMode::Reject => {
+                    return Err(IgError::RateLimited {
+                        bucket,
+                        retry_after,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Returns the remaining budget of each allowance.
+    pub fn budget(&self) -> RateLimitBudget {
+        let now = Instant::now();
+        let mut windows = self.lock();
+
+        RateLimitBudget {
+            historical_data_points: windows.historical_data_points.remaining(now),
+            non_trading_requests: windows.non_trading_requests.remaining(now),
+            trading_requests: windows.trading_requests.remaining(now),
+        }
+    }
+
+    /// Classify a request into the allowance it counts against. Deal requests on the
+    /// positions/otc and workingorders/otc endpoints are trading requests, GET requests on the
+    /// prices endpoint consume historical data points, and everything else is non-trading.
+    pub fn bucket_for(http_method: &str, path: &str) -> RateLimitBucket {
+        let is_get = http_method.eq_ignore_ascii_case("GET");
+
+        if !is_get && (path.starts_with("positions/otc") || path.starts_with("workingorders/otc")) {
+            RateLimitBucket::TradingRequests
+        } else if is_get && path.starts_with("prices") {
+            RateLimitBucket::HistoricalDataPoints
+        } else {
+            RateLimitBucket::NonTradingRequests
+        }
+    }
+
+    /// Acquire the lock on the allowances usage. A poisoned lock is recovered, as the usage
+    /// is always left consistent.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Windows> {
+        self.windows.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Create a new RateLimiter instance based on the provided configuration.
+    pub fn new(config: &RateLimiterConfig) -> Self {
+        Self {
+            mode: config.mode.clone(),
+            windows: Arc::new(Mutex::new(Windows {
+                historical_data_points: SlidingWindow::new(
+                    config.historical_data_points_per_week,
+                    WEEK,
+                ),
+                non_trading_requests: SlidingWindow::new(
+                    config.non_trading_requests_per_minute,
+                    MINUTE,
+                ),
+                trading_requests: SlidingWindow::new(config.trading_requests_per_minute, MINUTE),
+            })),
+        }
+    }
+
+    /// Record the historical price data points returned by a prices request.
+    pub fn record_data_points(&self, data_points: u32) {
+        self.lock()
+            .historical_data_points
+            .record(data_points, Instant::now());
+    }
+}
+
+/// Usage of each allowance tracked by the rate limiter.
+#[derive(Debug)]
+struct Windows {
+    historical_data_points: SlidingWindow,
+    non_trading_requests: SlidingWindow,
+    trading_requests: SlidingWindow,
+}
+
+/// Implementation for the Windows struct.
+impl Windows {
+    /// Take the budget used by a request, or return the time to wait until there is enough.
+    fn try_acquire(&mut self, bucket: RateLimitBucket, now: Instant) -> Result<(), Duration> {
+        match bucket {
+            RateLimitBucket::HistoricalDataPoints => {
+                // Historical data requests count as non-trading requests as well.
+                self.historical_data_points.wait_time(1, now)?;
+                self.non_trading_requests.wait_time(1, now)?;
+                self.non_trading_requests.record(1, now);
+            }
+            RateLimitBucket::NonTradingRequests => {
+                self.non_trading_requests.wait_time(1, now)?;
+                self.non_trading_requests.record(1, now);
+            }
+            RateLimitBucket::TradingRequests => {
+                self.trading_requests.wait_time(1, now)?;
+                self.trading_requests.record(1, now);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Allowance usage over a sliding window of time.
+#[derive(Debug)]
+struct SlidingWindow {
+    /// Maximum usage within the window.
+    limit: u32,
+    /// Usage recorded within the window, oldest first.
+    usage: VecDeque<(Instant, u32)>,
+    /// Length of the window.
+    window: Duration,
+}
+
+/// Implementation for the SlidingWindow struct.
+impl SlidingWindow {
+    /// Create a new SlidingWindow instance.
+    fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            usage: VecDeque::new(),
+            window,
+        }
+    }
+
+    /// Forget the usage that is older than the window.
+    fn prune(&mut self, now: Instant) {
+        while let Some((instant, _)) = self.usage.front() {
+            if now.duration_since(*instant) < self.window {
+                break;
+            }
+            self.usage.pop_front();
+        }
+    }
+
+    /// Record usage at the given instant.
+    fn record(&mut self, cost: u32, now: Instant) {
+        self.usage.push_back((now, cost));
+    }
+
+    /// Returns the remaining budget within the window.
+    fn remaining(&mut self, now: Instant) -> u32 {
+        self.prune(now);
+        self.limit.saturating_sub(self.used())
+    }
+
+    /// Total usage recorded within the window.
+    fn used(&self) -> u32 {
+        self.usage
+            .iter()
+            .fold(0u32, |used, (_, cost)| used.saturating_add(*cost))
+    }
+
+    /// Returns Ok if there is budget for the given cost, or the time to wait until there is.
+    fn wait_time(&mut self, cost: u32, now: Instant) -> Result<(), Duration> {
+        self.prune(now);
+
+        let mut used = self.used();
+        if used.saturating_add(cost) <= self.limit {
+            return Ok(());
+        }
+
+        // Find the instant at which enough usage leaves the window.
+        for (instant, recorded_cost) in &self.usage {
+            used = used.saturating_sub(*recorded_cost);
+            if used.saturating_add(cost) <= self.limit {
+                return Err((*instant + self.window).saturating_duration_since(now));
+            }
+        }
+
+        // The cost exceeds the limit, so wait for a whole window.
+        Err(self.window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_for_works() {
+        assert_eq!(
+            RateLimiter::bucket_for("POST", "positions/otc"),
+            RateLimitBucket::TradingRequests
+        );
+        assert_eq!(
+            RateLimiter::bucket_for("PUT", "workingorders/otc/DIAAAAA"),
+            RateLimitBucket::TradingRequests
+        );
+        assert_eq!(
+            RateLimiter::bucket_for("GET", "positions"),
+            RateLimitBucket::NonTradingRequests
+        );
+        assert_eq!(
+            RateLimiter::bucket_for("GET", "prices/CS.D.EURUSD.MINI.IP"),
+            RateLimitBucket::HistoricalDataPoints
+        );
+        assert_eq!(
+            RateLimiter::bucket_for("GET", "markets?epics=CS.D.EURUSD.MINI.IP"),
+            RateLimitBucket::NonTradingRequests
+        );
+    }
+
+    #[test]
+    fn sliding_window_works() {
+        let start = Instant::now();
+        let mut window = SlidingWindow::new(2, MINUTE);
+
+        assert_eq!(window.wait_time(1, start), Ok(()));
+        window.record(1, start);
+        window.record(1, start + Duration::from_secs(10));
+        assert_eq!(window.remaining(start + Duration::from_secs(10)), 0);
+
+        // The first request leaves the window 60 seconds after it was recorded.
+        assert_eq!(
+            window.wait_time(1, start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert_eq!(window.wait_time(1, start + MINUTE), Ok(()));
+        assert_eq!(window.remaining(start + MINUTE), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_rejects_requests_over_the_allowance() {
+        let rate_limiter = RateLimiter::new(&RateLimiterConfig {
+            mode: RateLimitMode::Reject,
+            trading_requests_per_minute: 1,
+            ..RateLimiterConfig::default()
+        });
+
+        assert!(rate_limiter
+            .acquire(RateLimitBucket::TradingRequests)
+            .await
+            .is_ok());
+        assert!(matches!(
+            rate_limiter.acquire(RateLimitBucket::TradingRequests).await,
+            Err(IgError::RateLimited {
+                bucket: RateLimitBucket::TradingRequests,
+                ..
+            })
+        ));
+
+        rate_limiter.record_data_points(9_999);
+        assert!(rate_limiter
+            .acquire(RateLimitBucket::HistoricalDataPoints)
+            .await
+            .is_ok());
+        rate_limiter.record_data_points(1);
+
+        let budget = rate_limiter.budget();
+        assert_eq!(budget.trading_requests, 0);
+        assert_eq!(budget.non_trading_requests, 59);
+        assert_eq!(budget.historical_data_points, 0);
+        assert!(rate_limiter
+            .acquire(RateLimitBucket::HistoricalDataPoints)
+            .await
+            .is_err());
+    }
+}
@@ -19,11 +19,6 @@ pub static DEAL_REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| {
 pub static EPIC_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^[A-Za-z0-9._]{6,30}$").expect("Invalid regex pattern EPIC_REGEX!"));
 
-pub static EPICS_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^([A-Z]+(?:\.[A-Z]+)*(?:,[A-Z]+(?:\.[A-Z]+)*)*)$")
-        .expect("Invalid regex pattern EPICS_REGEX!")
-});
-
 pub static EXPIRY_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(^\d{2}-)?[A-Z]{3}-\d{2}$|-|DFB").expect("Invalid regex pattern EXPIRY_REGEX!")
 });
@@ -32,5 +27,17 @@ pub static IDENTIFIER_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^[A-Za-z0-9\-_]{1,30}$").expect("Invalid regex pattern IDENTIFIER_REGEX!")
 });
 
+pub static MARKET_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z0-9._\-]{1,30}$").expect("Invalid regex pattern MARKET_ID_REGEX!")
+});
+
+pub static NODE_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z0-9_\-]{1,30}$").expect("Invalid regex pattern NODE_ID_REGEX!")
+});
+
 pub static PASSWORD_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^.{1,350}$").expect("Invalid regex pattern PASSWORD_REGEX!"));
+
+pub static WATCHLIST_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z0-9 _\-]{1,100}$").expect("Invalid regex pattern WATCHLIST_ID_REGEX!")
+});
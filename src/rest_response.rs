@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, DATE};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Headers identifying a request, in order of precedence.
+const REQUEST_ID_HEADERS: [&str; 3] = ["x-request-id", "x-correlation-id", "x-amzn-requestid"];
+
+/// Struct to represent the metadata of a REST API response.
+#[derive(Clone, Debug)]
+pub struct ResponseMeta {
+    /// The API version requested through the Version header.
+    pub api_version: usize,
+    /// The value of the Date header, if present and valid.
+    pub date: Option<DateTime<Utc>>,
+    /// Time taken by the request, including retries and logging in again after a session expiry.
+    pub duration: Duration,
+    /// The raw response headers.
+    pub headers: HeaderMap,
+    /// The request or correlation id assigned by the server, if any.
+    pub request_id: Option<String>,
+    /// The HTTP status code.
+    pub status: StatusCode,
+}
+
+/// Implementation for the ResponseMeta struct.
+impl ResponseMeta {
+    /// Create a new ResponseMeta instance, parsing the Date and request id headers. Header
+    /// values that are not valid UTF-8 are ignored.
+    pub fn new(
+        api_version: usize,
+        duration: Duration,
+        headers: HeaderMap,
+        status: StatusCode,
+    ) -> Self {
+        let date = headers
+            .get(DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&Utc));
+        let request_id = REQUEST_ID_HEADERS.iter().find_map(|name| {
+            headers
+                .get(*name)
+                .and_then(|request_id| request_id.to_str().ok())
+                .map(str::to_string)
+        });
+
+        Self {
+            api_version,
+            date,
+            duration,
+            headers,
+            request_id,
+            status,
+        }
+    }
+}
+
+/// Struct to represent a REST API response, with its deserialized body and its metadata.
+#[derive(Clone, Debug)]
+pub struct ApiResponse<T> {
+    /// The deserialized response body.
+    pub data: T,
+    /// The response metadata.
+    pub meta: ResponseMeta,
+}
+
+/// Implementation for the ApiResponse struct.
+impl<T> ApiResponse<T> {
+    /// Returns the deserialized response body, dropping the metadata.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+
+    /// Transform the response body, keeping the metadata.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ApiResponse<U> {
+        ApiResponse {
+            data: f(self.data),
+            meta: self.meta,
+        }
+    }
+
+    /// Create a new ApiResponse instance.
+    pub fn new(meta: ResponseMeta, data: T) -> Self {
+        Self { data, meta }
+    }
+}
+
+/// Struct to represent a REST API response merged from several batched requests, with its
+/// deserialized body and the metadata of each batch.
+#[derive(Clone, Debug)]
+pub struct BatchedApiResponse<T> {
+    /// The metadata of each batch response, in the order the batches were requested.
+    pub batches: Vec<ResponseMeta>,
+    /// The deserialized response body, merged from all the batches.
+    pub data: T,
+}
+
+/// Implementation for the BatchedApiResponse struct.
+impl<T> BatchedApiResponse<T> {
+    /// Returns the deserialized response body, dropping the metadata.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+
+    /// Create a new BatchedApiResponse instance.
+    pub fn new(batches: Vec<ResponseMeta>, data: T) -> Self {
+        Self { batches, data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn response_meta_parses_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            DATE,
+            HeaderValue::from_static("Tue, 15 Nov 1994 08:12:31 GMT"),
+        );
+        headers.insert("x-correlation-id", HeaderValue::from_static("abc-123"));
+        headers.insert("x-request-id", HeaderValue::from_bytes(b"\xff").unwrap());
+
+        let meta = ResponseMeta::new(2, Duration::from_millis(5), headers, StatusCode::OK);
+        assert_eq!(meta.date.unwrap().to_rfc3339(), "1994-11-15T08:12:31+00:00");
+        assert_eq!(meta.request_id.as_deref(), Some("abc-123"));
+        assert_eq!(meta.api_version, 2);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::common::*;
+use futures::future::BoxFuture;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+
+/// Struct to represent an HTTP request sent through a Transport.
+#[derive(Clone, Debug)]
+pub struct TransportRequest {
+    /// The request body, if any.
+    pub body: Option<Vec<u8>>,
+    /// The request headers, including the authentication headers.
+    pub headers: HeaderMap,
+    /// The HTTP method.
+    pub method: Method,
+    /// The full URL, including the query string.
+    pub url: String,
+}
+
+/// Struct to represent an HTTP response received through a Transport.
+#[derive(Clone, Debug)]
+pub struct TransportResponse {
+    /// The response body.
+    pub body: Vec<u8>,
+    /// The response headers.
+    pub headers: HeaderMap,
+    /// The HTTP status code.
+    pub status: StatusCode,
+}
+
+/// Trait to send HTTP requests to the REST API. RestClient sends all its requests through it,
+/// so it can be replaced to add middleware, route requests through a proxy layer or serve
+/// responses from memory in tests.
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    /// Send a request and return its response. Responses with error status codes must be
+    /// returned as responses, not as errors.
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, IgError>>;
+}
+
+/// Default transport sending requests through a reqwest client.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    /// The reqwest client instance.
+    pub client: reqwest::Client,
+}
+
+/// Implementation for the ReqwestTransport struct.
+impl ReqwestTransport {
+    /// Create a new ReqwestTransport instance using the provided reqwest client.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, IgError>> {
+        Box::pin(async move {
+            let mut request_builder = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+            if let Some(body) = request.body {
+                request_builder = request_builder.body(body);
+            }
+
+            let response = request_builder.send().await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes().await?.to_vec();
+
+            Ok(TransportResponse {
+                body,
+                headers,
+                status,
+            })
+        })
+    }
+}
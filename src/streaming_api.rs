@@ -1,10 +1,12 @@
 use crate::common::{ApiConfig, ExecutionEnvironment, LogType};
 use crate::rest_api::RestApi;
+use crate::rest_models::AccountId;
 use lightstreamer_client::ls_client::{LightstreamerClient, SubscriptionRequest, Transport};
 use lightstreamer_client::subscription::Subscription;
 use signal_hook::low_level::signal_name;
 use signal_hook::{consts::SIGINT, consts::SIGTERM, iterator::Signals};
 use std::error::Error;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::Notify;
@@ -15,11 +17,63 @@ const MAX_CONNECTION_ATTEMPTS: u64 = 1;
 pub struct StreamingApi {
     ls_client: LightstreamerClient,
     max_connection_attempts: u64,
+    /// The REST API whose session the streaming connection uses.
+    pub rest_api: RestApi,
     pub subscription_sender: Sender<SubscriptionRequest>,
     log_type: LogType,
 }
 
+/// Handle to switch the account of a StreamingApi while it is connected, e.g. from another task,
+/// since StreamingApi::connect keeps the StreamingApi mutably borrowed.
+#[derive(Clone, Debug)]
+pub struct StreamingHandle {
+    /// The REST API whose session the streaming connection uses.
+    pub rest_api: RestApi,
+    pub subscription_sender: Sender<SubscriptionRequest>,
+}
+
+/// Implementation for the StreamingHandle struct.
+impl StreamingHandle {
+    /// Switch the active account through the REST API and optionally re-point subscriptions to
+    /// it. Each pair holds the id of a subscription to remove and its replacement, e.g. built
+    /// with items from StreamingApi::account_item. The Lightstreamer session uses the new account
+    /// from the next connection attempt of StreamingApi::connect.
+    ///
+    /// Returns one result per pair, in order: the id of the replacement subscription, or the
+    /// error subscribing it, in which case the old subscription is kept.
+    pub async fn switch_account(
+        &self,
+        account_id: &AccountId,
+        resubscriptions: Vec<(usize, Subscription)>,
+    ) -> Result<Vec<Result<usize, Box<dyn Error + Send + Sync>>>, Box<dyn Error + Send + Sync>>
+    {
+        self.rest_api.switch_account(account_id).await?;
+
+        Ok(StreamingApi::resubscribe(self.subscription_sender.clone(), resubscriptions).await)
+    }
+}
+
 impl StreamingApi {
+    /// Returns the given subscription item pointed to another account if it is an account item,
+    /// i.e. ACCOUNT:{accountId} or TRADE:{accountId}, or unchanged otherwise.
+    pub fn account_item(item: &str, account_id: &str) -> String {
+        match item.split_once(':') {
+            Some((prefix @ ("ACCOUNT" | "TRADE"), _)) => format!("{}:{}", prefix, account_id),
+            _ => item.to_string(),
+        }
+    }
+
+    /// Returns the account the streaming connection logs in as: the active account of the REST
+    /// API session, which may differ from the configured one.
+    fn active_account_id(rest_api: &RestApi) -> String {
+        rest_api.client.account_id().unwrap_or_else(|| {
+            match rest_api.config.execution_environment {
+                ExecutionEnvironment::Demo => rest_api.config.account_number_demo.clone(),
+                ExecutionEnvironment::Live => rest_api.config.account_number_live.clone(),
+            }
+        })
+    }
+
     pub async fn connect(&mut self) {
         // Create a new Notify instance to send a shutdown signal to the signal handler thread.
         let shutdown_signal = Arc::new(tokio::sync::Notify::new());
@@ -32,6 +86,7 @@ impl StreamingApi {
         let mut retry_interval_milis: u64 = 0;
         let mut retry_counter: u64 = 0;
         while retry_counter < self.max_connection_attempts {
+            self.refresh_connection_details();
             match self.ls_client.connect(Arc::clone(&shutdown_signal)).await {
                 Ok(_) => {
                     self.ls_client.disconnect().await;
@@ -72,7 +127,7 @@ impl StreamingApi {
         //
         // Connect to REST API and authenticate.
         //
-        let mut rest_api = match RestApi::new(api_config).await {
+        let rest_api = match RestApi::new(api_config).await {
             Ok(api) => api,
             Err(e) => {
                 return Err(Box::<dyn Error>::from(format!(
@@ -82,7 +137,7 @@ impl StreamingApi {
             }
         };
         if !auto_login {
-            let _ = rest_api.client.login();
+            let _ = rest_api.client.login().await;
         }
 
         // Get the CST and X-SECURITY-TOKEN values from the REST API session.
@@ -102,13 +157,10 @@ impl StreamingApi {
         let mut ls_client = LightstreamerClient::new(
             Some(&format!(
                 "{}/lightstreamer",
-                &rest_api.client.lightstreamer_endpoint
+                rest_api.client.lightstreamer_endpoint()
             )),
             None,
-            match rest_api.config.execution_environment {
-                ExecutionEnvironment::Demo => Some(&rest_api.config.account_number_demo),
-                ExecutionEnvironment::Live => Some(&rest_api.config.account_number_live),
-            },
+            Some(&StreamingApi::active_account_id(&rest_api)),
             Some(&format!("CST-{}|XST-{}", cst.to_string(), x_security_token)),
         )?;
 
@@ -127,6 +179,7 @@ impl StreamingApi {
         Ok(Self {
             ls_client,
             max_connection_attempts,
+            rest_api,
             subscription_sender,
             log_type: api_log_type,
         })
@@ -137,8 +190,8 @@ impl StreamingApi {
         //
         // Get auth headers from the REST API session.
         //
-        let auth_headers = match rest_api.client.auth_headers {
-            Some(ref headers) => headers,
+        let auth_headers = match rest_api.client.auth_headers() {
+            Some(headers) => headers,
             None => {
                 return Err(Box::<dyn Error>::from(
                     "Client not authenticated, auth headers not found.",
@@ -180,6 +233,65 @@ impl StreamingApi {
         Ok((cst, x_security_token))
     }
 
+    /// Returns a handle to switch the account while connected. It must be taken before calling
+    /// connect.
+    pub fn handle(&self) -> StreamingHandle {
+        StreamingHandle {
+            rest_api: self.rest_api.clone(),
+            subscription_sender: self.subscription_sender.clone(),
+        }
+    }
+
+    /// Points the Lightstreamer client at the active account and session tokens of the REST API,
+    /// which may have changed since the client was created, e.g. after switch_account.
+    fn refresh_connection_details(&mut self) {
+        let account_id = StreamingApi::active_account_id(&self.rest_api);
+        self.ls_client.connection_details.set_user(Some(account_id));
+
+        if let Ok((cst, x_security_token)) = StreamingApi::get_tokens(&self.rest_api) {
+            self.ls_client
+                .connection_details
+                .set_password(Some(format!("CST-{}|XST-{}", cst, x_security_token)));
+        }
+    }
+
+    /// Replace each subscription id with its replacement subscription. Returns one result per
+    /// pair, in order, as described in StreamingHandle::switch_account.
+    pub async fn resubscribe(
+        subscription_sender: Sender<SubscriptionRequest>,
+        resubscriptions: Vec<(usize, Subscription)>,
+    ) -> Vec<Result<usize, Box<dyn Error + Send + Sync>>> {
+        StreamingApi::resubscribe_with(
+            resubscriptions,
+            |subscription| StreamingApi::subscribe_get_id(subscription_sender.clone(), subscription),
+            |subscription_id| StreamingApi::unsubscribe(subscription_sender.clone(), subscription_id),
+        )
+        .await
+    }
+
+    /// Subscribe each replacement before unsubscribing the subscription it replaces, so the
+    /// items are never left unsubscribed, and keep the old subscription if subscribing fails.
+    async fn resubscribe_with<T, F, Fut>(
+        resubscriptions: Vec<(usize, T)>,
+        mut subscribe: F,
+        mut unsubscribe: impl FnMut(usize),
+    ) -> Vec<Result<usize, Box<dyn Error + Send + Sync>>>
+    where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<usize, Box<dyn Error + Send + Sync>>>,
+    {
+        let mut results = Vec::new();
+        for (subscription_id, subscription) in resubscriptions {
+            let result = subscribe(subscription).await;
+            if result.is_ok() {
+                unsubscribe(subscription_id);
+            }
+            results.push(result);
+        }
+
+        results
+    }
+
     /// Sets up a signal hook for SIGINT and SIGTERM.
     ///
     /// Creates a signal hook for the specified signals and spawns a thread to handle them.
@@ -221,6 +333,20 @@ impl StreamingApi {
         LightstreamerClient::unsubscribe(subscription_sender, subscription_id);
     }
 
+    /// Switch the active account through the REST API and optionally re-point subscriptions to
+    /// it, as described in StreamingHandle::switch_account. Use handle to switch the account
+    /// while connected.
+    pub async fn switch_account(
+        &self,
+        account_id: &AccountId,
+        resubscriptions: Vec<(usize, Subscription)>,
+    ) -> Result<Vec<Result<usize, Box<dyn Error + Send + Sync>>>, Box<dyn Error + Send + Sync>>
+    {
+        self.handle()
+            .switch_account(account_id, resubscriptions)
+            .await
+    }
+
     pub fn make_log(&mut self, loglevel: Level, log: &str) {
         Self::log_msg(&self.log_type, loglevel, log);
     }
@@ -256,4 +382,74 @@ impl StreamingApi {
             },
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn account_item_works() {
+        assert_eq!(
+            StreamingApi::account_item("ACCOUNT:ABC123", "XYZ789"),
+            "ACCOUNT:XYZ789"
+        );
+        assert_eq!(
+            StreamingApi::account_item("TRADE:ABC123", "XYZ789"),
+            "TRADE:XYZ789"
+        );
+
+        // Other items are not account items.
+        for item in [
+            "MARKET:IX.D.DAX.IFMM.IP",
+            "CHART:IX.D.DAX.IFMM.IP:TICK",
+            "ACCOUNT",
+            "TRADES:ABC123",
+        ] {
+            assert_eq!(StreamingApi::account_item(item, "XYZ789"), item);
+        }
+    }
+
+    #[tokio::test]
+    async fn resubscriptions_subscribe_before_unsubscribing() {
+        let events = Mutex::new(Vec::new());
+        let results = StreamingApi::resubscribe_with(
+            vec![(1, "TRADE:XYZ789"), (2, "FAIL"), (3, "ACCOUNT:XYZ789")],
+            |item| {
+                events.lock().unwrap().push(format!("subscribe {}", item));
+                async move {
+                    match item {
+                        "TRADE:XYZ789" => Ok(4),
+                        "ACCOUNT:XYZ789" => Ok(5),
+                        _ => Err("Failed to get subscription id".into()),
+                    }
+                }
+            },
+            |subscription_id| {
+                events
+                    .lock()
+                    .unwrap()
+                    .push(format!("unsubscribe {}", subscription_id))
+            },
+        )
+        .await;
+
+        // The failed replacement keeps its old subscription and doesn't stop the others.
+        assert_eq!(
+            events.into_inner().unwrap(),
+            vec![
+                "subscribe TRADE:XYZ789",
+                "unsubscribe 1",
+                "subscribe FAIL",
+                "subscribe ACCOUNT:XYZ789",
+                "unsubscribe 3",
+            ]
+        );
+        let subscription_ids: Vec<Option<usize>> = results
+            .iter()
+            .map(|result| result.as_ref().ok().copied())
+            .collect();
+        assert_eq!(subscription_ids, vec![Some(4), None, Some(5)]);
+    }
+}
@@ -1,9 +1,11 @@
 use base64::{display::Base64Display, engine::general_purpose::STANDARD};
 use ig_trading_api::common::*;
 use ig_trading_api::rest_api::*;
+use ig_trading_api::rest_cassette::RecordingTransport;
 use ig_trading_api::rest_models::*;
+use ig_trading_api::rest_response::ApiResponse;
+use ig_trading_api::rest_transport::{ReqwestTransport, Transport};
 use regex::Regex;
-use serde_json::Value;
 use std::sync::Arc;
 use tokio::sync::OnceCell;
 
@@ -23,7 +25,15 @@ async fn get_or_init_rest_api() -> Arc<RestApi> {
         // Load the configuration from config.yaml file and create a new mutable Api instance,
         let api_config = ApiConfig::default();
         let auto_login = api_config.auto_login.unwrap_or(false);
-        let mut rest_api = match RestApi::new(api_config).await {
+        // Record the requests and responses to a cassette if IG_RECORD_CASSETTE is set.
+        let transport: Arc<dyn Transport> = match std::env::var("IG_RECORD_CASSETTE") {
+            Ok(path) => Arc::new(
+                RecordingTransport::new(Arc::new(ReqwestTransport::default()), path)
+                    .expect("Failed to open the cassette"),
+            ),
+            Err(_) => Arc::new(ReqwestTransport::default()),
+        };
+        let mut rest_api = match RestApi::with_transport(api_config, transport).await {
             Ok(api) => api,
             Err(e) => panic!("Failed to create and initialize REST API: {}", e),
         };
@@ -32,7 +42,7 @@ async fn get_or_init_rest_api() -> Arc<RestApi> {
         rest_api.client.config.execution_environment = ExecutionEnvironment::Demo;
 
         if !auto_login {
-            let _ = rest_api.client.login();
+            let _ = rest_api.client.login().await;
         }
 
         Arc::new(rest_api)
@@ -66,42 +76,21 @@ async fn aaa_rest_api_is_properly_initialized() {
     println!("API instance: {:?}", api);
 
     // First check if auth headers are set.
-    assert!(api.client.auth_headers.is_some());
+    assert!(api.client.auth_headers().is_some());
+    let auth_headers = api.client.auth_headers().unwrap();
 
     // Then check auth tokens are set and have the correct format for the configured session version.
     if let Some(session_version) = api.client.config.session_version.as_ref() {
         match session_version {
             1 | 2 => {
-                assert!(api
-                    .client
-                    .auth_headers
-                    .as_ref()
-                    .unwrap()
-                    .contains_key("cst"));
-                assert!(api
-                    .client
-                    .auth_headers
-                    .as_ref()
-                    .unwrap()
-                    .contains_key("x-security-token"));
+                assert!(auth_headers.contains_key("cst"));
+                assert!(auth_headers.contains_key("x-security-token"));
 
-                let cst_value = api
-                    .client
-                    .auth_headers
-                    .as_ref()
-                    .unwrap()
-                    .get("cst")
-                    .unwrap()
-                    .to_str()
-                    .unwrap();
+                let cst_value = auth_headers.get("cst").unwrap().to_str().unwrap();
                 let re = Regex::new(r"^[a-fA-F0-9]{69}$").unwrap();
                 assert!(re.is_match(cst_value));
 
-                let security_token_value = api
-                    .client
-                    .auth_headers
-                    .as_ref()
-                    .unwrap()
+                let security_token_value = auth_headers
                     .get("x-security-token")
                     .unwrap()
                     .to_str()
@@ -110,22 +99,10 @@ async fn aaa_rest_api_is_properly_initialized() {
                 assert!(re.is_match(security_token_value));
             }
             3 => {
-                assert!(api
-                    .client
-                    .auth_headers
-                    .as_ref()
-                    .unwrap()
-                    .contains_key("authorization"));
+                assert!(auth_headers.contains_key("authorization"));
 
-                let authorization_value = api
-                    .client
-                    .auth_headers
-                    .as_ref()
-                    .unwrap()
-                    .get("authorization")
-                    .unwrap()
-                    .to_str()
-                    .unwrap();
+                let authorization_value =
+                    auth_headers.get("authorization").unwrap().to_str().unwrap();
                 let re = regex::Regex::new(
                     r"^Bearer [0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
                 ).unwrap();
@@ -159,13 +136,10 @@ async fn accounts_get_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     sleep();
@@ -184,13 +158,10 @@ async fn accounts_preferences_get_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     sleep();
@@ -213,13 +184,10 @@ async fn accounts_preferences_put_works() {
         }
     };
 
-    println!(
-        "Response_1 headers: {}",
-        serde_json::to_string_pretty(&response_1.0).unwrap()
-    );
+    println!("Response_1 meta: {:#?}", response_1.meta);
     println!(
         "Response_1 body: {}",
-        serde_json::to_string_pretty(&response_1.1).unwrap()
+        serde_json::to_string_pretty(&response_1.data).unwrap()
     );
 
     sleep();
@@ -230,7 +198,7 @@ async fn accounts_preferences_put_works() {
     println!("Updating the account preferences...");
     let body_1;
     let body_2;
-    match response_1.1.trailing_stops_enabled {
+    match response_1.data.trailing_stops_enabled {
         true => {
             body_1 = AccountsPreferencesPutRequest {
                 trailing_stops_enabled: false,
@@ -257,13 +225,10 @@ async fn accounts_preferences_put_works() {
         }
     };
 
-    println!(
-        "Response_2 headers: {}",
-        serde_json::to_string_pretty(&response_2.0).unwrap()
-    );
+    println!("Response_2 meta: {:#?}", response_2.meta);
     println!(
         "Response_2 body: {}",
-        serde_json::to_string_pretty(&response_2.1).unwrap()
+        serde_json::to_string_pretty(&response_2.data).unwrap()
     );
 
     sleep();
@@ -280,13 +245,10 @@ async fn accounts_preferences_put_works() {
         }
     };
 
-    println!(
-        "Response_3 headers: {}",
-        serde_json::to_string_pretty(&response_3.0).unwrap()
-    );
+    println!("Response_3 meta: {:#?}", response_3.meta);
     println!(
         "Response_3 body: {}",
-        serde_json::to_string_pretty(&response_3.1).unwrap()
+        serde_json::to_string_pretty(&response_3.data).unwrap()
     );
 
     sleep();
@@ -303,13 +265,10 @@ async fn accounts_preferences_put_works() {
         }
     };
 
-    println!(
-        "Response_4 headers: {}",
-        serde_json::to_string_pretty(&response_4.0).unwrap()
-    );
+    println!("Response_4 meta: {:#?}", response_4.meta);
     println!(
         "Response_4 body: {}",
-        serde_json::to_string_pretty(&response_4.1).unwrap()
+        serde_json::to_string_pretty(&response_4.data).unwrap()
     );
 
     sleep();
@@ -326,28 +285,105 @@ async fn accounts_preferences_put_works() {
         }
     };
 
-    println!(
-        "Response_5 headers: {}",
-        serde_json::to_string_pretty(&response_5.0).unwrap()
-    );
+    println!("Response_5 meta: {:#?}", response_5.meta);
     println!(
         "Response_5 body: {}",
-        serde_json::to_string_pretty(&response_5.1).unwrap()
+        serde_json::to_string_pretty(&response_5.data).unwrap()
     );
 
     //
     // Verify that the original trailing_stops_enabled field differs from the updated field.
     //
     assert_eq!(
-        response_1.1.trailing_stops_enabled,
-        !response_3.1.trailing_stops_enabled
+        response_1.data.trailing_stops_enabled,
+        !response_3.data.trailing_stops_enabled
     );
     //
     // Verify that the trailing_stops_enabled field has the same value as the original account preferences.
     //
     assert_eq!(
-        response_1.1.trailing_stops_enabled,
-        response_5.1.trailing_stops_enabled
+        response_1.data.trailing_stops_enabled,
+        response_5.data.trailing_stops_enabled
+    );
+
+    sleep();
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// CLIENT SENTIMENT ENDPOINT INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn clientsentiment_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    //
+    // Get the client sentiment for a list of markets.
+    //
+    println!("Getting client sentiment for a list of markets...");
+    let params = SentimentQuery {
+        market_ids: Some(vec!["FT100".to_string(), "DE30".to_string()]),
+    };
+
+    let response_1 = match api.clientsentiment_get(params).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting client sentiment: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_1.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_1.data).unwrap()
+    );
+
+    assert_eq!(response_1.data.client_sentiments.len(), 2);
+
+    sleep();
+
+    //
+    // Get the client sentiment for a single market.
+    //
+    println!("Getting client sentiment for a single market...");
+    let response_2 = match api.clientsentiment_market_get("FT100".to_string()).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting client sentiment for market: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_2.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_2.data).unwrap()
+    );
+
+    assert_eq!(response_2.data.market_id, "FT100");
+
+    sleep();
+
+    //
+    // Get the client sentiment for the related markets.
+    //
+    println!("Getting client sentiment for related markets...");
+    let response_3 = match api.clientsentiment_related_get("FT100".to_string()).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting related client sentiment: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_3.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_3.data).unwrap()
     );
 
     sleep();
@@ -384,13 +420,36 @@ async fn history_activity_get_works() {
     };
 
     // Print the response for manual verification.
+    println!("Response meta: {:#?}", response.meta);
     println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
+        "Response body: {}",
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
+
+    sleep();
+}
+
+#[tokio::test]
+async fn history_activity_v1_get_works() {
+    // Initialize the API instance.
+    let api = get_or_init_rest_api().await;
+
+    // Last 30 days.
+    let period = HistoryPeriod::LastPeriod(std::time::Duration::from_secs(30 * 24 * 3600));
+
+    let response = match api.history_activity_v1_get(period).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting activity history v1: {:?}", e);
+            panic!("Test failed due to an error.");
+        }
+    };
+
+    // Print the response for manual verification.
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     sleep();
@@ -421,13 +480,43 @@ async fn history_transactions_get_works() {
     };
 
     // Print the response for manual verification.
+    println!("Response meta: {:#?}", response.meta);
     println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
+        "Response body: {}",
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
+
+    sleep();
+}
+
+#[tokio::test]
+async fn history_transactions_v1_get_works() {
+    // Initialize the API instance.
+    let api = get_or_init_rest_api().await;
+
+    // Last 30 days.
+    let today = chrono::Utc::now().date_naive();
+    let period = HistoryPeriod::DateRange {
+        from: today - chrono::Duration::try_days(30).unwrap(),
+        to: today,
+    };
+
+    let response = match api
+        .history_transactions_v1_get(TransactionType::All, period)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting transaction history v1: {:?}", e);
+            panic!("Test failed due to an error.");
+        }
+    };
+
+    // Print the response for manual verification.
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     sleep();
@@ -439,6 +528,30 @@ async fn history_transactions_get_works() {
 //
 ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+#[tokio::test]
+async fn market_get_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    let response = match api.market_get("IX.D.DAX.IFMM.IP".parse().unwrap()).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting market details: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response.data).unwrap()
+    );
+
+    assert_eq!(response.data.instrument.epic, "IX.D.DAX.IFMM.IP");
+
+    sleep();
+}
+
 #[tokio::test]
 async fn marketnavigation_get_works() {
     // Get the API instance.
@@ -453,11 +566,11 @@ async fn marketnavigation_get_works() {
         }
     };
 
-    let nodes_1 = response_1.1.nodes.as_ref().unwrap();
+    let nodes_1 = response_1.data.nodes.as_ref().unwrap();
     assert!(!nodes_1.is_empty());
 
     let node_id = response_1
-        .1
+        .data
         .nodes
         .as_ref()
         .unwrap()
@@ -466,13 +579,10 @@ async fn marketnavigation_get_works() {
         .id
         .clone();
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_1.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_1.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_1.1).unwrap()
+        serde_json::to_string_pretty(&response_1.data).unwrap()
     );
 
     println!("Node ID: {}", node_id);
@@ -488,17 +598,136 @@ async fn marketnavigation_get_works() {
         }
     };
 
+    println!("Response meta: {:#?}", response_2.meta);
     println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_2.0).unwrap()
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_2.data).unwrap()
     );
+
+    let nodes_2 = response_2.data.nodes.as_ref().unwrap();
+    assert!(!nodes_2.is_empty());
+
+    sleep();
+}
+
+#[tokio::test]
+async fn markets_get_batched_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    let epics: Vec<Epic> = vec![
+        "CS.D.BITCOIN.CFD.IP".parse().unwrap(),
+        "IX.D.DAX.IFMM.IP".parse().unwrap(),
+        "IX.D.FTSE.IFM.IP".parse().unwrap(),
+    ];
+
+    let response = match api.markets_get_batched(epics.clone(), None).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting batched market details: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response batches: {:#?}", response.batches);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_2.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
-    let nodes_2 = response_2.1.nodes.as_ref().unwrap();
-    assert!(!nodes_2.is_empty());
+    // The market details must be returned in the same order as the requested epics.
+    let returned_epics: Vec<Epic> = response
+        .data
+        .market_details
+        .iter()
+        .map(|market_details| market_details.instrument.epic.clone())
+        .collect();
+    assert_eq!(returned_epics, epics);
+
+    sleep();
+}
+
+#[tokio::test]
+async fn markets_search_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    //
+    // Search markets by term.
+    //
+    println!("Searching markets...");
+    let response_1 = match api.markets_search("Germany 40").await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error searching markets: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_1.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_1.data).unwrap()
+    );
+
+    assert!(!response_1.data.is_empty());
+
+    sleep();
+
+    //
+    // Get the market details of the exact epic match.
+    //
+    println!("Getting the market details of the best match...");
+    let response_2 = match api.markets_search_details("IX.D.DAX.IFMM.IP").await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting market details of the best match: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_2.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_2.data).unwrap()
+    );
+
+    assert_eq!(response_2.data.unwrap().instrument.epic, "IX.D.DAX.IFMM.IP");
+
+    sleep();
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// OPERATIONS ENDPOINT INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn operations_application_get_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    let response = match api.operations_application_get().await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting client applications: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response.data).unwrap()
+    );
+
+    // The API key in use must be one of the returned applications.
+    assert!(response
+        .data
+        .applications
+        .iter()
+        .any(|application| application.api_key == api.config.api_key));
 
     sleep();
 }
@@ -522,7 +751,7 @@ async fn positions_flow_works() {
         currency_code: "EUR".to_string(),
         deal_reference: None,
         direction: Direction::Buy,
-        epic: "IX.D.DAX.IFMM.IP".to_string(),
+        epic: "IX.D.DAX.IFMM.IP".parse().unwrap(),
         expiry: "-".to_string(), // "-" for no expiry.
         force_open: true,
         guaranteed_stop: false,
@@ -531,7 +760,7 @@ async fn positions_flow_works() {
         limit_level: None,
         order_type: OrderType::Market,
         quote_id: None,
-        size: 1.0,
+        size: Number::from(1),
         stop_distance: None,
         stop_level: None,
         time_in_force: None,
@@ -547,17 +776,14 @@ async fn positions_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_1.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_1.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_1.1).unwrap()
+        serde_json::to_string_pretty(&response_1.data).unwrap()
     );
 
-    let deal_reference = response_1.1.deal_reference.clone();
+    let deal_reference = response_1.data.deal_reference.clone();
 
     //
     // Get the trade confirmation for the new position.
@@ -579,18 +805,15 @@ async fn positions_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_2.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_2.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_2.1).unwrap()
+        serde_json::to_string_pretty(&response_2.data).unwrap()
     );
 
-    let deal_id = response_2.1.deal_id.clone();
-    let position_level = response_2.1.level.unwrap();
+    let deal_id = response_2.data.deal_id.clone();
+    let position_level = response_2.data.level.unwrap();
     println!("Position level: {}", position_level);
 
     //
@@ -605,14 +828,11 @@ async fn positions_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_3.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_3.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_3.1).unwrap()
+        serde_json::to_string_pretty(&response_3.data).unwrap()
     );
 
     sleep();
@@ -633,14 +853,11 @@ async fn positions_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_4.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_4.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_4.1).unwrap()
+        serde_json::to_string_pretty(&response_4.data).unwrap()
     );
 
     sleep();
@@ -651,8 +868,8 @@ async fn positions_flow_works() {
     println!("Updating the position...");
     let position_update_request = PositionPutRequest {
         guaranteed_stop: Some(true),
-        limit_level: Some(position_level + 100.0),
-        stop_level: Some(position_level - 50.0),
+        limit_level: Some(position_level + Number::from(100)),
+        stop_level: Some(position_level - Number::from(50)),
         trailing_stop: None,
         trailing_stop_distance: None,
         trailing_stop_increment: None,
@@ -669,14 +886,11 @@ async fn positions_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_5.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_5.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_5.1).unwrap()
+        serde_json::to_string_pretty(&response_5.data).unwrap()
     );
 
     sleep();
@@ -693,7 +907,7 @@ async fn positions_flow_works() {
         level: None,
         order_type: Some(OrderType::Market),
         quote_id: None,
-        size: 1.0,
+        size: Number::from(1),
         time_in_force: None,
     };
 
@@ -705,14 +919,11 @@ async fn positions_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_6.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_6.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_6.1).unwrap()
+        serde_json::to_string_pretty(&response_6.data).unwrap()
     );
 
     sleep();
@@ -738,13 +949,10 @@ async fn session_get_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     // Test with params.
@@ -760,13 +968,33 @@ async fn session_get_works() {
         }
     };
 
+    println!("Response meta: {:#?}", response.meta);
     println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
+        "Response body: {}",
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
+
+    sleep();
+}
+
+#[tokio::test]
+async fn session_post_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    // Log in again with the configured session version.
+    let response = match api.session_post().await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error creating session: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     sleep();
@@ -792,7 +1020,7 @@ async fn session_put_works() {
     };
 
     let body = AccountSwitchPutRequest {
-        account_id: new_account_number.clone(),
+        account_id: new_account_number.parse().unwrap(),
         default_account: None,
     };
 
@@ -807,18 +1035,57 @@ async fn session_put_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
     sleep();
 }
 
+#[tokio::test]
+async fn switch_account_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    // If config account_number_test is not set, then skip this test.
+    let test_account_number = match api.config.account_number_test.clone() {
+        Some(account_number) => account_number,
+        None => {
+            println!("Skipping test because account_number_test is not set in configuration file.");
+            return;
+        }
+    };
+    let account_number = match api.client.account_id() {
+        Some(account_number) => account_number,
+        None => {
+            println!("The client is not logged in.");
+            panic!("Test failed due to error.");
+        }
+    };
+
+    // Switch to the test account and back to the original one.
+    for account_id in [&test_account_number, &account_number] {
+        let response = match api.switch_account(&account_id.parse().unwrap()).await {
+            Ok(response) => response,
+            Err(e) => {
+                println!("Error switching to account '{}': {:?}", account_id, e);
+                panic!("Test failed due to error.");
+            }
+        };
+        assert_eq!(api.client.account_id().as_ref(), Some(account_id));
+
+        println!("Response meta: {:#?}", response.meta);
+        println!(
+            "Response body: {}",
+            serde_json::to_string_pretty(&response.data).unwrap()
+        );
+
+        sleep();
+    }
+}
+
 #[tokio::test]
 async fn session_encryption_key_get_works() {
     // Get the API instance.
@@ -832,16 +1099,13 @@ async fn session_encryption_key_get_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response.data).unwrap()
     );
 
-    let encryption_key = response.1.encryption_key.as_bytes();
+    let encryption_key = response.data.encryption_key.as_bytes();
     let decoded_encryption_key = Base64Display::new(encryption_key, &STANDARD);
     println!("Decoded encryption key: {}", decoded_encryption_key);
 
@@ -860,16 +1124,13 @@ async fn session_refresh_token_post_works() {
     }
 
     let body = SessionRefreshTokenPostRequest {
-        refresh_token: api.client.refresh_token.as_ref().unwrap().clone(),
+        refresh_token: api.client.refresh_token().unwrap(),
     };
 
     println!("Refresh token: {:?}", body.refresh_token);
-    println!(
-        "Auth headers: {:?}",
-        api.client.auth_headers.as_ref().unwrap()
-    );
+    println!("Auth headers: {:?}", api.client.auth_headers().unwrap());
 
-    let response: (Value, SessionRefreshTokenPostResponse) =
+    let response: ApiResponse<SessionRefreshTokenPostResponse> =
         match api.session_refresh_token_post(&body).await {
             Ok(response) => response,
             Err(e) => {
@@ -878,13 +1139,228 @@ async fn session_refresh_token_post_works() {
             }
         };
 
+    println!("Response meta: {:#?}", response.meta);
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response.data).unwrap()
+    );
+
+    sleep();
+}
+
+#[tokio::test]
+async fn token_refresher_works() {
+    // Use a dedicated API instance so that other tests don't refresh its tokens.
+    let api_config = ApiConfig::default();
+
+    // If config session_version is not 3, then skip this test.
+    if api_config.session_version.unwrap_or(0) != 3 {
+        println!("Skipping test because session_version is not 3 in configuration file.");
+        return;
+    }
+
+    let api = match RestApi::new(api_config).await {
+        Ok(api) => api,
+        Err(e) => panic!("Failed to create and initialize REST API: {}", e),
+    };
+    if !api.client.auto_login {
+        let _ = api.client.login().await;
+    }
+
+    let get_authorization = || api.client.auth_headers().unwrap()["authorization"].clone();
+    let authorization = get_authorization();
+
+    let (token_refresher, mut failures) = match api.client.start_token_refresher() {
+        Ok(token_refresher) => token_refresher,
+        Err(e) => {
+            println!("Error starting the token refresher: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    // Access tokens expire after 60 seconds, so wait until the refresher has replaced it.
+    tokio::time::sleep(std::time::Duration::from_secs(55)).await;
+
+    assert!(token_refresher.is_running());
+    assert!(failures.try_recv().is_err());
+    assert_ne!(get_authorization(), authorization);
+
+    // The refreshed token must be usable.
+    if let Err(e) = api.session_get(None).await {
+        println!("Error getting session details: {:?}", e);
+        panic!("Test failed due to error.");
+    }
+
+    token_refresher.stop().await.unwrap();
+
+    sleep();
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// WATCHLISTS ENDPOINT INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn watchlists_flow_works() {
+    // Get the API instance.
+    let api = get_or_init_rest_api().await;
+
+    //
+    // Create a new watchlist.
+    //
+    println!("Creating a new watchlist...");
+    let create_watchlist_request = CreateWatchlist {
+        epics: vec!["IX.D.DAX.IFMM.IP".parse().unwrap()],
+        name: format!("ig_trading_api_{}", chrono::Utc::now().timestamp()),
+    };
+
+    let response_1 = match api.watchlists_post(&create_watchlist_request).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error creating a new watchlist: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_1.meta);
+
     println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_1.data).unwrap()
     );
+
+    let watchlist_id = response_1.data.watchlist_id.clone();
+
+    sleep();
+
+    //
+    // Get the list of watchlists.
+    //
+    println!("Getting list of watchlists...");
+    let response_2 = match api.watchlists_get().await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error getting list of watchlists: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_2.meta);
+
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_2.data).unwrap()
+    );
+
+    assert!(response_2
+        .data
+        .watchlists
+        .iter()
+        .any(|watchlist| watchlist.id == watchlist_id));
+
+    sleep();
+
+    //
+    // Add a market to the new watchlist.
+    //
+    println!("Adding a market to the new watchlist...");
+    let add_to_watchlist_request = AddToWatchlist {
+        epic: "CS.D.BITCOIN.CFD.IP".parse().unwrap(),
+    };
+
+    let response_3 = match api
+        .watchlist_put(&add_to_watchlist_request, watchlist_id.clone())
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error adding a market to the new watchlist: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_3.meta);
+
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response.1).unwrap()
+        serde_json::to_string_pretty(&response_3.data).unwrap()
+    );
+
+    sleep();
+
+    //
+    // Get the markets of the new watchlist.
+    //
+    println!("Getting the markets of the new watchlist...");
+    let response_4 = match api.watchlist_get(watchlist_id.clone()).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!(
+                "Error getting the markets of watchlist '{}': {:?}",
+                watchlist_id, e
+            );
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_4.meta);
+
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_4.data).unwrap()
+    );
+
+    assert!(response_4
+        .data
+        .markets
+        .iter()
+        .any(|market| market.epic == "CS.D.BITCOIN.CFD.IP"));
+
+    sleep();
+
+    //
+    // Remove the market from the new watchlist.
+    //
+    println!("Removing a market from the new watchlist...");
+    let response_5 = match api
+        .watchlist_epic_delete(watchlist_id.clone(), "CS.D.BITCOIN.CFD.IP".parse().unwrap())
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error removing a market from the new watchlist: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_5.meta);
+
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_5.data).unwrap()
+    );
+
+    sleep();
+
+    //
+    // Delete the new watchlist.
+    //
+    println!("Deleting the new watchlist...");
+    let response_6 = match api.watchlist_delete(watchlist_id.clone()).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("Error deleting the new watchlist: {:?}", e);
+            panic!("Test failed due to error.");
+        }
+    };
+
+    println!("Response meta: {:#?}", response_6.meta);
+
+    println!(
+        "Response body: {}",
+        serde_json::to_string_pretty(&response_6.data).unwrap()
     );
 
     sleep();
@@ -909,15 +1385,15 @@ async fn workingorders_flow_works() {
         currency_code: "EUR".to_string(),
         deal_reference: None,
         direction: Direction::Buy,
-        epic: "IX.D.DAX.IFMM.IP".to_string(),
+        epic: "IX.D.DAX.IFMM.IP".parse().unwrap(),
         expiry: "-".to_string(), // "-" for no expiry.
         force_open: Some(true),
         good_till_date: None,
         guaranteed_stop: false,
-        level: 10000.0,
+        level: Number::from(10000),
         limit_distance: None,
         limit_level: None,
-        size: 1.0,
+        size: Number::from(1),
         stop_distance: None,
         stop_level: None,
         time_in_force: WorkingOrderTimeInForce::GoodTillCancelled,
@@ -932,17 +1408,14 @@ async fn workingorders_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_1.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_1.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_1.1).unwrap()
+        serde_json::to_string_pretty(&response_1.data).unwrap()
     );
 
-    let deal_reference = response_1.1.deal_reference.clone();
+    let deal_reference = response_1.data.deal_reference.clone();
 
     sleep();
 
@@ -966,22 +1439,19 @@ async fn workingorders_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_2.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_2.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_2.1).unwrap()
+        serde_json::to_string_pretty(&response_2.data).unwrap()
     );
 
-    let deal_id = response_2.1.deal_id.clone();
+    let deal_id = response_2.data.deal_id.clone();
     let working_order_level;
-    if let Some(level) = response_2.1.level {
+    if let Some(level) = response_2.data.level {
         working_order_level = level;
     } else {
-        working_order_level = 18500.0;
+        working_order_level = Number::from(18500);
     }
     println!("Working order level: {}", working_order_level);
 
@@ -999,14 +1469,11 @@ async fn workingorders_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_3.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_3.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_3.1).unwrap()
+        serde_json::to_string_pretty(&response_3.data).unwrap()
     );
 
     sleep();
@@ -1018,7 +1485,7 @@ async fn workingorders_flow_works() {
     let working_order_update_request = WorkingOrderPutRequest {
         good_till_date: None,
         guaranteed_stop: None,
-        level: working_order_level + 100.0,
+        level: working_order_level + Number::from(100),
         limit_distance: None,
         limit_level: None,
         stop_distance: None,
@@ -1038,14 +1505,11 @@ async fn workingorders_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_4.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_4.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_4.1).unwrap()
+        serde_json::to_string_pretty(&response_4.data).unwrap()
     );
 
     sleep();
@@ -1062,14 +1526,11 @@ async fn workingorders_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_5.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_5.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_5.1).unwrap()
+        serde_json::to_string_pretty(&response_5.data).unwrap()
     );
 
     sleep();
@@ -1086,14 +1547,11 @@ async fn workingorders_flow_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response_6.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response_6.meta);
 
     println!(
         "Response body: {}",
-        serde_json::to_string_pretty(&response_6.1).unwrap()
+        serde_json::to_string_pretty(&response_6.data).unwrap()
     );
 
     sleep();
@@ -1114,10 +1572,7 @@ async fn zzz_session_delete_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
 
     sleep();
 }
@@ -1129,7 +1584,7 @@ async fn prices_get_works() {
     // Get the API instance.
     let api = get_or_init_rest_api().await;
 
-    let response = match api.prices_get("IX.D.FTSE.IFM.IP", PricesGetRequest {
+    let response = match api.prices_get(&"IX.D.FTSE.IFM.IP".parse().unwrap(), PricesGetRequest {
         resolution: None,
         from: None,
         to: None,
@@ -1144,10 +1599,7 @@ async fn prices_get_works() {
         }
     };
 
-    println!(
-        "Response headers: {}",
-        serde_json::to_string_pretty(&response.0).unwrap()
-    );
+    println!("Response meta: {:#?}", response.meta);
 
     sleep();
 }
@@ -0,0 +1,361 @@
+use chrono::{NaiveDate, Timelike};
+use ig_trading_api::common::*;
+use ig_trading_api::rest_api::*;
+use ig_trading_api::rest_cassette::ReplayTransport;
+use ig_trading_api::rest_models::*;
+use std::sync::Arc;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// FUNCTIONS SHARED BY ALL THE CASSETTE INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Create a REST API instance replaying the given cassette of the tests/cassettes directory.
+async fn replay_cassette(name: &str) -> RestApi {
+    let path = format!("{}/tests/cassettes/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let transport = ReplayTransport::from_file(path).unwrap();
+    let config = ApiConfig {
+        account_number_demo: "ABC123".to_string(),
+        account_number_live: "ABC123".to_string(),
+        account_number_test: None,
+        api_key: "test_api_key".to_string(),
+        auto_login: Some(true),
+        base_url_demo: "https://demo-api.ig.com/gateway/deal".to_string(),
+        base_url_live: "https://api.ig.com/gateway/deal".to_string(),
+        encrypted_password: None,
+        execution_environment: ExecutionEnvironment::Demo,
+        logger: LogType::StdLogs,
+        password: "test_password".to_string(),
+        rate_limiter: None,
+        retry_policy: None,
+        session_version: Some(2),
+        streaming_api_max_connection_attempts: None,
+        username: "test_username".to_string(),
+    };
+
+    RestApi::with_transport(config, Arc::new(transport))
+        .await
+        .unwrap()
+}
+
+/// Midnight of the given day of June 2024.
+fn june_2024(day: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 6, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// CASSETTE INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn cassette_account_and_session_responses_are_deserialized() {
+    let api = replay_cassette("ig_demo_v2.json").await;
+
+    let session = api.session_post().await.unwrap().data;
+    assert!(matches!(session, SessionPostResponse::V2(_)));
+    assert_eq!(session.account_id(), "ABC123");
+
+    let accounts = api.accounts_get().await.unwrap().data.accounts;
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(accounts[1].account_type, AccountType::Spreadbet);
+    assert_eq!(accounts[1].status, AccountStatus::Enabled);
+
+    let preferences = api.accounts_preferences_get().await.unwrap().data;
+    assert!(!preferences.trailing_stops_enabled);
+
+    let session = api.session_get(None).await.unwrap().data;
+    assert_eq!(session.account_id, "ABC123");
+    assert_eq!(session.locale, "en_GB");
+}
+
+#[tokio::test]
+async fn cassette_dealing_responses_are_deserialized() {
+    let api = replay_cassette("ig_demo_v2.json").await;
+
+    let positions = api.positions_get().await.unwrap().data.positions;
+    assert_eq!(positions[0].position.deal_id, "DIAAAAPB5K6EPAS");
+    assert_eq!(positions[0].position.direction, Direction::Buy);
+    assert_eq!(
+        positions[0].position.created_date_utc.to_rfc3339(),
+        "2024-06-03T07:15:02+00:00"
+    );
+    assert_eq!(positions[0].market.market_status, MarketStatus::Tradeable);
+
+    let working_orders = api.workingorders_get().await.unwrap().data;
+    assert_eq!(working_orders.working_orders.len(), 1);
+
+    let confirm = api
+        .confirms_get(ConfirmsGetRequest {
+            deal_reference: "7DQUQJ3DUP244T4".parse().unwrap(),
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(confirm.deal_id, "DIAAAAPB5K6EPAS");
+    assert!(matches!(confirm.reason, DealReason::Success));
+    assert!(matches!(confirm.deal_status, DealStatus::Accepted));
+
+    // Sprint market positions are created with a POST request, which only matches the
+    // recorded POST interaction.
+    let sprint_market_position = api
+        .positions_sprintmarkets_post(SprintMarketPositionsPostRequest {
+            deal_reference: None,
+            direction: Some(Direction::Buy),
+            epic: "FM.D.EURUSD24.EURUSD24.IP".parse().unwrap(),
+            expiry_period: Some(SprintMarketExpiryPeriod::FiveMinutes),
+            size: Number::from(10),
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(sprint_market_position.deal_reference, "QK4JWA8EG6UTYP9");
+}
+
+#[tokio::test]
+async fn cassette_market_responses_are_deserialized() {
+    let api = replay_cassette("ig_demo_v2.json").await;
+
+    let market = api
+        .market_get("IX.D.DAX.IFMM.IP".parse().unwrap())
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(market.instrument.name, "Germany 40");
+    assert_eq!(market.instrument.margin_deposit_bands.len(), 3);
+    assert_eq!(
+        market.instrument.opening_hours.unwrap().market_times[0].close_time,
+        "22:00"
+    );
+    assert_eq!(
+        market.dealing_rules.min_deal_size.value,
+        "0.5".parse::<Number>().unwrap()
+    );
+
+    let markets = api.markets_search("DAX").await.unwrap().data;
+    assert_eq!(markets[0].epic, "IX.D.DAX.IFMM.IP");
+
+    let navigation = api.marketnavigation_get(None).await.unwrap().data;
+    assert_eq!(navigation.nodes.unwrap()[0].name, "Indices");
+    assert!(navigation.markets.is_none());
+
+    let sentiment = api
+        .clientsentiment_market_get("DAX".to_string())
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(sentiment.long_position_percentage, 61.0);
+
+    let watchlists = api.watchlists_get().await.unwrap().data.watchlists;
+    assert!(watchlists[0].default_system_watchlist);
+    assert_eq!(watchlists[1].id, "12345678");
+}
+
+#[tokio::test]
+async fn cassette_price_and_history_responses_are_deserialized() {
+    let api = replay_cassette("ig_demo_v2.json").await;
+
+    let prices = api
+        .prices_get(
+            &"IX.D.DAX.IFMM.IP".parse().unwrap(),
+            PricesGetRequest {
+                resolution: Some(Resolution::Hour),
+                from: Some(june_2024(3).with_hour(9).unwrap()),
+                to: Some(june_2024(3).with_hour(10).unwrap()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(prices.prices.len(), 1);
+    assert_eq!(prices.metadata.allowance.remaining_allowance, 9998);
+    assert_eq!(
+        prices.prices[0].snapshot_time_utc.to_rfc3339(),
+        "2024-06-03T07:00:00+00:00"
+    );
+
+    let activities = api
+        .history_activity_get(ActivityHistoryGetRequest {
+            from: june_2024(3),
+            to: Some(june_2024(4)),
+            detailed: Some(false),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .data
+        .activities;
+    assert_eq!(activities[0].deal_id, "DIAAAAPB5K6EPAS");
+    assert!(activities[0].details.is_none());
+
+    let transactions = api
+        .history_transactions_get(TransactionHistoryGetRequest {
+            r#type: Some(TransactionType::AllDeal),
+            from: june_2024(3),
+            to: Some(june_2024(4)),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .data
+        .transactions;
+    assert_eq!(
+        transactions[0].profit_and_loss_amount("EUR"),
+        Some(Money {
+            amount: "21.60".parse().unwrap(),
+            currency: "EUR".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn cassette_older_api_version_responses_are_deserialized() {
+    let api = replay_cassette("ig_demo_v2.json").await;
+
+    let positions = api.positions_v1_get().await.unwrap().data.positions;
+    assert_eq!(positions[0].position.deal_size, Number::from(1));
+    assert_eq!(
+        positions[0].position.open_level,
+        "18510.4".parse::<Number>().unwrap()
+    );
+    assert_eq!(positions[0].market.update_time.to_string(), "10:21:43");
+
+    let position = api
+        .position_v1_post(PositionV1PostRequest {
+            currency_code: "EUR".to_string(),
+            direction: Direction::Buy,
+            epic: "IX.D.DAX.IFMM.IP".parse().unwrap(),
+            expiry: "-".to_string(),
+            force_open: true,
+            guaranteed_stop: false,
+            level: None,
+            limit_distance: None,
+            limit_level: None,
+            order_type: OrderType::Market,
+            quote_id: None,
+            size: Number::from(1),
+            stop_distance: None,
+            stop_level: Some(Number::from(18400)),
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(position.deal_reference, "9WDHK3ZQM2L4XRT");
+
+    let working_orders = api
+        .workingorders_v1_get()
+        .await
+        .unwrap()
+        .data
+        .working_orders;
+    assert!(matches!(
+        working_orders[0].working_order_data.request_type,
+        WorkingOrderV1RequestType::LimitOrder
+    ));
+    assert_eq!(working_orders[0].market_data.epic, "CS.D.EURUSD.MINI.IP");
+
+    for market in [
+        api.market_v1_get("IX.D.DAX.IFMM.IP".parse().unwrap())
+            .await
+            .unwrap()
+            .data,
+        api.market_v2_get("IX.D.DAX.IFMM.IP".parse().unwrap())
+            .await
+            .unwrap()
+            .data,
+    ] {
+        assert_eq!(market.instrument.name, "Germany 40");
+        assert_eq!(market.snapshot.market_status, MarketStatus::Tradeable);
+    }
+
+    let epic = "IX.D.DAX.IFMM.IP".parse().unwrap();
+    let prices = api
+        .prices_v1_get(
+            &epic,
+            Resolution::Hour,
+            PricesV1GetRequest {
+                end_date: june_2024(3).with_hour(10).unwrap(),
+                start_date: june_2024(3).with_hour(9).unwrap(),
+            },
+        )
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(prices.allowance.remaining_allowance, 9997);
+    assert_eq!(
+        prices.prices[0].snapshot_time.date_time,
+        june_2024(3).with_hour(9).unwrap()
+    );
+
+    let prices = api
+        .prices_v2_get(
+            &epic,
+            Resolution::Hour,
+            PriceRange::DateRange {
+                from: june_2024(3).with_hour(9).unwrap(),
+                to: june_2024(3).with_hour(10).unwrap(),
+            },
+        )
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(prices.prices.len(), 1);
+}
+
+#[tokio::test]
+async fn cassette_older_history_responses_are_deserialized() {
+    let api = replay_cassette("ig_demo_v2.json").await;
+    let period = || HistoryPeriod::DateRange {
+        from: june_2024(3).date(),
+        to: june_2024(4).date(),
+    };
+
+    let activities = api
+        .history_activity_v1_get(period())
+        .await
+        .unwrap()
+        .data
+        .activities;
+    assert_eq!(activities[0].deal_id, "DIAAAAPB5K6EPAS");
+    assert!(matches!(
+        activities[0].action_status,
+        ActivityV1ActionStatus::Accept
+    ));
+    assert_eq!(activities[0].size, "+1");
+
+    let history = api
+        .history_activity_v2_get(ActivityHistoryV2GetRequest {
+            from: Some(june_2024(3)),
+            to: Some(june_2024(4)),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(history.activities[0].market_name, "Germany 40");
+    assert_eq!(history.metadata.page_data.total_pages, 1);
+
+    let transactions = api
+        .history_transactions_v1_get(TransactionType::AllDeal, period())
+        .await
+        .unwrap()
+        .data
+        .transactions;
+    assert_eq!(
+        transactions[0].close_level_value(),
+        Some(Number::from(18532))
+    );
+    assert_eq!(
+        transactions[0].profit_and_loss_amount("EUR"),
+        Some(Money {
+            amount: "21.60".parse().unwrap(),
+            currency: "EUR".to_string(),
+        })
+    );
+}
@@ -0,0 +1,450 @@
+#![cfg(feature = "fake-server")]
+
+use chrono::{Duration, Utc};
+use ig_trading_api::common::*;
+use ig_trading_api::rest_api::*;
+use ig_trading_api::rest_cassette::{RecordingTransport, ReplayTransport};
+use ig_trading_api::rest_fake_server::FakeIgServer;
+use ig_trading_api::rest_models::*;
+use ig_trading_api::rest_transport::ReqwestTransport;
+use std::sync::Arc;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// FUNCTIONS SHARED BY ALL THE FAKE SERVER INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Start a new fake server and create a REST API instance logged in to it with the given
+/// session version.
+async fn start_fake_server(session_version: usize) -> (FakeIgServer, RestApi) {
+    let server = FakeIgServer::start().await.unwrap();
+    let config = ApiConfig {
+        session_version: Some(session_version),
+        ..server.api_config()
+    };
+    let api = RestApi::new(config).await.unwrap();
+
+    (server, api)
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+//
+// FAKE SERVER INTEGRATION TESTS.
+//
+////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn fake_server_positions_flow_works() {
+    let (_server, api) = start_fake_server(2).await;
+
+    // Open a position and check its confirmation.
+    let position_request = PositionPostRequest {
+        currency_code: "EUR".to_string(),
+        deal_reference: None,
+        direction: Direction::Buy,
+        epic: "IX.D.DAX.IFMM.IP".parse().unwrap(),
+        expiry: "-".to_string(),
+        force_open: true,
+        guaranteed_stop: false,
+        level: None,
+        limit_distance: None,
+        limit_level: None,
+        order_type: OrderType::Market,
+        quote_id: None,
+        size: Number::from(2),
+        stop_distance: None,
+        stop_level: None,
+        time_in_force: None,
+        trailing_stop: None,
+        trailing_stop_increment: None,
+    };
+    let position_response = api.position_post(position_request).await.unwrap().data;
+    let confirm = api
+        .confirms_get(ConfirmsGetRequest {
+            deal_reference: position_response.deal_reference,
+        })
+        .await
+        .unwrap()
+        .data;
+    assert!(matches!(confirm.deal_status, DealStatus::Accepted));
+    assert!(matches!(
+        confirm.affected_deals[0].status,
+        AffectedDealStatus::Opened
+    ));
+    let deal_id = confirm.deal_id;
+    let level = confirm.level.unwrap();
+
+    // The new position shows up in the list of open positions.
+    let positions = api.positions_get().await.unwrap().data;
+    assert_eq!(positions.positions.len(), 1);
+    assert_eq!(positions.positions[0].position.deal_id, deal_id);
+    assert_eq!(positions.positions[0].position.size, Number::from(2));
+    let created = positions.positions[0].position.created_date_utc;
+    assert!(Utc::now() - created < Duration::minutes(1));
+
+    // Update the stop and limit levels of the position.
+    let position_update_request = PositionPutRequest {
+        limit_level: Some(level + Number::from(100)),
+        stop_level: Some(level - Number::from(50)),
+        ..Default::default()
+    };
+    api.position_put(position_update_request, deal_id.clone())
+        .await
+        .unwrap();
+    let position = api
+        .position_get(PositionGetRequest {
+            deal_id: deal_id.clone(),
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(
+        position.position.limit_level,
+        Some(level + Number::from(100))
+    );
+    assert_eq!(position.position.stop_level, Some(level - Number::from(50)));
+
+    // Close the position partially and then fully.
+    for (size, expected_positions) in [(Number::from(1), 1), (Number::from(1), 0)] {
+        let position_close_request = PositionDeleteRequest {
+            deal_id: Some(deal_id.clone()),
+            direction: Some(Direction::Sell),
+            expiry: Some("-".to_string()),
+            order_type: Some(OrderType::Market),
+            size,
+            ..Default::default()
+        };
+        api.position_delete(position_close_request).await.unwrap();
+
+        let positions = api.positions_get().await.unwrap().data;
+        assert_eq!(positions.positions.len(), expected_positions);
+    }
+
+    // Both closes are recorded as transactions, and every deal as an activity.
+    let from = (Utc::now() - Duration::days(1)).naive_utc();
+    let transactions = api
+        .history_transactions_get(TransactionHistoryGetRequest {
+            from,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(transactions.transactions.len(), 2);
+    let account_currency = &api.accounts_get().await.unwrap().data.accounts[0].currency;
+    for transaction in &transactions.transactions {
+        let profit_and_loss = transaction
+            .profit_and_loss_amount(account_currency)
+            .unwrap();
+        assert_eq!(profit_and_loss.currency, "EUR");
+        // The symbol of the EUR position currency.
+        assert_eq!(transaction.currency, "E");
+        assert!(transaction.profit_and_loss.starts_with('E'));
+        assert_eq!(transaction.size_value(), Some(Number::from(1)));
+        assert!(transaction.open_level_value().is_some());
+    }
+
+    let activity = api
+        .history_activity_get(ActivityHistoryGetRequest {
+            from,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(activity.activities.len(), 4);
+
+    // The same history is served by version 1 of the API.
+    let transactions = api
+        .history_transactions_v1_get(
+            TransactionType::AllDeal,
+            HistoryPeriod::LastPeriod(std::time::Duration::from_secs(3600)),
+        )
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(transactions.transactions.len(), 2);
+
+    let today = Utc::now().date_naive();
+    let activity = api
+        .history_activity_v1_get(HistoryPeriod::DateRange {
+            from: today - Duration::days(1),
+            to: today,
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(activity.activities.len(), 4);
+    assert!(matches!(
+        activity.activities[0].action_status,
+        ActivityV1ActionStatus::Accept
+    ));
+}
+
+#[tokio::test]
+async fn fake_server_workingorders_flow_works() {
+    let (_server, api) = start_fake_server(2).await;
+
+    let working_order_request = WorkingOrderPostRequest {
+        currency_code: "EUR".to_string(),
+        deal_reference: None,
+        direction: Direction::Buy,
+        epic: "IX.D.DAX.IFMM.IP".parse().unwrap(),
+        expiry: "-".to_string(),
+        force_open: None,
+        good_till_date: None,
+        guaranteed_stop: false,
+        level: Number::from(17_500),
+        limit_distance: None,
+        limit_level: None,
+        size: Number::from(1),
+        stop_distance: None,
+        stop_level: None,
+        time_in_force: WorkingOrderTimeInForce::GoodTillCancelled,
+        r#type: WorkingOrderType::Limit,
+    };
+    let working_order_response = api
+        .workingorders_post(&working_order_request)
+        .await
+        .unwrap()
+        .data;
+    let confirm = api
+        .confirms_get(ConfirmsGetRequest {
+            deal_reference: working_order_response.deal_reference,
+        })
+        .await
+        .unwrap()
+        .data;
+    let deal_id = confirm.deal_id;
+
+    let working_orders = api.workingorders_get().await.unwrap().data;
+    assert_eq!(working_orders.working_orders.len(), 1);
+
+    let working_order_update_request = WorkingOrderPutRequest {
+        level: Number::from(17_400),
+        time_in_force: WorkingOrderTimeInForce::GoodTillCancelled,
+        r#type: WorkingOrderType::Limit,
+        ..Default::default()
+    };
+    let working_order_response = api
+        .workingorders_put(&working_order_update_request, deal_id.clone())
+        .await
+        .unwrap()
+        .data;
+    let confirm = api
+        .confirms_get(ConfirmsGetRequest {
+            deal_reference: working_order_response.deal_reference,
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(confirm.level, Some(Number::from(17_400)));
+
+    api.workingorders_delete(deal_id).await.unwrap();
+    let working_orders = api.workingorders_get().await.unwrap().data;
+    assert!(working_orders.working_orders.is_empty());
+}
+
+#[tokio::test]
+async fn fake_server_markets_and_prices_work() {
+    let (_server, api) = start_fake_server(2).await;
+
+    let market = api
+        .market_get("CS.D.EURUSD.MINI.IP".parse().unwrap())
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(market.instrument.epic, "CS.D.EURUSD.MINI.IP");
+
+    let markets = api
+        .markets_get(MarketsGetRequest {
+            epics: vec![
+                "IX.D.DAX.IFMM.IP".parse().unwrap(),
+                "IX.D.FTSE.DAILY.IP".parse().unwrap(),
+            ],
+            filter: None,
+        })
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(markets.market_details.len(), 2);
+
+    let epics: Vec<Epic> = vec![
+        "IX.D.FTSE.DAILY.IP".parse().unwrap(),
+        "CS.D.EURUSD.MINI.IP".parse().unwrap(),
+        "IX.D.FTSE.DAILY.IP".parse().unwrap(),
+    ];
+    let batched = api.markets_get_batched(epics, None).await.unwrap();
+    assert_eq!(batched.batches.len(), 1);
+    assert_eq!(batched.data.market_details.len(), 2);
+    assert_eq!(
+        batched.data.market_details[1].instrument.epic,
+        "CS.D.EURUSD.MINI.IP"
+    );
+
+    let prices = api
+        .prices_get(
+            &"IX.D.DAX.IFMM.IP".parse().unwrap(),
+            PricesGetRequest {
+                resolution: Some(Resolution::Hour),
+                max: Some(5),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(prices.prices.len(), 5);
+    assert_eq!(prices.metadata.allowance.remaining_allowance, 9_995);
+}
+
+#[tokio::test]
+async fn fake_server_expired_sessions_are_renewed() {
+    for session_version in [2, 3] {
+        let (server, api) = start_fake_server(session_version).await;
+        let auth_headers = api.client.auth_headers();
+
+        // The client logs in again, or refreshes the access token, and replays the request.
+        server.expire_sessions();
+        let accounts = api.accounts_get().await.unwrap().data;
+        assert_eq!(accounts.accounts.len(), 1);
+        assert_ne!(api.client.auth_headers(), auth_headers);
+    }
+}
+
+#[tokio::test]
+async fn fake_server_session_post_works() {
+    for session_version in [1, 2, 3] {
+        let (_server, api) = start_fake_server(session_version).await;
+
+        let response = api.session_post().await.unwrap();
+        assert_eq!(response.meta.api_version, session_version);
+        assert_eq!(response.data.account_id(), "ABC123");
+        assert!(!response.data.lightstreamer_endpoint().is_empty());
+
+        match response.data {
+            SessionPostResponse::V1(session) => {
+                assert_eq!(session_version, 1);
+                assert_eq!(session.accounts.len(), 1);
+                assert_eq!(session.account_type, AccountType::Cfd);
+                assert_eq!(session.client_type.as_deref(), Some("RETAIL"));
+                assert_eq!(session.currency_iso_code, "EUR");
+                assert!(session.dealing_enabled);
+            }
+            SessionPostResponse::V2(session) => {
+                assert_eq!(session_version, 2);
+                assert_eq!(session.accounts.len(), 1);
+                assert_eq!(session.account_type, AccountType::Cfd);
+                assert_eq!(session.currency_iso_code, "EUR");
+                assert!(session.dealing_enabled);
+            }
+            SessionPostResponse::V3(session) => {
+                assert_eq!(session_version, 3);
+                assert!(!session.oauth_token.access_token.is_empty());
+            }
+        }
+
+        // The new session is used by subsequent requests.
+        let accounts = api.accounts_get().await.unwrap().data;
+        assert_eq!(accounts.accounts.len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn fake_server_switch_account_works() {
+    for session_version in [2, 3] {
+        let (server, api) = start_fake_server(session_version).await;
+        server.add_account("XYZ789", AccountType::Spreadbet);
+
+        api.switch_account(&"XYZ789".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(api.client.account_id().as_deref(), Some("XYZ789"));
+        let session = api.session_get(None).await.unwrap().data;
+        assert_eq!(session.account_id, "XYZ789");
+
+        // The active account outlives an expired session.
+        server.expire_sessions();
+        let session = api.session_get(None).await.unwrap().data;
+        assert_eq!(session.account_id, "XYZ789");
+
+        // Switching to the active account is rejected and leaves the client state untouched.
+        assert!(api
+            .switch_account(&"XYZ789".parse().unwrap())
+            .await
+            .is_err());
+        assert_eq!(api.client.account_id().as_deref(), Some("XYZ789"));
+    }
+}
+
+#[tokio::test]
+async fn fake_server_encrypted_password_login_works() {
+    let server = FakeIgServer::start().await.unwrap();
+    let config = ApiConfig {
+        encrypted_password: Some(true),
+        ..server.api_config()
+    };
+    let api = RestApi::new(config).await.unwrap();
+
+    let accounts = api.accounts_get().await.unwrap().data;
+    assert_eq!(accounts.accounts.len(), 1);
+
+    // A wrong password is still rejected when encrypted.
+    let config = ApiConfig {
+        encrypted_password: Some(true),
+        password: "wrong_password".to_string(),
+        ..server.api_config()
+    };
+    assert!(RestApi::new(config).await.is_err());
+}
+
+#[tokio::test]
+async fn fake_server_cassettes_are_recorded_and_replayed() {
+    let server = FakeIgServer::start().await.unwrap();
+    let path = std::env::temp_dir().join(format!(
+        "ig_trading_api_fake_server_cassette_{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    // Record a session through the fake server.
+    let recorder =
+        RecordingTransport::new(Arc::new(ReqwestTransport::default()), path.clone()).unwrap();
+    let config = server.api_config();
+    let api = RestApi::with_transport(config.clone(), Arc::new(recorder))
+        .await
+        .unwrap();
+    let recorded_accounts = api.accounts_get().await.unwrap().data;
+    let recorded_market = api
+        .market_get("IX.D.DAX.IFMM.IP".parse().unwrap())
+        .await
+        .unwrap()
+        .data;
+    drop(api);
+    drop(server);
+
+    // Replay it without the server, with the same results and no secrets in the cassette.
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains(&config.password));
+
+    let replayer = ReplayTransport::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let api = RestApi::with_transport(config, Arc::new(replayer))
+        .await
+        .unwrap();
+    let accounts = api.accounts_get().await.unwrap().data;
+    let market = api
+        .market_get("IX.D.DAX.IFMM.IP".parse().unwrap())
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(
+        accounts.accounts[0].account_id,
+        recorded_accounts.accounts[0].account_id
+    );
+    assert_eq!(market.instrument.epic, recorded_market.instrument.epic);
+
+    // Every recorded interaction has been replayed.
+    assert!(api.accounts_get().await.is_err());
+}